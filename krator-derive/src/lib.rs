@@ -1,15 +1,22 @@
 //! A crate for deriving state machine traits in Kubelet.
 //!
-//! Right now this crate consists of a [TransitionTo] derive macro for the `TransitionTo` trait.
+//! Right now this crate consists of a [TransitionTo] derive macro for the `TransitionTo` trait,
+//! a [StateMachine] derive macro which declares a whole state graph from an enum, and an
+//! [ObjectStatus] derive macro for status types.
 //!
 //! In addition to the `derive` attribute, this macro
 //! also requires the use of a custom attribute called `transition_to` that specifies the types that
 //! can be transitioned to. Not specifying this attribute will result in a compile time error.
 //!
+//! If the feature `graph` is enabled, the [TransitionTo] derive macro also implements
+//! `krator::graph::Transitions`, so that the state graph can be exported.
+//!
 //! If the feature `admission-webhook` is enabled, this crate provides a [AdmissionWebhook] derive macro that
 //! provides functions for creating necessary resources for running a admission webhook.
 extern crate proc_macro;
 use crate::proc_macro::TokenStream;
+mod object_status;
+mod state_machine;
 mod transitions;
 
 #[proc_macro_derive(TransitionTo, attributes(transition_to))]
@@ -17,6 +24,112 @@ pub fn derive_transition_to(input: TokenStream) -> TokenStream {
     transitions::run_custom_derive(input)
 }
 
+/// Declares every state of a state machine as a variant of an enum.
+///
+/// For each variant, a struct of the same name and with the same fields is generated, along with
+/// `TransitionTo` implementations for the states listed in the variant's `transition_to`
+/// attribute. Variants without the attribute are terminal states. Unit variants also derive
+/// `Default`, as required of an operator's `InitialState` and `DeletedState`. The enum itself is
+/// only used as a declaration.
+///
+/// With `#[state_machine(object_state = ...)]` on the enum, a `State` implementation is
+/// generated for each state as well. Terminal states complete the state machine. Other states
+/// call an inherent `run` method with the arguments of `State::next`, which holds their
+/// behaviour. The status is left unchanged, unless the variant is marked
+/// `#[state_machine(status)]`, in which case an inherent `report_status` method with the
+/// arguments of `State::status` is called. Variants marked `#[state_machine(manual)]` implement
+/// `State` by hand.
+///
+/// ```ignore
+/// #[derive(StateMachine)]
+/// #[allow(dead_code)]
+/// enum MooseStates {
+///     /// Moose was tagged.
+///     #[transition_to(Roam)]
+///     Tagged,
+///     /// Moose is roaming the wilderness.
+///     #[transition_to(Eat)]
+///     Roam,
+///     /// Moose is eating.
+///     #[transition_to(Sleep, Roam)]
+///     Eat { food: f64 },
+///     /// Moose is sleeping.
+///     #[transition_to(Roam)]
+///     Sleep,
+///     /// Moose was released.
+///     Released,
+/// }
+///
+/// // Generates `struct Tagged;`, `struct Eat { food: f64 }`, ...,
+/// // `impl TransitionTo<Roam> for Tagged {}`, ...
+/// ```
+///
+/// ```ignore
+/// #[derive(StateMachine)]
+/// #[state_machine(object_state = MooseState)]
+/// #[allow(dead_code)]
+/// enum MooseStates {
+///     #[transition_to(Released)]
+///     #[state_machine(status)]
+///     Tagged,
+///     Released,
+/// }
+///
+/// impl Tagged {
+///     async fn run(
+///         self: Box<Self>,
+///         _shared: SharedState<MooseShared>,
+///         _state: &mut MooseState,
+///         _manifest: Manifest<Moose>,
+///     ) -> Transition<MooseState> {
+///         Transition::next(self, Released)
+///     }
+///
+///     async fn report_status(
+///         &self,
+///         _state: &mut MooseState,
+///         _manifest: &Moose,
+///     ) -> anyhow::Result<Option<MooseStatus>> {
+///         Ok(Some(MooseStatus::tagged()))
+///     }
+/// }
+///
+/// // `Released` completes the state machine without any code.
+/// ```
+#[proc_macro_derive(StateMachine, attributes(transition_to, state_machine))]
+pub fn derive_state_machine(input: TokenStream) -> TokenStream {
+    state_machine::run_custom_derive(input)
+}
+
+/// Implements `krator::ObjectStatus` for a status struct.
+///
+/// The generated `json_patch` serializes the whole struct under `status`, so the struct must
+/// implement `Serialize`. Fields left unset should be skipped with
+/// `#[serde(skip_serializing_if = "Option::is_none")]`, otherwise they are cleared.
+///
+/// The generated `failed` starts from `Default::default()` and records the error in the field
+/// marked `#[status(message)]`, which may be a `String` or an `Option<String>`, and as a
+/// `Ready` condition with status `False` and reason `Failed` in the field marked
+/// `#[status(conditions)]`, which must be a `Vec<krator::Condition>`. At least one of the two
+/// must be marked.
+///
+/// ```ignore
+/// #[derive(Default, Serialize, ObjectStatus)]
+/// struct MooseStatus {
+///     #[serde(skip_serializing_if = "Option::is_none")]
+///     phase: Option<MoosePhase>,
+///     #[status(message)]
+///     #[serde(skip_serializing_if = "Option::is_none")]
+///     message: Option<String>,
+///     #[status(conditions)]
+///     conditions: Vec<krator::Condition>,
+/// }
+/// ```
+#[proc_macro_derive(ObjectStatus, attributes(status))]
+pub fn derive_object_status(input: TokenStream) -> TokenStream {
+    object_status::run_custom_derive(input)
+}
+
 #[cfg(feature = "admission-webhook")]
 mod admission;
 
@@ -0,0 +1,133 @@
+use crate::proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Ident, Meta, NestedMeta};
+
+const ATTRIBUTE_NAME: &str = "status";
+
+pub fn run_custom_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+    let fields = match input.data {
+        Data::Struct(data) => match data.fields {
+            Fields::Named(fields) => fields.named,
+            _ => {
+                let message = format!(
+                    "`ObjectStatus` can only be derived for structs with named fields, but `{}` has none",
+                    name
+                );
+                return TokenStream::from(Error::new(name.span(), message).to_compile_error());
+            }
+        },
+        _ => {
+            let message = format!(
+                "`ObjectStatus` can only be derived for structs, but `{}` is not a struct",
+                name
+            );
+            return TokenStream::from(Error::new(name.span(), message).to_compile_error());
+        }
+    };
+
+    let mut message_field: Option<Ident> = None;
+    let mut conditions_field: Option<Ident> = None;
+    for field in fields {
+        for marker in match field_markers(&field.attrs) {
+            Ok(markers) => markers,
+            Err(error) => return TokenStream::from(error.to_compile_error()),
+        } {
+            let slot = if marker == "message" {
+                &mut message_field
+            } else if marker == "conditions" {
+                &mut conditions_field
+            } else {
+                let message = format!(
+                    "Unknown `{}` attribute `{}`. Expected `message` or `conditions`",
+                    ATTRIBUTE_NAME, marker
+                );
+                return TokenStream::from(Error::new(marker.span(), message).to_compile_error());
+            };
+            if slot.is_some() {
+                let message = format!(
+                    "Multiple fields of `{}` are marked `{}`. Please mark only one field",
+                    name, marker
+                );
+                return TokenStream::from(Error::new(marker.span(), message).to_compile_error());
+            }
+            *slot = field.ident.clone();
+        }
+    }
+
+    if message_field.is_none() && conditions_field.is_none() {
+        let message = format!(
+            "No field of `{}` is marked `#[{}(message)]` or `#[{}(conditions)]`, so failures cannot be reported",
+            name, ATTRIBUTE_NAME, ATTRIBUTE_NAME
+        );
+        return TokenStream::from(Error::new(name.span(), message).to_compile_error());
+    }
+
+    let set_message = message_field.map(|field| {
+        quote! {
+            status.#field = e.to_string().into();
+        }
+    });
+    let set_condition = conditions_field.map(|field| {
+        quote! {
+            status.#field.push(
+                krator::Condition::new("Ready", false)
+                    .with_reason("Failed")
+                    .with_message(e),
+            );
+        }
+    });
+
+    let expanded = quote! {
+        #[automatically_derived]
+        impl krator::ObjectStatus for #name {
+            fn json_patch(&self) -> krator::__private::serde_json::Value {
+                krator::__private::serde_json::json!({ "status": self })
+            }
+
+            fn failed(e: &str) -> Self {
+                let mut status: Self = Default::default();
+                #set_message
+                #set_condition
+                status
+            }
+        }
+    };
+    TokenStream::from(expanded)
+}
+
+/// Idents listed in the `status` attributes of a field.
+fn field_markers(attrs: &[syn::Attribute]) -> syn::Result<Vec<Ident>> {
+    let mut markers = vec![];
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident(ATTRIBUTE_NAME))
+    {
+        match attr.parse_meta()? {
+            Meta::List(list) => {
+                for nested in list.nested {
+                    match nested {
+                        NestedMeta::Meta(Meta::Path(path)) if path.get_ident().is_some() => {
+                            markers.push(path.get_ident().cloned().unwrap());
+                        }
+                        other => {
+                            return Err(Error::new_spanned(
+                                other,
+                                "Expected `message` or `conditions`",
+                            ))
+                        }
+                    }
+                }
+            }
+            other => {
+                return Err(Error::new_spanned(
+                    other,
+                    "Expected `#[status(message)]` or `#[status(conditions)]`",
+                ))
+            }
+        }
+    }
+    Ok(markers)
+}
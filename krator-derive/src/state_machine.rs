@@ -0,0 +1,257 @@
+use crate::proc_macro::TokenStream;
+use crate::transitions::get_transitions;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    token::Comma,
+    Attribute, Data, DeriveInput, Error, Fields, Ident, Path, Result, Type,
+};
+
+const ATTRIBUTE_NAME: &str = "state_machine";
+
+/// Options of the `state_machine` attribute on the enum.
+struct MachineOptions {
+    /// Generate `State` implementations for this object state.
+    object_state: Type,
+}
+
+impl Parse for MachineOptions {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let key: Ident = input.parse()?;
+        if key != "object_state" {
+            return Err(Error::new(
+                key.span(),
+                format!("Unknown `{}` option `{}`", ATTRIBUTE_NAME, key),
+            ));
+        }
+        input.parse::<syn::Token![=]>()?;
+        Ok(MachineOptions {
+            object_state: input.parse()?,
+        })
+    }
+}
+
+/// Options of the `state_machine` attribute on a variant.
+#[derive(Default)]
+struct StateOptions {
+    /// The `State` implementation is written by hand.
+    manual: bool,
+    /// The status is reported by an inherent `report_status` method.
+    status: bool,
+}
+
+impl Parse for StateOptions {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut options = StateOptions::default();
+        for key in input.parse_terminated::<Ident, Comma>(Ident::parse)? {
+            if key == "manual" {
+                options.manual = true;
+            } else if key == "status" {
+                options.status = true;
+            } else {
+                return Err(Error::new(
+                    key.span(),
+                    format!("Unknown `{}` option `{}`", ATTRIBUTE_NAME, key),
+                ));
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn is_option(attr: &Attribute) -> bool {
+    attr.path.is_ident(ATTRIBUTE_NAME)
+}
+
+pub fn run_custom_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+    let vis = input.vis;
+    let machine = match input
+        .attrs
+        .iter()
+        .filter(|attr| is_option(attr))
+        .map(|attr| attr.parse_args::<MachineOptions>())
+        .last()
+        .transpose()
+    {
+        Ok(machine) => machine,
+        Err(error) => return TokenStream::from(error.to_compile_error()),
+    };
+    let data = match input.data {
+        Data::Enum(data) => data,
+        _ => {
+            let message = format!(
+                "`StateMachine` can only be derived for enums, but `{}` is not an enum",
+                name
+            );
+            return TokenStream::from(Error::new(name.span(), message).to_compile_error());
+        }
+    };
+
+    let mut token_stream = TokenStream::new();
+    for variant in data.variants {
+        let state = variant.ident;
+        let docs: Vec<Attribute> = variant
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("doc"))
+            .cloned()
+            .collect();
+        let options = match variant
+            .attrs
+            .iter()
+            .filter(|attr| is_option(attr))
+            .map(|attr| attr.parse_args::<StateOptions>())
+            .last()
+            .transpose()
+        {
+            Ok(options) => options.unwrap_or_default(),
+            Err(error) => return TokenStream::from(error.to_compile_error()),
+        };
+        let mut transitions = get_transitions(variant.attrs);
+        if transitions.len() > 1 {
+            let message = format!(
+                "Multiple `transition_to` attributes found for `{}`. Please specify only one attribute",
+                state
+            );
+            return TokenStream::from(Error::new(state.span(), message).to_compile_error());
+        }
+        let targets = transitions.pop().map(|t| t.all).unwrap_or_default();
+
+        let definition = match variant.fields {
+            Fields::Unit => quote! {
+                #(#docs)*
+                #[derive(Debug, Default)]
+                #vis struct #state;
+            },
+            Fields::Named(fields) => {
+                let fields = fields.named.into_iter().map(|field| {
+                    let attrs = field.attrs;
+                    let ident = field.ident;
+                    let ty = field.ty;
+                    quote! { #(#attrs)* #vis #ident: #ty }
+                });
+                quote! {
+                    #(#docs)*
+                    #[derive(Debug)]
+                    #vis struct #state {
+                        #(#fields),*
+                    }
+                }
+            }
+            Fields::Unnamed(fields) => {
+                let fields = fields.unnamed.into_iter().map(|field| {
+                    let attrs = field.attrs;
+                    let ty = field.ty;
+                    quote! { #(#attrs)* #vis #ty }
+                });
+                quote! {
+                    #(#docs)*
+                    #[derive(Debug)]
+                    #vis struct #state(#(#fields),*);
+                }
+            }
+        };
+        token_stream.extend(TokenStream::from(definition));
+
+        #[cfg(feature = "graph")]
+        {
+            let expanded = quote! {
+                #[automatically_derived]
+                impl krator::graph::Transitions for #state {
+                    fn transitions() -> Vec<krator::graph::StateNode> {
+                        vec![#(krator::graph::StateNode::of::<#targets>()),*]
+                    }
+                }
+            };
+            token_stream.extend(TokenStream::from(expanded));
+        }
+
+        if let Some(ref machine) = machine {
+            if !options.manual {
+                let expanded = state_impl(&state, &machine.object_state, &targets, &options);
+                token_stream.extend(TokenStream::from(expanded));
+            }
+        }
+
+        for target in targets {
+            let expanded = quote! {
+                #[automatically_derived]
+                impl krator::TransitionTo<#target> for #state {}
+            };
+            token_stream.extend(TokenStream::from(expanded));
+        }
+    }
+
+    token_stream
+}
+
+/// The `State` implementation of `state`. Terminal states complete right away, and the others
+/// delegate to an inherent `run` method. The status is left unchanged unless the state reports
+/// it with an inherent `report_status` method.
+fn state_impl(
+    state: &Ident,
+    object_state: &Type,
+    targets: &[Path],
+    options: &StateOptions,
+) -> proc_macro2::TokenStream {
+    let shared_state = quote! { <#object_state as krator::ObjectState>::SharedState };
+    let manifest = quote! { <#object_state as krator::ObjectState>::Manifest };
+    let status = quote! { <#object_state as krator::ObjectState>::Status };
+    let next = if targets.is_empty() {
+        quote! {
+            async fn next(
+                self: Box<Self>,
+                _shared: krator::SharedState<#shared_state>,
+                _state: &mut #object_state,
+                _manifest: krator::Manifest<#manifest>,
+            ) -> krator::Transition<#object_state> {
+                krator::Transition::Complete(Ok(()))
+            }
+        }
+    } else {
+        quote! {
+            async fn next(
+                self: Box<Self>,
+                shared: krator::SharedState<#shared_state>,
+                state: &mut #object_state,
+                manifest: krator::Manifest<#manifest>,
+            ) -> krator::Transition<#object_state> {
+                #state::run(self, shared, state, manifest).await
+            }
+        }
+    };
+    let report = if options.status {
+        quote! {
+            async fn status(
+                &self,
+                state: &mut #object_state,
+                manifest: &#manifest,
+            ) -> krator::__private::anyhow::Result<Option<#status>> {
+                #state::report_status(self, state, manifest).await
+            }
+        }
+    } else {
+        quote! {
+            async fn status(
+                &self,
+                _state: &mut #object_state,
+                _manifest: &#manifest,
+            ) -> krator::__private::anyhow::Result<Option<#status>> {
+                Ok(None)
+            }
+        }
+    };
+    quote! {
+        #[automatically_derived]
+        #[krator::__private::async_trait::async_trait]
+        impl krator::State<#object_state> for #state {
+            #next
+
+            #report
+        }
+    }
+}
@@ -9,8 +9,8 @@ use syn::{
 
 const ATTRIBUTE_NAME: &str = "transition_to";
 
-struct Transitions {
-    all: Vec<Path>,
+pub(crate) struct Transitions {
+    pub(crate) all: Vec<Path>,
 }
 
 impl Parse for Transitions {
@@ -51,7 +51,7 @@ pub fn run_custom_derive(input: TokenStream) -> TokenStream {
     }
 }
 
-fn get_transitions(attrs: Vec<Attribute>) -> Vec<Transitions> {
+pub(crate) fn get_transitions(attrs: Vec<Attribute>) -> Vec<Transitions> {
     attrs
         .into_iter()
         .filter_map(parse_as_transition_attr)
@@ -74,6 +74,20 @@ fn generate_impl(transitions: Transitions, generics: Generics, name: Ident) -> T
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let mut token_stream = TokenStream::new();
 
+    #[cfg(feature = "graph")]
+    {
+        let transition_types = &transitions.all;
+        let expanded = quote! {
+            #[automatically_derived]
+            impl #impl_generics krator::graph::Transitions for #name #ty_generics #where_clause {
+                fn transitions() -> Vec<krator::graph::StateNode> {
+                    vec![#(krator::graph::StateNode::of::<#transition_types>()),*]
+                }
+            }
+        };
+        token_stream.extend(TokenStream::from(expanded));
+    }
+
     for transition_type in transitions.all.into_iter() {
         let expanded = quote! {
             #[automatically_derived]
@@ -134,11 +134,11 @@ impl State<MooseState> for Tagged {
         &self,
         _state: &mut MooseState,
         _manifest: &Moose,
-    ) -> anyhow::Result<MooseStatus> {
-        Ok(MooseStatus {
+    ) -> anyhow::Result<Option<MooseStatus>> {
+        Ok(Some(MooseStatus {
             phase: Some(MoosePhase::Roaming),
             message: None,
-        })
+        }))
     }
 }
 
@@ -203,11 +203,11 @@ impl State<MooseState> for Roam {
         &self,
         _state: &mut MooseState,
         _manifest: &Moose,
-    ) -> anyhow::Result<MooseStatus> {
-        Ok(MooseStatus {
+    ) -> anyhow::Result<Option<MooseStatus>> {
+        Ok(Some(MooseStatus {
             phase: Some(MoosePhase::Roaming),
             message: Some("Gahrooo!".to_string()),
-        })
+        }))
     }
 }
 
@@ -234,11 +234,11 @@ impl State<MooseState> for Eat {
         &self,
         _state: &mut MooseState,
         _manifest: &Moose,
-    ) -> anyhow::Result<MooseStatus> {
-        Ok(MooseStatus {
+    ) -> anyhow::Result<Option<MooseStatus>> {
+        Ok(Some(MooseStatus {
             phase: Some(MoosePhase::Hungry),
             message: Some("*munch*".to_string()),
-        })
+        }))
     }
 }
 
@@ -263,11 +263,11 @@ impl State<MooseState> for Sleep {
         &self,
         _state: &mut MooseState,
         _manifest: &Moose,
-    ) -> anyhow::Result<MooseStatus> {
-        Ok(MooseStatus {
+    ) -> anyhow::Result<Option<MooseStatus>> {
+        Ok(Some(MooseStatus {
             phase: Some(MoosePhase::Asleep),
             message: Some("zzzzzz".to_string()),
-        })
+        }))
     }
 }
 
@@ -291,11 +291,11 @@ impl State<MooseState> for Released {
         &self,
         state: &mut MooseState,
         _manifest: &Moose,
-    ) -> anyhow::Result<MooseStatus> {
-        Ok(MooseStatus {
+    ) -> anyhow::Result<Option<MooseStatus>> {
+        Ok(Some(MooseStatus {
             phase: None,
             message: Some(format!("Bye, {}!", state.name)),
-        })
+        }))
     }
 }
 
@@ -356,7 +356,7 @@ impl Operator for MooseTracker {
     async fn admission_hook(
         &self,
         manifest: Self::Manifest,
-    ) -> krator::admission::AdmissionResult<Self::Manifest> {
+    ) -> krator::admission::AdmissionVerdict<Self::Manifest> {
         use k8s_openapi::apimachinery::pkg::apis::meta::v1::Status;
         // All moose names start with "M"
         let name = manifest.meta().name.clone().unwrap();
@@ -370,6 +370,7 @@ impl Operator for MooseTracker {
                 ..Default::default()
             }),
         }
+        .into()
     }
 
     #[cfg(feature = "admission-webhook")]
@@ -533,15 +534,15 @@ Running moose example. Try to install some of the manifests provided in examples
     {
         use krator::OperatorRuntime;
         let mut runtime = OperatorRuntime::new(&kubeconfig, tracker, Some(params));
-        runtime.start().await;
+        runtime.start().await?;
     }
     #[cfg(not(feature = "admission-webhook"))]
     {
         use krator::{ControllerBuilder, Manager};
         let mut manager = Manager::new(&kubeconfig);
         let controller = ControllerBuilder::new(tracker).with_params(params);
-        manager.register_controller(controller);
-        manager.start().await;
+        manager.register_controller(controller)?;
+        manager.start().await?;
     }
     Ok(())
 }
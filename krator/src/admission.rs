@@ -2,6 +2,7 @@
 use crate::ObjectState;
 use crate::Operator;
 use anyhow::{bail, ensure, Context};
+use futures::future::{BoxFuture, FutureExt};
 use k8s_openapi::{
     api::{
         admissionregistration::v1::MutatingWebhookConfiguration,
@@ -17,11 +18,32 @@ use kube::{
 use serde::{Deserialize, Serialize};
 use std::{
     fmt::{Display, Formatter},
+    future::Future,
     sync::Arc,
+    time::Duration,
 };
-use tracing::{info, trace, warn};
+use tokio_rustls::rustls::{
+    internal::pemfile,
+    sign::{self, CertifiedKey},
+    ClientHello, NoClientAuth, ResolvesServerCert, ServerConfig,
+};
+use tracing::{debug, info, trace, warn};
 use tracing_futures::Instrument;
 
+use crate::metrics::AdmissionMetrics;
+
+#[cfg(feature = "admission-webhook-bootstrap")]
+mod bootstrap;
+#[cfg(feature = "admission-webhook-bootstrap")]
+pub use bootstrap::CertificateBootstrap;
+mod configuration;
+pub(crate) use configuration::{name_stem, WebhookConfigurations, WebhookRegistration};
+pub use configuration::{FailurePolicy, SideEffects};
+#[cfg(feature = "cert-manager")]
+mod cert_manager;
+#[cfg(feature = "cert-manager")]
+pub use cert_manager::CertManagerTls;
+
 /// WebhookResources encapsulates Kubernetes resources necessary to register the admission webhook.
 /// and provides some convenience functions
 ///
@@ -263,6 +285,7 @@ impl Display for WebhookResources {
 
 /// AdmissionTls wraps certificate and private key for the admission webhook server. If you read
 /// the secret from a Kubernetes secret, use the convenience function [AdmissionTls::from()]
+#[derive(Clone, PartialEq, Eq)]
 pub struct AdmissionTls {
     /// tls certificate
     pub cert: String,
@@ -271,11 +294,11 @@ pub struct AdmissionTls {
 }
 
 impl AdmissionTls {
-    /// Convenience function to extract secret data from a Kubernetes secret of type `tls`. It supports
-    /// Secrets that have secrets set via `data` or `string_data`
+    /// Convenience function to extract secret data from a Kubernetes secret of type `tls` or
+    /// `kubernetes.io/tls`. It supports Secrets that have secrets set via `data` or `string_data`
     pub fn from(s: &Secret) -> anyhow::Result<Self> {
         ensure!(
-            s.type_.as_ref().unwrap() == "tls",
+            matches!(s.type_.as_deref(), Some("tls") | Some("kubernetes.io/tls")),
             "only tls secrets can be converted to AdmisstionTLS struct"
         );
 
@@ -318,13 +341,264 @@ impl AdmissionTls {
             metadata.namespace.as_ref().unwrap_or(&"".to_string())
         )
     }
+
+    /// Read the PEM encoded certificate and private key from files, such as
+    /// those of a Secret mounted into the operator's pod.
+    pub async fn from_files(
+        cert: impl AsRef<std::path::Path>,
+        private_key: impl AsRef<std::path::Path>,
+    ) -> anyhow::Result<Self> {
+        let cert = cert.as_ref();
+        let private_key = private_key.as_ref();
+        Ok(AdmissionTls {
+            cert: tokio::fs::read_to_string(cert)
+                .await
+                .with_context(|| format!("Failed to read certificate {}", cert.display()))?,
+            private_key: tokio::fs::read_to_string(private_key)
+                .await
+                .with_context(|| format!("Failed to read private key {}", private_key.display()))?,
+        })
+    }
+
+    /// Parse the certificate chain and private key, which may be PKCS#8 or
+    /// RSA encoded.
+    fn certified_key(&self) -> anyhow::Result<CertifiedKey> {
+        let certs = pemfile::certs(&mut self.cert.as_bytes())
+            .map_err(|()| anyhow::anyhow!("Failed to parse certificate PEM"))?;
+        ensure!(!certs.is_empty(), "No certificate found in PEM");
+        let mut keys = pemfile::pkcs8_private_keys(&mut self.private_key.as_bytes())
+            .map_err(|()| anyhow::anyhow!("Failed to parse private key PEM"))?;
+        if keys.is_empty() {
+            keys = pemfile::rsa_private_keys(&mut self.private_key.as_bytes())
+                .map_err(|()| anyhow::anyhow!("Failed to parse private key PEM"))?;
+        }
+        let key = keys.first().context("No private key found in PEM")?;
+        let key = sign::any_supported_type(key)
+            .map_err(|_| anyhow::anyhow!("Unsupported private key type"))?;
+        Ok(CertifiedKey::new(certs, Arc::new(key)))
+    }
+}
+
+/// Loads the TLS of the admission webhook server, initially and whenever it
+/// is reloaded.
+pub(crate) type TlsSource =
+    Arc<dyn Fn() -> BoxFuture<'static, anyhow::Result<AdmissionTls>> + Send + Sync>;
+
+/// Load TLS with [admission_hook_tls](Operator::admission_hook_tls).
+pub(crate) fn tls_source<O: Operator>(operator: Arc<O>) -> TlsSource {
+    Arc::new(move || {
+        let operator = Arc::clone(&operator);
+        async move { operator.admission_hook_tls().await }.boxed()
+    })
+}
+
+/// TLS served by the built-in server, and how it is reloaded.
+pub(crate) type ServedTls = (AdmissionTls, Option<(TlsSource, Duration)>);
+
+/// Resolves every TLS handshake to the current certificate valid for the
+/// server name the client requested, or to the first certificate. Each
+/// certificate is swapped when it is reloaded.
+struct ReloadingCert(Vec<std::sync::RwLock<CertifiedKey>>);
+
+impl ResolvesServerCert for ReloadingCert {
+    fn resolve(&self, client_hello: ClientHello) -> Option<CertifiedKey> {
+        let keys: Vec<CertifiedKey> = self
+            .0
+            .iter()
+            .map(|key| key.read().expect("Certificate lock poisoned.").clone())
+            .collect();
+        let matching = client_hello.server_name().and_then(|name| {
+            keys.iter()
+                .position(|key| key.cross_check_end_entity_cert(Some(name)).is_ok())
+        });
+        keys.into_iter().nth(matching.unwrap_or(0))
+    }
 }
 
-/// Type signature for validating or mutating webhooks.
+/// Type signature for validating or mutating webhooks, registered with
+/// [ControllerBuilder](crate::ControllerBuilder). Webhooks receive the object
+/// from the request along with the [AdmissionContext] of the request.
+/// [webhook_fn] and [webhook_fn_with_context] box an async closure as a
+/// `WebhookFn`.
 pub type WebhookFn<C> = dyn Fn(
-    <C as Operator>::Manifest,
-    <<C as Operator>::ObjectState as ObjectState>::SharedState,
-) -> AdmissionResult<<C as Operator>::Manifest>;
+        <C as Operator>::Manifest,
+        AdmissionContext,
+    ) -> BoxFuture<'static, AdmissionVerdict<<C as Operator>::Manifest>>
+    + Send
+    + Sync;
+
+/// Details of an admission request beyond the object.
+#[derive(Clone)]
+pub struct AdmissionContext {
+    dry_run: bool,
+    operation: Operation,
+    user: UserInfo,
+    client: Option<Client>,
+}
+
+impl AdmissionContext {
+    /// Whether the request is a dry run, such as
+    /// `kubectl apply --dry-run=server`, which must not cause side effects.
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Operation requested.
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// The user who made the request.
+    pub fn user(&self) -> &UserInfo {
+        &self.user
+    }
+
+    /// Client for the Kubernetes API. Only webhooks declaring
+    /// [SideEffects::NoneOnDryRun] receive one, and only for requests which
+    /// are not a dry run.
+    pub fn client(&self) -> Option<&Client> {
+        self.client.as_ref()
+    }
+}
+
+/// Box an async closure as a [WebhookFn].
+///
+/// ```no_run
+/// # use krator::admission::{webhook_fn, AdmissionResult, WebhookFn};
+/// # use std::sync::Arc;
+/// # fn example<O: krator::Operator>() {
+/// let allow_all: Arc<WebhookFn<O>> =
+///     webhook_fn::<O, _, _>(|manifest| async move { AdmissionResult::Allow(manifest) });
+/// # }
+/// ```
+pub fn webhook_fn<O, F, Fut>(f: F) -> Arc<WebhookFn<O>>
+where
+    O: Operator,
+    F: Fn(O::Manifest) -> Fut + Send + Sync + 'static,
+    Fut: Future + Send + 'static,
+    Fut::Output: Into<AdmissionVerdict<O::Manifest>>,
+{
+    Arc::new(move |manifest, _context| f(manifest).map(Into::into).boxed())
+}
+
+/// Box an async closure receiving the [AdmissionContext] as a [WebhookFn].
+/// Register it with [SideEffects::NoneOnDryRun] through
+/// [ControllerBuilder::with_webhook](crate::ControllerBuilder::with_webhook)
+/// for it to receive a client.
+///
+/// ```no_run
+/// # use krator::admission::{webhook_fn_with_context, AdmissionResult, WebhookFn};
+/// # use std::sync::Arc;
+/// # fn example<O: krator::Operator>() {
+/// let hook: Arc<WebhookFn<O>> = webhook_fn_with_context::<O, _, _>(|manifest, context| async move {
+///     if let Some(_client) = context.client() {
+///         // Record the request somewhere, which is skipped for dry runs.
+///     }
+///     AdmissionResult::Allow(manifest)
+/// });
+/// # }
+/// ```
+pub fn webhook_fn_with_context<O, F, Fut>(f: F) -> Arc<WebhookFn<O>>
+where
+    O: Operator,
+    F: Fn(O::Manifest, AdmissionContext) -> Fut + Send + Sync + 'static,
+    Fut: Future + Send + 'static,
+    Fut::Output: Into<AdmissionVerdict<O::Manifest>>,
+{
+    Arc::new(move |manifest, context| f(manifest, context).map(Into::into).boxed())
+}
+
+/// Whether a webhook may change the objects it admits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WebhookKind {
+    /// Changes to the object are ignored.
+    Validating,
+    /// Changes to the object are sent back as a JSON Patch.
+    Mutating,
+}
+
+/// A webhook registered with a [ControllerBuilder](crate::ControllerBuilder).
+pub(crate) struct Webhook<O: Operator> {
+    pub(crate) path: String,
+    pub(crate) kind: WebhookKind,
+    pub(crate) side_effects: SideEffects,
+    pub(crate) f: Arc<WebhookFn<O>>,
+}
+
+/// Reviews the JSON encoded `AdmissionReview` in a request body, returning
+/// the JSON encoded response.
+type ReviewFn = Arc<dyn Fn(Vec<u8>) -> BoxFuture<'static, anyhow::Result<Vec<u8>>> + Send + Sync>;
+
+/// Routes admission requests to the registered webhooks by path, independent
+/// of any HTTP server. Use it to serve the webhooks from an existing server
+/// with an [AdmissionServer].
+#[derive(Clone, Default)]
+pub struct AdmissionHandler {
+    routes: std::collections::BTreeMap<String, ReviewFn>,
+    /// Reviews requests for every other path.
+    fallback: Option<ReviewFn>,
+}
+
+impl AdmissionHandler {
+    /// Paths webhooks are registered at.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.routes.keys().map(String::as_str)
+    }
+
+    /// Whether requests for `path` are reviewed by a webhook. Others should
+    /// be answered with `404 Not Found`.
+    pub fn handles(&self, path: &str) -> bool {
+        self.fallback.is_some() || self.routes.contains_key(path)
+    }
+
+    /// Review the `AdmissionReview` request `body` POSTed to `path`, and
+    /// return the `AdmissionReview` response to send back with content type
+    /// `application/json`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no webhook handles `path` or `body` is not a valid
+    /// `AdmissionReview`, which should be answered with `400 Bad Request`.
+    pub async fn review(&self, path: &str, body: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let review = self
+            .routes
+            .get(path)
+            .or(self.fallback.as_ref())
+            .with_context(|| format!("No admission webhook at {}", path))?;
+        review(body.to_vec()).await
+    }
+
+    /// Add the routes of `other`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error without adding any route if a path of `other` is
+    /// already registered.
+    pub(crate) fn merge(&mut self, other: AdmissionHandler) -> anyhow::Result<()> {
+        if let Some(path) = other.paths().find(|path| self.routes.contains_key(*path)) {
+            bail!("Admission webhook path {} is registered twice", path);
+        }
+        if self.fallback.is_some() && other.fallback.is_some() {
+            bail!("Admission webhooks serving every path are registered twice");
+        }
+        self.routes.extend(other.routes);
+        if self.fallback.is_none() {
+            self.fallback = other.fallback;
+        }
+        Ok(())
+    }
+}
+
+/// Serves admission webhooks over HTTP. By default, they are served by a
+/// built-in server on port 8443 with the TLS of
+/// [admission_hook_tls](Operator::admission_hook_tls). Implement this trait
+/// to use a different HTTP server or TLS stack, or to embed the webhooks into
+/// an existing server, by passing requests to [AdmissionHandler::review].
+#[async_trait::async_trait]
+pub trait AdmissionServer: Send + Sync + 'static {
+    /// Serve `handler` until the server fails.
+    async fn serve(self: Box<Self>, handler: AdmissionHandler) -> anyhow::Result<()>;
+}
 
 /// Result of admission hook.
 #[allow(clippy::large_enum_variant)]
@@ -334,19 +608,361 @@ pub enum AdmissionResult<T> {
     Allow(T),
     /// Deny the request. Pass a Status object to provide information about the error.
     Deny(Status),
+    /// Permit the request, changing exactly the fields edited by the
+    /// [Mutation] rather than diffing a whole object. Fields the webhook
+    /// never looked at are left alone.
+    Mutate(Mutation),
+}
+
+/// What an admission hook returns: the [AdmissionResult], along with
+/// warnings shown to the client whether or not the request is permitted,
+/// for example in the output of `kubectl`. Every [AdmissionResult] converts
+/// into a verdict without warnings.
+///
+/// ```
+/// # use krator::admission::{AdmissionResult, AdmissionVerdict};
+/// let verdict: AdmissionVerdict<()> = AdmissionResult::Allow(()).into();
+/// assert!(verdict.warnings.is_empty());
+/// ```
+pub struct AdmissionVerdict<T> {
+    /// Whether and how the request is permitted.
+    pub result: AdmissionResult<T>,
+    /// Warnings shown to the client.
+    pub warnings: Vec<String>,
+}
+
+impl<T> From<AdmissionResult<T>> for AdmissionVerdict<T> {
+    fn from(result: AdmissionResult<T>) -> Self {
+        AdmissionVerdict {
+            result,
+            warnings: vec![],
+        }
+    }
+}
+
+impl<T> AdmissionVerdict<T> {
+    /// Add a warning which is shown to the client.
+    pub fn with_warning(mut self, warning: impl Into<String>) -> Self {
+        self.warnings.push(warning.into());
+        self
+    }
+
+    /// Add warnings which are shown to the client.
+    pub fn with_warnings<I>(mut self, warnings: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.warnings.extend(warnings.into_iter().map(Into::into));
+        self
+    }
+}
+
+/// Edits to an admitted object, sent to the API server as a JSON Patch
+/// (RFC 6902). Fields are addressed by JSON Pointer (RFC 6901), such as
+/// `/spec/replicas`.
+///
+/// Missing parent objects, such as `metadata.labels` on an object without
+/// labels, are created, and removing a field which does not exist does
+/// nothing, so that the patch applies to any object.
+///
+/// ```
+/// # use krator::admission::{AdmissionResult, Mutation};
+/// let result = AdmissionResult::<()>::Mutate(
+///     Mutation::new()
+///         .add_label("app.kubernetes.io/managed-by", "krator")
+///         .set_field("/spec/antlers", true)
+///         .remove_field("/spec/deprecated"),
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Mutation {
+    edits: Vec<Edit>,
+}
+
+#[derive(Clone, Debug)]
+enum Edit {
+    Set(String, serde_json::Value),
+    Remove(String),
+}
+
+impl Mutation {
+    /// A mutation which changes nothing.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the field at `pointer` to `value`, adding it if it is missing.
+    pub fn set_field(mut self, pointer: &str, value: impl Into<serde_json::Value>) -> Self {
+        self.edits
+            .push(Edit::Set(pointer.to_string(), value.into()));
+        self
+    }
+
+    /// Remove the field at `pointer`, if it exists.
+    pub fn remove_field(mut self, pointer: &str) -> Self {
+        self.edits.push(Edit::Remove(pointer.to_string()));
+        self
+    }
+
+    /// Set the label `key` to `value`.
+    pub fn add_label(self, key: &str, value: &str) -> Self {
+        self.set_field(&format!("/metadata/labels/{}", escape(key)), value)
+    }
+
+    /// Remove the label `key`, if present.
+    pub fn remove_label(self, key: &str) -> Self {
+        self.remove_field(&format!("/metadata/labels/{}", escape(key)))
+    }
+
+    /// Set the annotation `key` to `value`.
+    pub fn add_annotation(self, key: &str, value: &str) -> Self {
+        self.set_field(&format!("/metadata/annotations/{}", escape(key)), value)
+    }
+
+    /// Remove the annotation `key`, if present.
+    pub fn remove_annotation(self, key: &str) -> Self {
+        self.remove_field(&format!("/metadata/annotations/{}", escape(key)))
+    }
+
+    /// Whether the mutation changes nothing.
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// The JSON Patch making these edits to `object`. Each operation is
+    /// applied to a copy of `object` as it is emitted, so that parents which
+    /// are missing, `null`, or removed by an earlier edit are created first.
+    fn to_patch(&self, object: &serde_json::Value) -> anyhow::Result<json_patch::Patch> {
+        let mut document = object.clone();
+        let mut operations = vec![];
+        let mut emit = |document: &mut serde_json::Value, operation: serde_json::Value| {
+            let operation: json_patch::PatchOperation = serde_json::from_value(operation)
+                .context("Failed to build JSON Patch from mutation")?;
+            json_patch::patch(document, &json_patch::Patch(vec![operation.clone()]))
+                .context("Failed to apply mutation")?;
+            operations.push(operation);
+            Ok::<(), anyhow::Error>(())
+        };
+        for edit in &self.edits {
+            match edit {
+                Edit::Set(path, value) => {
+                    for (index, _) in path.match_indices('/').skip(1) {
+                        let parent = &path[..index];
+                        let present = !matches!(
+                            document.pointer(parent),
+                            None | Some(serde_json::Value::Null)
+                        );
+                        if !present {
+                            emit(
+                                &mut document,
+                                serde_json::json!({ "op": "add", "path": parent, "value": {} }),
+                            )?;
+                        }
+                    }
+                    emit(
+                        &mut document,
+                        serde_json::json!({ "op": "add", "path": path, "value": value }),
+                    )?;
+                }
+                Edit::Remove(path) => {
+                    if document.pointer(path).is_some() {
+                        emit(
+                            &mut document,
+                            serde_json::json!({ "op": "remove", "path": path }),
+                        )?;
+                    }
+                }
+            }
+        }
+        Ok(json_patch::Patch(operations))
+    }
+}
+
+/// Escape a key for use as a JSON Pointer segment.
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// What a permitted request changes.
+enum Allowed<T> {
+    /// The object as it should be admitted.
+    Object(T),
+    /// Explicit edits to the object.
+    Mutation(Mutation),
+}
+
+impl<T> AdmissionResult<T> {
+    /// Deny the request with an HTTP status `code`, a machine-readable
+    /// `reason` such as `Forbidden` or `Invalid`, and a human readable
+    /// `message`.
+    ///
+    /// ```
+    /// # use krator::admission::AdmissionResult;
+    /// let verdict = AdmissionResult::<()>::deny(403, "Forbidden", "Moose may not be renamed.")
+    ///     .with_warning("Renaming is planned for v2.");
+    /// assert_eq!(verdict.warnings, ["Renaming is planned for v2."]);
+    /// ```
+    pub fn deny(code: u16, reason: &str, message: &str) -> Self {
+        AdmissionResult::Deny(Status {
+            code: Some(code.into()),
+            reason: Some(reason.to_string()),
+            message: Some(message.to_string()),
+            status: Some("Failure".to_string()),
+            ..Default::default()
+        })
+    }
+
+    /// Add a warning which is shown to the client whether or not the request
+    /// is permitted.
+    pub fn with_warning(self, warning: impl Into<String>) -> AdmissionVerdict<T> {
+        AdmissionVerdict::from(self).with_warning(warning)
+    }
+
+    /// What the permitted request changes, or why it is denied.
+    fn into_allowed(self) -> Result<Allowed<T>, Status> {
+        match self {
+            AdmissionResult::Allow(object) => Ok(Allowed::Object(object)),
+            AdmissionResult::Deny(status) => Err(status),
+            AdmissionResult::Mutate(mutation) => Ok(Allowed::Mutation(mutation)),
+        }
+    }
 }
 
-#[derive(Debug)]
-enum Operation {
+/// Operation of an admission request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// An object is created.
     Create,
+    /// An object is updated.
     Update,
+    /// An object is deleted.
     Delete,
 }
 
-#[derive(Deserialize, Debug)]
-struct UserInfo {
-    username: String,
-    groups: Vec<String>,
+impl Operation {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Operation::Create => "CREATE",
+            Operation::Update => "UPDATE",
+            Operation::Delete => "DELETE",
+        }
+    }
+}
+
+/// The user who made an admission request.
+#[derive(Clone, Deserialize, Debug)]
+pub struct UserInfo {
+    /// Name of the user.
+    pub username: String,
+    /// Groups the user is a member of.
+    #[serde(default)]
+    pub groups: Vec<String>,
+}
+
+/// Resource of the object in an admission request.
+#[derive(Deserialize, Debug, Default)]
+struct RequestResource {
+    group: String,
+    resource: String,
+}
+
+impl Display for RequestResource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if self.group.is_empty() {
+            write!(f, "{}", self.resource)
+        } else {
+            write!(f, "{}.{}", self.resource, self.group)
+        }
+    }
+}
+
+/// An admission decision, recorded by [AdmissionAudit] sinks.
+#[derive(Clone, Debug)]
+pub struct AdmissionEvent {
+    /// Identifier of the request.
+    pub uid: Option<String>,
+    /// Resource of the object, such as `mooses.animals.com`.
+    pub resource: String,
+    /// Name of the object.
+    pub name: String,
+    /// Namespace of the object, if it is namespaced.
+    pub namespace: Option<String>,
+    /// Operation requested.
+    pub operation: Operation,
+    /// The user who made the request.
+    pub user: UserInfo,
+    /// Whether the request was a dry run.
+    pub dry_run: bool,
+    /// Whether the request was permitted.
+    pub allowed: bool,
+    /// Why the request was denied.
+    pub status: Option<Status>,
+    /// Whether the object was changed by a mutating webhook.
+    pub patched: bool,
+    /// Warnings returned to the client.
+    pub warnings: Vec<String>,
+    /// Time taken to review the request.
+    pub duration: Duration,
+}
+
+/// Records every admission decision, for example to an audit log. Closures
+/// taking an [AdmissionEvent] implement this trait. Sinks are called while
+/// the request is reviewed, so slow sinks should hand events off to a
+/// background task.
+pub trait AdmissionAudit: Send + Sync + 'static {
+    /// Record a decision.
+    fn record(&self, event: &AdmissionEvent);
+}
+
+impl<F: Fn(&AdmissionEvent) + Send + Sync + 'static> AdmissionAudit for F {
+    fn record(&self, event: &AdmissionEvent) {
+        self(event)
+    }
+}
+
+/// Metrics and audit sinks notified of admission decisions. Cloning returns
+/// a handle to the same observers, so that they can be configured after
+/// webhooks have been registered.
+#[derive(Clone, Default)]
+pub(crate) struct AdmissionObserver {
+    inner: Arc<std::sync::RwLock<Observers>>,
+}
+
+#[derive(Default)]
+struct Observers {
+    metrics: Option<AdmissionMetrics>,
+    audit: Vec<Arc<dyn AdmissionAudit>>,
+}
+
+impl AdmissionObserver {
+    pub(crate) fn set_metrics(&self, metrics: AdmissionMetrics) {
+        self.inner
+            .write()
+            .expect("Admission observer lock poisoned.")
+            .metrics = Some(metrics);
+    }
+
+    pub(crate) fn add_audit(&self, sink: Arc<dyn AdmissionAudit>) {
+        self.inner
+            .write()
+            .expect("Admission observer lock poisoned.")
+            .audit
+            .push(sink);
+    }
+
+    fn observe(&self, event: &AdmissionEvent) {
+        let observers = self
+            .inner
+            .read()
+            .expect("Admission observer lock poisoned.");
+        if let Some(ref metrics) = observers.metrics {
+            metrics.record(&event.resource, event.duration, event.allowed);
+        }
+        for sink in &observers.audit {
+            sink.record(event);
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -377,6 +993,12 @@ struct AdmissionRequest<T> {
     uid: Option<String>,
     /// Information about the requesting user.
     user_info: UserInfo,
+    /// Resource of the object.
+    #[serde(default)]
+    resource: RequestResource,
+    /// Whether the request must not cause side effects.
+    #[serde(default)]
+    dry_run: bool,
     #[serde(flatten)]
     operation: AdmissionRequestOperation<T>,
 }
@@ -423,6 +1045,10 @@ struct AdmissionResponse {
     patch: Option<json_patch::Patch>,
     /// The type of Patch. Currently we only allow "JSONPatch".
     patch_type: Option<String>,
+    /// Warnings returned to the client, whether or not the request was
+    /// permitted.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
 }
 
 #[derive(Deserialize)]
@@ -441,21 +1067,58 @@ struct AdmissionReviewResponse {
     response: AdmissionResponse,
 }
 
+async fn review<O: Operator>(
+    operator: Arc<O>,
+    request: AdmissionReviewRequest<O::Manifest>,
+    observer: &AdmissionObserver,
+) -> AdmissionReviewResponse {
+    review_with(
+        request,
+        WebhookKind::Mutating,
+        observer,
+        |manifest, _context| async move {
+            let span = tracing::debug_span!("Operator::admission_hook",);
+            operator.admission_hook(manifest).instrument(span).await
+        },
+    )
+    .await
+}
+
 #[tracing::instrument(
     level="debug",
-    skip(operator, request),
+    skip(request, hook, observer),
     fields(
         name=%request.request.name(),
         namespace=?request.request.namespace(),
         api_version=%request.api_version,
         operation=?request.request.operation(),
+        dry_run=request.request.dry_run,
         user_info=?request.request.user_info
     )
 )]
-async fn review<O: Operator>(
-    operator: Arc<O>,
-    request: AdmissionReviewRequest<O::Manifest>,
-) -> warp::reply::Json {
+async fn review_with<T, F, Fut>(
+    request: AdmissionReviewRequest<T>,
+    kind: WebhookKind,
+    observer: &AdmissionObserver,
+    hook: F,
+) -> AdmissionReviewResponse
+where
+    T: Resource + Serialize + Clone,
+    F: FnOnce(T, AdmissionContext) -> Fut,
+    Fut: std::future::Future,
+    Fut::Output: Into<AdmissionVerdict<T>>,
+{
+    let started = tokio::time::Instant::now();
+    let operation = request.request.operation();
+    let user = request.request.user_info.clone();
+    let dry_run = request.request.dry_run;
+    let context = AdmissionContext {
+        dry_run,
+        operation,
+        user: user.clone(),
+        client: None,
+    };
+    let resource = request.request.resource.to_string();
     let manifest = match request.request.operation {
         AdmissionRequestOperation::Create { object, .. } => object,
         AdmissionRequestOperation::Update {
@@ -478,20 +1141,32 @@ async fn review<O: Operator>(
     let name = manifest.name();
     let namespace = manifest.namespace();
 
-    let span = tracing::debug_span!("Operator::admission_hook",);
-
-    let result = operator
-        .admission_hook(manifest.clone())
-        .instrument(span)
-        .await;
-
-    let response = match result {
-        AdmissionResult::Allow(new_manifest) => {
+    let AdmissionVerdict { result, warnings } = hook(manifest.clone(), context).await.into();
+    let result = result.into_allowed();
+    let old_value = serde_json::to_value(&manifest).unwrap();
+    let result = result.and_then(|allowed| match allowed {
+        Allowed::Object(new_manifest) => {
             let new_value = serde_json::to_value(&new_manifest).unwrap();
-            let old_value = serde_json::to_value(&manifest).unwrap();
-
-            let patch = json_patch::diff(&old_value, &new_value);
-            let (patch, patch_type) = if !patch.0.is_empty() {
+            Ok(json_patch::diff(&old_value, &new_value))
+        }
+        Allowed::Mutation(mutation) => mutation.to_patch(&old_value).map_err(|error| Status {
+            code: Some(500),
+            message: Some(format!("{:#}", error)),
+            status: Some("Failure".to_string()),
+            ..Default::default()
+        }),
+    });
+    let response = match result {
+        Ok(patch) => {
+            if kind == WebhookKind::Validating && !patch.0.is_empty() {
+                warn!(
+                    %name,
+                    ?namespace,
+                    ?patch,
+                    "Validating webhook changed the object, ignoring changes."
+                );
+            }
+            let (patch, patch_type) = if kind == WebhookKind::Mutating && !patch.0.is_empty() {
                 (Some(patch), Some("JSONPatch".to_string()))
             } else {
                 (None, None)
@@ -501,6 +1176,7 @@ async fn review<O: Operator>(
                 ?namespace,
                 allowed=true,
                 ?patch,
+                ?warnings,
                 "Admission request allowed."
             );
             AdmissionResponse {
@@ -509,9 +1185,10 @@ async fn review<O: Operator>(
                 status: None,
                 patch,
                 patch_type,
+                warnings,
             }
         }
-        AdmissionResult::Deny(status) => {
+        Err(status) => {
             warn!(
                 code=?status.code,
                 reason=?status.reason,
@@ -519,6 +1196,7 @@ async fn review<O: Operator>(
                 %name,
                 ?namespace,
                 allowed=false,
+                ?warnings,
                 "Admission request denied."
             );
             AdmissionResponse {
@@ -527,38 +1205,352 @@ async fn review<O: Operator>(
                 status: Some(status),
                 patch: None,
                 patch_type: None,
+                warnings,
             }
         }
     };
-    warp::reply::json(&AdmissionReviewResponse {
+    observer.observe(&AdmissionEvent {
+        uid: response.uid.clone(),
+        resource,
+        name: name.clone(),
+        namespace: namespace.clone(),
+        operation,
+        user,
+        dry_run,
+        allowed: response.allowed,
+        status: response.status.clone(),
+        patched: response.patch.is_some(),
+        warnings: response.warnings.clone(),
+        duration: started.elapsed(),
+    });
+    AdmissionReviewResponse {
         api_version: request.api_version,
         kind: request.kind,
         response,
-    })
+    }
 }
 
-pub(crate) async fn endpoint<O: Operator>(operator: Arc<O>) {
-    let tls = operator
-        .admission_hook_tls()
-        .await
-        .expect("getting webhook tls AdmissionTls failed");
+/// Parse an `AdmissionReview` request, review it and encode the response.
+async fn review_json<T, F, Fut>(body: Vec<u8>, review: F) -> anyhow::Result<Vec<u8>>
+where
+    T: serde::de::DeserializeOwned,
+    F: FnOnce(AdmissionReviewRequest<T>) -> Fut,
+    Fut: Future<Output = AdmissionReviewResponse>,
+{
+    let request = serde_json::from_slice(&body).context("Failed to parse admission review")?;
+    let response = review(request).await;
+    serde_json::to_vec(&response).context("Failed to encode admission review")
+}
 
+/// Handler serving `webhook` for the objects of operator `O`.
+///
+/// Only webhooks declaring [SideEffects::NoneOnDryRun] receive `client`, and
+/// only for requests which are not a dry run.
+pub(crate) fn webhook_handler<O: Operator>(
+    webhook: Webhook<O>,
+    observer: AdmissionObserver,
+    client: Client,
+) -> AdmissionHandler {
+    let Webhook {
+        path,
+        kind,
+        side_effects,
+        f,
+    } = webhook;
+    let review: ReviewFn = Arc::new(move |body: Vec<u8>| {
+        let f = Arc::clone(&f);
+        let observer = observer.clone();
+        let client = client.clone();
+        async move {
+            review_json(
+                body,
+                |request: AdmissionReviewRequest<O::Manifest>| async move {
+                    review_with(request, kind, &observer, |manifest, mut context| {
+                        if side_effects == SideEffects::NoneOnDryRun && !context.dry_run {
+                            context.client = Some(client);
+                        }
+                        f(manifest, context)
+                    })
+                    .await
+                },
+            )
+            .await
+        }
+        .boxed()
+    });
+    let mut handler = AdmissionHandler::default();
+    handler.routes.insert(path, review);
+    handler
+}
+
+/// Serves admission webhooks with warp, on port 8443.
+struct WarpServer {
+    tls: Vec<ServedTls>,
+}
+
+#[async_trait::async_trait]
+impl AdmissionServer for WarpServer {
+    async fn serve(self: Box<Self>, handler: AdmissionHandler) -> anyhow::Result<()> {
+        serve(handler, self.tls)?.await
+    }
+}
+
+/// The built-in server, serving with each TLS for the names its certificate
+/// is valid for, and with the first one otherwise. TLS with a source is
+/// loaded again from it at the interval.
+///
+/// # Panics
+///
+/// Panics if `tls` is empty.
+pub(crate) fn default_server(tls: Vec<ServedTls>) -> Box<dyn AdmissionServer> {
+    assert!(!tls.is_empty(), "Admission webhook server needs TLS.");
+    Box::new(WarpServer { tls })
+}
+
+/// Route every POST request to `handler`.
+fn warp_routes(
+    handler: AdmissionHandler,
+) -> warp::filters::BoxedFilter<(warp::http::Response<Vec<u8>>,)> {
+    use warp::http::{Response, StatusCode};
     use warp::Filter;
-    let routes = warp::any()
-        .and(warp::post())
-        .and(warp::body::json())
-        .and_then(move |request: AdmissionReviewRequest<O::Manifest>| {
+    warp::post()
+        .and(warp::path::full())
+        .and(warp::body::bytes())
+        .and_then(
+            move |path: warp::path::FullPath, body: warp::hyper::body::Bytes| {
+                let handler = handler.clone();
+                async move {
+                    if !handler.handles(path.as_str()) {
+                        return Err(warp::reject::not_found());
+                    }
+                    let response = match handler.review(path.as_str(), &body).await {
+                        Ok(body) => Response::builder()
+                            .header("content-type", "application/json")
+                            .body(body),
+                        Err(error) => {
+                            warn!(path = path.as_str(), ?error, "Invalid admission request.");
+                            Response::builder()
+                                .status(StatusCode::BAD_REQUEST)
+                                .body(format!("{:#}", error).into_bytes())
+                        }
+                    };
+                    Ok(response.expect("Admission response is valid."))
+                }
+            },
+        )
+        .boxed()
+}
+
+/// Serve `handler` with `tls` until the server fails. TLS with a source is
+/// loaded again from it at the interval, and new connections use the new
+/// certificate once it changes. The listener and open connections are kept.
+///
+/// # Errors
+///
+/// Returns an error if any `tls` cannot be parsed, and the returned future
+/// fails if the listener cannot be bound.
+fn serve(
+    handler: AdmissionHandler,
+    tls: Vec<ServedTls>,
+) -> anyhow::Result<impl Future<Output = anyhow::Result<()>>> {
+    let keys = tls
+        .iter()
+        .map(|(tls, _)| Ok(std::sync::RwLock::new(tls.certified_key()?)))
+        .collect::<anyhow::Result<_>>()?;
+    let resolver = Arc::new(ReloadingCert(keys));
+    let mut config = ServerConfig::new(NoClientAuth::new());
+    config.cert_resolver = Arc::clone(&resolver) as Arc<dyn ResolvesServerCert>;
+    config.set_protocols(&[b"h2".to_vec(), b"http/1.1".to_vec()]);
+    let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+
+    Ok(async move {
+        let addr = std::net::SocketAddr::from(([0, 0, 0, 0], 8443));
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed to bind admission webhook server to {}", addr))?;
+        let (tx, rx) = tokio::sync::mpsc::channel::<std::io::Result<_>>(32);
+        let accept = async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, peer)) => {
+                        let acceptor = acceptor.clone();
+                        let tx = tx.clone();
+                        // Handshake separately so that a slow client does
+                        // not hold up others.
+                        tokio::spawn(async move {
+                            match acceptor.accept(stream).await {
+                                Ok(stream) => {
+                                    tx.send(Ok(stream)).await.ok();
+                                }
+                                Err(error) => debug!(%peer, ?error, "TLS handshake failed."),
+                            }
+                        });
+                    }
+                    Err(error) => {
+                        warn!(?error, "Failed to accept admission webhook connection.");
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    }
+                }
+            }
+        };
+        let server = warp::serve(warp_routes(handler))
+            .run_incoming(tokio_stream::wrappers::ReceiverStream::new(rx));
+        let reload = futures::future::join_all(tls.into_iter().enumerate().filter_map(
+            |(index, (tls, reload))| {
+                let (source, interval) = reload?;
+                Some(reload_tls(
+                    Arc::clone(&resolver),
+                    index,
+                    tls,
+                    source,
+                    interval,
+                ))
+            },
+        ))
+        .then(|_| futures::future::pending::<()>());
+        tokio::select! {
+            _ = server => (),
+            _ = accept => (),
+            _ = reload => (),
+        }
+        Ok(())
+    })
+}
+
+/// Load TLS from `source` every `interval`, swapping certificate `index` of
+/// `resolver` when it changes. Failures keep the current certificate.
+async fn reload_tls(
+    resolver: Arc<ReloadingCert>,
+    index: usize,
+    mut current: AdmissionTls,
+    source: TlsSource,
+    interval: Duration,
+) {
+    let mut interval = tokio::time::interval(interval);
+    // The first tick completes immediately.
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        match source().await {
+            Ok(tls) if tls == current => trace!("Admission webhook TLS unchanged."),
+            Ok(tls) => match tls.certified_key() {
+                Ok(key) => {
+                    *resolver.0[index]
+                        .write()
+                        .expect("Certificate lock poisoned.") = key;
+                    current = tls;
+                    info!("Reloaded admission webhook TLS.");
+                }
+                Err(error) => warn!(
+                    ?error,
+                    "Invalid admission webhook TLS, keeping current certificate."
+                ),
+            },
+            Err(error) => warn!(
+                ?error,
+                "Failed to reload admission webhook TLS, keeping current certificate."
+            ),
+        }
+    }
+}
+
+/// Serve the operator's admission hook until the server fails.
+///
+/// # Errors
+///
+/// Returns an error if the TLS cannot be loaded or the server fails, for
+/// example because it cannot bind its port.
+pub(crate) async fn endpoint<O: Operator>(
+    operator: Arc<O>,
+    observer: AdmissionObserver,
+    server: Option<Box<dyn AdmissionServer>>,
+) -> anyhow::Result<()> {
+    let server = match server {
+        Some(server) => server,
+        None => {
+            let source = tls_source(Arc::clone(&operator));
+            let reload = operator
+                .admission_hook_tls_refresh()
+                .map(|interval| (Arc::clone(&source), interval));
+            let tls = source()
+                .await
+                .context("Failed to get admission webhook TLS")?;
+            default_server(vec![(tls, reload)])
+        }
+    };
+
+    let handler = AdmissionHandler {
+        routes: Default::default(),
+        fallback: Some(Arc::new(move |body: Vec<u8>| {
             let operator = Arc::clone(&operator);
+            let observer = observer.clone();
             async move {
-                let response = review(operator, request).await;
-                Ok::<_, std::convert::Infallible>(response)
+                review_json(
+                    body,
+                    |request: AdmissionReviewRequest<O::Manifest>| async move {
+                        review(operator, request, &observer).await
+                    },
+                )
+                .await
             }
-        });
+            .boxed()
+        })),
+    };
+
+    server.serve(handler).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
 
-    warp::serve(routes)
-        .tls()
-        .cert(tls.cert)
-        .key(tls.private_key)
-        .run(([0, 0, 0, 0], 8443))
-        .await;
+    /// Apply the patch of `mutation` to `object`.
+    fn mutate(mutation: Mutation, mut object: serde_json::Value) -> serde_json::Value {
+        let patch = mutation.to_patch(&object).unwrap();
+        json_patch::patch(&mut object, &patch).unwrap();
+        object
+    }
+
+    #[test]
+    fn set_creates_missing_parents() {
+        let object = mutate(
+            Mutation::new().set_field("/spec/antlers/count", 2),
+            json!({ "metadata": { "name": "moose" } }),
+        );
+        assert_eq!(
+            object,
+            json!({ "metadata": { "name": "moose" }, "spec": { "antlers": { "count": 2 } } })
+        );
+    }
+
+    #[test]
+    fn set_replaces_null_parent() {
+        let object = mutate(
+            Mutation::new().add_label("app", "moose"),
+            json!({ "metadata": { "name": "moose", "labels": null } }),
+        );
+        assert_eq!(
+            object,
+            json!({ "metadata": { "name": "moose", "labels": { "app": "moose" } } })
+        );
+    }
+
+    #[test]
+    fn set_below_removed_field_recreates_it() {
+        let object = mutate(
+            Mutation::new()
+                .remove_field("/spec/antlers")
+                .set_field("/spec/antlers/count", 1),
+            json!({ "spec": { "antlers": { "count": 2, "color": "brown" } } }),
+        );
+        assert_eq!(object, json!({ "spec": { "antlers": { "count": 1 } } }));
+    }
+
+    #[test]
+    fn remove_missing_field_does_nothing() {
+        let mutation = Mutation::new().remove_label("app");
+        let patch = mutation.to_patch(&json!({ "metadata": {} })).unwrap();
+        assert!(patch.0.is_empty());
+    }
 }
@@ -0,0 +1,284 @@
+//! Self-signed certificates for the admission webhook server.
+
+use super::AdmissionTls;
+use anyhow::Context;
+use k8s_openapi::api::admissionregistration::v1::{
+    MutatingWebhookConfiguration, ValidatingWebhookConfiguration, WebhookClientConfig,
+};
+use k8s_openapi::api::core::v1::Secret;
+use k8s_openapi::ByteString;
+use kube::api::{Api, ObjectMeta, PostParams};
+use kube::{Client, Resource};
+use rcgen::{BasicConstraints, Certificate, CertificateParams, DnType, IsCa};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tracing::{debug, info, warn};
+
+/// Number of times a webhook configuration update is retried after a
+/// conflict.
+const CONFLICT_RETRIES: u32 = 5;
+
+const CA_CRT: &str = "ca.crt";
+const TLS_CRT: &str = "tls.crt";
+const TLS_KEY: &str = "tls.key";
+
+/// Bootstraps TLS for the admission webhook server without external tooling,
+/// which is convenient for development clusters.
+///
+/// On first use, a self-signed CA and a serving certificate for the webhook
+/// Service are generated and stored in a Secret. Later calls, including those
+/// from other replicas, reuse the Secret. The CA is then injected as
+/// `caBundle` into every webhook of the given webhook configurations, unless
+/// it is already there, so the API server trusts the serving certificate.
+///
+/// Call it from [admission_hook_tls](crate::Operator::admission_hook_tls).
+/// The operator needs permission to get and create Secrets in `namespace`,
+/// and to get and update the webhook configurations.
+///
+/// ```no_run
+/// # use krator::admission::{AdmissionTls, CertificateBootstrap};
+/// # async fn example(client: kube::Client) -> anyhow::Result<AdmissionTls> {
+/// CertificateBootstrap::new("moose-system", "moose-webhook")
+///     .with_mutating_webhook_configuration("mooses.animals.com")
+///     .run(&client)
+///     .await
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CertificateBootstrap {
+    namespace: String,
+    service: String,
+    secret: String,
+    mutating: Vec<String>,
+    validating: Vec<String>,
+}
+
+impl CertificateBootstrap {
+    /// Bootstrap a certificate for the webhook Service `service` in
+    /// `namespace`, stored in the Secret `{service}-tls` of the same
+    /// namespace.
+    pub fn new(namespace: &str, service: &str) -> Self {
+        CertificateBootstrap {
+            namespace: namespace.to_string(),
+            service: service.to_string(),
+            secret: format!("{}-tls", service),
+            mutating: vec![],
+            validating: vec![],
+        }
+    }
+
+    /// Store the certificate in the Secret `name` instead.
+    pub fn with_secret_name(mut self, name: &str) -> Self {
+        self.secret = name.to_string();
+        self
+    }
+
+    /// Inject the CA into the MutatingWebhookConfiguration `name`.
+    pub fn with_mutating_webhook_configuration(mut self, name: &str) -> Self {
+        self.mutating.push(name.to_string());
+        self
+    }
+
+    /// Inject the CA into the ValidatingWebhookConfiguration `name`.
+    pub fn with_validating_webhook_configuration(mut self, name: &str) -> Self {
+        self.validating.push(name.to_string());
+        self
+    }
+
+    /// Load or generate the certificate, inject the CA into the webhook
+    /// configurations and return the TLS to serve with.
+    pub async fn run(&self, client: &Client) -> anyhow::Result<AdmissionTls> {
+        let (ca_bundle, tls) = self.load_or_create_secret(client).await?;
+        for name in &self.mutating {
+            let api = Api::<MutatingWebhookConfiguration>::all(client.clone());
+            inject_ca_bundle(&api, name, &ca_bundle, mutating_client_configs).await?;
+        }
+        for name in &self.validating {
+            let api = Api::<ValidatingWebhookConfiguration>::all(client.clone());
+            inject_ca_bundle(&api, name, &ca_bundle, validating_client_configs).await?;
+        }
+        Ok(tls)
+    }
+
+    /// The CA bundle and serving TLS from the Secret, which is created if it
+    /// does not exist.
+    async fn load_or_create_secret(
+        &self,
+        client: &Client,
+    ) -> anyhow::Result<(ByteString, AdmissionTls)> {
+        let api = Api::<Secret>::namespaced(client.clone(), &self.namespace);
+        match api.get(&self.secret).await {
+            Ok(secret) => return read_secret(&secret),
+            Err(kube::Error::Api(ref response)) if response.code == 404 => (),
+            Err(error) => {
+                return Err(error).with_context(|| {
+                    format!("Failed to get secret {}/{}", self.namespace, self.secret)
+                })
+            }
+        }
+
+        let (ca, tls) = self.generate().context("Failed to generate certificate")?;
+        let mut data = BTreeMap::new();
+        data.insert(CA_CRT.to_string(), ca.clone());
+        data.insert(TLS_CRT.to_string(), tls.cert.clone());
+        data.insert(TLS_KEY.to_string(), tls.private_key.clone());
+        let secret = Secret {
+            metadata: ObjectMeta {
+                name: Some(self.secret.clone()),
+                namespace: Some(self.namespace.clone()),
+                ..Default::default()
+            },
+            string_data: Some(data),
+            type_: Some("tls".to_string()),
+            ..Default::default()
+        };
+        match api.create(&PostParams::default(), &secret).await {
+            Ok(_) => {
+                info!(
+                    namespace = %self.namespace,
+                    secret = %self.secret,
+                    "Created self-signed admission webhook certificate."
+                );
+                Ok((ByteString(ca.into_bytes()), tls))
+            }
+            Err(kube::Error::Api(ref response)) if response.code == 409 => {
+                // Another replica created the Secret first.
+                debug!(
+                    namespace = %self.namespace,
+                    secret = %self.secret,
+                    "Admission webhook certificate already exists, using it."
+                );
+                let secret = api.get(&self.secret).await.with_context(|| {
+                    format!("Failed to get secret {}/{}", self.namespace, self.secret)
+                })?;
+                read_secret(&secret)
+            }
+            Err(error) => Err(error).with_context(|| {
+                format!("Failed to create secret {}/{}", self.namespace, self.secret)
+            }),
+        }
+    }
+
+    /// Generate a CA and a serving certificate for the Service signed by it.
+    fn generate(&self) -> anyhow::Result<(String, AdmissionTls)> {
+        let mut ca_params = CertificateParams::default();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        ca_params
+            .distinguished_name
+            .push(DnType::CommonName, format!("{} CA", self.service));
+        let ca = Certificate::from_params(ca_params)?;
+
+        let host = format!("{}.{}.svc", self.service, self.namespace);
+        let mut params = CertificateParams::new(vec![
+            self.service.clone(),
+            format!("{}.{}", self.service, self.namespace),
+            host.clone(),
+            format!("{}.cluster.local", host),
+        ]);
+        params.distinguished_name.push(DnType::CommonName, host);
+        let cert = Certificate::from_params(params)?;
+
+        Ok((
+            ca.serialize_pem()?,
+            AdmissionTls {
+                cert: cert.serialize_pem_with_signer(&ca)?,
+                private_key: cert.serialize_private_key_pem(),
+            },
+        ))
+    }
+}
+
+/// The CA bundle and serving TLS stored in a Secret. Secrets without a CA
+/// are trusted by their own certificate.
+fn read_secret(secret: &Secret) -> anyhow::Result<(ByteString, AdmissionTls)> {
+    let tls = AdmissionTls::from(secret)?;
+    let ca_bundle = secret
+        .data
+        .as_ref()
+        .and_then(|data| data.get(CA_CRT).cloned())
+        .or_else(|| {
+            secret
+                .string_data
+                .as_ref()
+                .and_then(|data| data.get(CA_CRT))
+                .map(|ca| ByteString(ca.clone().into_bytes()))
+        })
+        .unwrap_or_else(|| ByteString(tls.cert.clone().into_bytes()));
+    Ok((ca_bundle, tls))
+}
+
+fn mutating_client_configs(
+    configuration: &mut MutatingWebhookConfiguration,
+) -> Vec<&mut WebhookClientConfig> {
+    configuration
+        .webhooks
+        .iter_mut()
+        .flatten()
+        .map(|webhook| &mut webhook.client_config)
+        .collect()
+}
+
+fn validating_client_configs(
+    configuration: &mut ValidatingWebhookConfiguration,
+) -> Vec<&mut WebhookClientConfig> {
+    configuration
+        .webhooks
+        .iter_mut()
+        .flatten()
+        .map(|webhook| &mut webhook.client_config)
+        .collect()
+}
+
+/// Set `caBundle` of every webhook in the configuration `name`, unless all of
+/// them already have it.
+async fn inject_ca_bundle<K>(
+    api: &Api<K>,
+    name: &str,
+    ca_bundle: &ByteString,
+    client_configs: fn(&mut K) -> Vec<&mut WebhookClientConfig>,
+) -> anyhow::Result<()>
+where
+    K: Resource + Clone + DeserializeOwned + Serialize + std::fmt::Debug,
+{
+    let mut attempt = 0;
+    loop {
+        let mut configuration = api
+            .get(name)
+            .await
+            .with_context(|| format!("Failed to get webhook configuration {}", name))?;
+        let mut changed = false;
+        for client_config in client_configs(&mut configuration) {
+            if client_config.ca_bundle.as_ref() != Some(ca_bundle) {
+                client_config.ca_bundle = Some(ca_bundle.clone());
+                changed = true;
+            }
+        }
+        if !changed {
+            return Ok(());
+        }
+        match api
+            .replace(name, &PostParams::default(), &configuration)
+            .await
+        {
+            Ok(_) => {
+                info!(%name, "Injected caBundle into webhook configuration.");
+                return Ok(());
+            }
+            Err(kube::Error::Api(ref response))
+                if response.code == 409 && attempt < CONFLICT_RETRIES =>
+            {
+                attempt += 1;
+                warn!(
+                    %name,
+                    attempt,
+                    "Conflict injecting caBundle, retrying with latest webhook configuration."
+                );
+            }
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("Failed to update webhook configuration {}", name))
+            }
+        }
+    }
+}
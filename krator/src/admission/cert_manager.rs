@@ -0,0 +1,193 @@
+//! Webhook certificates issued by cert-manager.
+
+use super::AdmissionTls;
+use anyhow::Context;
+use k8s_openapi::api::core::v1::Secret;
+use kube::api::{Api, ApiResource, DynamicObject, GroupVersionKind, Patch, PatchParams};
+use kube::Client;
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// Interval at which the Secret is polled while waiting for cert-manager.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Obtains TLS for the admission webhook server from a cert-manager
+/// `Certificate`.
+///
+/// A `Certificate` for the webhook Service is applied, issued by the given
+/// Issuer, and the Secret cert-manager writes is read once it exists. Call it
+/// from [admission_hook_tls](crate::Operator::admission_hook_tls): the
+/// `Certificate` is applied idempotently, so renewals are picked up when the
+/// TLS is reloaded as configured by
+/// [admission_hook_tls_refresh](crate::Operator::admission_hook_tls_refresh).
+///
+/// To have the API server trust the certificate, annotate the webhook
+/// configurations with `cert-manager.io/inject-ca-from: {namespace}/{name}`
+/// of the `Certificate`, and let cert-manager's CA injector set `caBundle`.
+/// The operator needs permission to patch `certificates.cert-manager.io` and
+/// to get Secrets in `namespace`.
+///
+/// ```no_run
+/// # use krator::admission::{AdmissionTls, CertManagerTls};
+/// # async fn example(client: kube::Client) -> anyhow::Result<AdmissionTls> {
+/// CertManagerTls::new("moose-system", "moose-webhook", "selfsigned")
+///     .with_cluster_issuer()
+///     .run(&client)
+///     .await
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CertManagerTls {
+    namespace: String,
+    service: String,
+    name: String,
+    secret: String,
+    issuer_name: String,
+    issuer_kind: String,
+    issuer_group: String,
+    timeout: Duration,
+}
+
+impl CertManagerTls {
+    /// Request a certificate for the webhook Service `service` in `namespace`
+    /// from the namespaced Issuer `issuer`. The `Certificate` is named
+    /// `{service}` and its Secret `{service}-tls`.
+    pub fn new(namespace: &str, service: &str, issuer: &str) -> Self {
+        CertManagerTls {
+            namespace: namespace.to_string(),
+            service: service.to_string(),
+            name: service.to_string(),
+            secret: format!("{}-tls", service),
+            issuer_name: issuer.to_string(),
+            issuer_kind: "Issuer".to_string(),
+            issuer_group: "cert-manager.io".to_string(),
+            timeout: Duration::from_secs(120),
+        }
+    }
+
+    /// Reference a ClusterIssuer instead of a namespaced Issuer.
+    pub fn with_cluster_issuer(self) -> Self {
+        self.with_issuer_kind("ClusterIssuer", "cert-manager.io")
+    }
+
+    /// Reference an issuer of another kind, such as an external issuer.
+    pub fn with_issuer_kind(mut self, kind: &str, group: &str) -> Self {
+        self.issuer_kind = kind.to_string();
+        self.issuer_group = group.to_string();
+        self
+    }
+
+    /// Name the `Certificate` `name` instead.
+    pub fn with_certificate_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Have cert-manager store the certificate in the Secret `name` instead.
+    pub fn with_secret_name(mut self, name: &str) -> Self {
+        self.secret = name.to_string();
+        self
+    }
+
+    /// How long to wait for cert-manager to issue the certificate. Defaults
+    /// to two minutes.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Apply the `Certificate`, wait for its Secret and return the TLS it
+    /// contains.
+    pub async fn run(&self, client: &Client) -> anyhow::Result<AdmissionTls> {
+        self.apply_certificate(client).await?;
+        tokio::time::timeout(self.timeout, self.wait_for_secret(client))
+            .await
+            .with_context(|| {
+                format!(
+                    "Timed out waiting for cert-manager to issue secret {}/{}",
+                    self.namespace, self.secret
+                )
+            })?
+    }
+
+    async fn apply_certificate(&self, client: &Client) -> anyhow::Result<()> {
+        let resource = ApiResource::from_gvk(&GroupVersionKind::gvk(
+            "cert-manager.io",
+            "v1",
+            "Certificate",
+        ));
+        let api: Api<DynamicObject> =
+            Api::namespaced_with(client.clone(), &self.namespace, &resource);
+        let host = format!("{}.{}.svc", self.service, self.namespace);
+        let certificate = serde_json::json!({
+            "apiVersion": "cert-manager.io/v1",
+            "kind": "Certificate",
+            "metadata": {
+                "name": self.name,
+                "namespace": self.namespace,
+            },
+            "spec": {
+                "secretName": self.secret,
+                "dnsNames": [
+                    self.service,
+                    format!("{}.{}", self.service, self.namespace),
+                    host,
+                    format!("{}.cluster.local", host),
+                ],
+                "issuerRef": {
+                    "name": self.issuer_name,
+                    "kind": self.issuer_kind,
+                    "group": self.issuer_group,
+                },
+            },
+        });
+        api.patch(
+            &self.name,
+            &PatchParams::apply("krator").force(),
+            &Patch::Apply(&certificate),
+        )
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to apply certificate {}/{}",
+                self.namespace, self.name
+            )
+        })?;
+        debug!(
+            namespace = %self.namespace,
+            certificate = %self.name,
+            "Applied cert-manager certificate."
+        );
+        Ok(())
+    }
+
+    async fn wait_for_secret(&self, client: &Client) -> anyhow::Result<AdmissionTls> {
+        let api = Api::<Secret>::namespaced(client.clone(), &self.namespace);
+        loop {
+            match api.get(&self.secret).await {
+                Ok(secret) => {
+                    let tls = AdmissionTls::from(&secret)?;
+                    info!(
+                        namespace = %self.namespace,
+                        secret = %self.secret,
+                        "Loaded admission webhook certificate issued by cert-manager."
+                    );
+                    return Ok(tls);
+                }
+                Err(kube::Error::Api(ref response)) if response.code == 404 => {
+                    debug!(
+                        namespace = %self.namespace,
+                        secret = %self.secret,
+                        "Waiting for cert-manager to issue certificate."
+                    );
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+                Err(error) => {
+                    return Err(error).with_context(|| {
+                        format!("Failed to get secret {}/{}", self.namespace, self.secret)
+                    })
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! Registration of webhooks with the API server.
+
+use super::{Operation, WebhookKind};
+use anyhow::Context;
+use k8s_openapi::api::admissionregistration::v1::{
+    MutatingWebhook, MutatingWebhookConfiguration, RuleWithOperations, ServiceReference,
+    ValidatingWebhook, ValidatingWebhookConfiguration, WebhookClientConfig,
+};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::LabelSelector;
+use kube::api::{Api, ObjectMeta, Patch, PatchParams};
+use kube::{Client, Resource};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Interval at which webhook configurations are reconciled after startup.
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Field manager of webhook configurations.
+const FIELD_MANAGER: &str = "krator";
+
+/// What the API server does when a webhook cannot be called.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// Reject the request.
+    #[default]
+    Fail,
+    /// Admit the request as if the webhook did not exist.
+    Ignore,
+}
+
+impl FailurePolicy {
+    fn as_str(self) -> &'static str {
+        match self {
+            FailurePolicy::Fail => "Fail",
+            FailurePolicy::Ignore => "Ignore",
+        }
+    }
+}
+
+/// Whether a webhook has side effects besides admitting the request, such as
+/// changing other objects.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SideEffects {
+    /// The webhook has no side effects. It is not given a client.
+    #[default]
+    None,
+    /// The webhook has side effects, which it skips for dry-run requests. It
+    /// is only given a client for requests which are not a dry run.
+    NoneOnDryRun,
+}
+
+impl SideEffects {
+    fn as_str(self) -> &'static str {
+        match self {
+            SideEffects::None => "None",
+            SideEffects::NoneOnDryRun => "NoneOnDryRun",
+        }
+    }
+}
+
+/// A webhook as registered in a webhook configuration.
+pub(crate) struct WebhookRegistration {
+    path: String,
+    kind: WebhookKind,
+    rule: RuleWithOperations,
+    failure_policy: FailurePolicy,
+    namespace_selector: Option<LabelSelector>,
+    side_effects: SideEffects,
+}
+
+impl WebhookRegistration {
+    /// Register the webhook at `path` for `operations` on objects of type
+    /// `R`.
+    pub(crate) fn new<R: Resource<DynamicType = ()>>(
+        path: &str,
+        kind: WebhookKind,
+        operations: &[Operation],
+        failure_policy: FailurePolicy,
+        namespace_selector: Option<LabelSelector>,
+        side_effects: SideEffects,
+    ) -> Self {
+        WebhookRegistration {
+            path: path.to_string(),
+            kind,
+            rule: RuleWithOperations {
+                api_groups: Some(vec![R::group(&()).to_string()]),
+                api_versions: Some(vec![R::version(&()).to_string()]),
+                resources: Some(vec![R::plural(&()).to_string()]),
+                operations: Some(
+                    operations
+                        .iter()
+                        .map(|operation| operation.as_str().to_string())
+                        .collect(),
+                ),
+                scope: None,
+            },
+            failure_policy,
+            namespace_selector,
+            side_effects,
+        }
+    }
+
+    /// Name of the webhook within the configuration `configuration`, derived
+    /// from its path. Paths with the same [name_stem] are rejected when
+    /// controllers are registered, so the name is unique.
+    fn name(&self, configuration: &str) -> String {
+        let stem = name_stem(&self.path);
+        if stem.is_empty() {
+            format!("webhook.{}", configuration)
+        } else {
+            format!("{}.{}", stem, configuration)
+        }
+    }
+
+    fn client_config(&self, service: &ServiceReference) -> WebhookClientConfig {
+        WebhookClientConfig {
+            service: Some(ServiceReference {
+                path: Some(self.path.clone()),
+                ..service.clone()
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// The part of the name of the webhook served at `path` derived from the
+/// path, which is the same for paths differing only in case or in the
+/// characters other than letters and digits, such as `/a-b` and `/a/b`.
+pub(crate) fn name_stem(path: &str) -> String {
+    let stem: String = path
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    stem.trim_matches('-').to_string()
+}
+
+/// The `ValidatingWebhookConfiguration` and `MutatingWebhookConfiguration`
+/// registering the webhooks served by a Manager.
+pub(crate) struct WebhookConfigurations {
+    /// Name of both configurations.
+    pub(crate) name: String,
+    /// Service in front of the webhook server.
+    pub(crate) service: ServiceReference,
+    pub(crate) registrations: Vec<WebhookRegistration>,
+}
+
+impl WebhookConfigurations {
+    /// Apply both configurations, leaving out those without webhooks.
+    /// Webhooks which are no longer registered are removed, while fields
+    /// set by others, such as an injected `caBundle`, are kept.
+    pub(crate) async fn reconcile(&self, client: &Client) -> anyhow::Result<()> {
+        let params = PatchParams::apply(FIELD_MANAGER).force();
+        let metadata = ObjectMeta {
+            name: Some(self.name.clone()),
+            ..Default::default()
+        };
+
+        let validating: Vec<ValidatingWebhook> = self
+            .registrations
+            .iter()
+            .filter(|registration| registration.kind == WebhookKind::Validating)
+            .map(|registration| ValidatingWebhook {
+                name: registration.name(&self.name),
+                admission_review_versions: vec!["v1".to_string()],
+                client_config: registration.client_config(&self.service),
+                failure_policy: Some(registration.failure_policy.as_str().to_string()),
+                namespace_selector: registration.namespace_selector.clone(),
+                rules: Some(vec![registration.rule.clone()]),
+                side_effects: registration.side_effects.as_str().to_string(),
+                ..Default::default()
+            })
+            .collect();
+        if !validating.is_empty() {
+            let configuration = ValidatingWebhookConfiguration {
+                metadata: metadata.clone(),
+                webhooks: Some(validating),
+            };
+            Api::<ValidatingWebhookConfiguration>::all(client.clone())
+                .patch(&self.name, &params, &Patch::Apply(&configuration))
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to apply validating webhook configuration {}",
+                        self.name
+                    )
+                })?;
+        }
+
+        let mutating: Vec<MutatingWebhook> = self
+            .registrations
+            .iter()
+            .filter(|registration| registration.kind == WebhookKind::Mutating)
+            .map(|registration| MutatingWebhook {
+                name: registration.name(&self.name),
+                admission_review_versions: vec!["v1".to_string()],
+                client_config: registration.client_config(&self.service),
+                failure_policy: Some(registration.failure_policy.as_str().to_string()),
+                namespace_selector: registration.namespace_selector.clone(),
+                rules: Some(vec![registration.rule.clone()]),
+                side_effects: registration.side_effects.as_str().to_string(),
+                ..Default::default()
+            })
+            .collect();
+        if !mutating.is_empty() {
+            let configuration = MutatingWebhookConfiguration {
+                metadata,
+                webhooks: Some(mutating),
+            };
+            Api::<MutatingWebhookConfiguration>::all(client.clone())
+                .patch(&self.name, &params, &Patch::Apply(&configuration))
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to apply mutating webhook configuration {}",
+                        self.name
+                    )
+                })?;
+        }
+
+        debug!(name = %self.name, "Reconciled webhook configurations.");
+        Ok(())
+    }
+
+    /// Reconcile the configurations periodically, correcting changes made by
+    /// others.
+    pub(crate) async fn run(self, client: Client) {
+        let mut interval = tokio::time::interval(RECONCILE_INTERVAL);
+        // The first tick completes immediately, after the configurations
+        // were reconciled at startup.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(error) = self.reconcile(&client).await {
+                warn!(name = %self.name, ?error, "Failed to reconcile webhook configurations.");
+            }
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Long-running and periodic tasks supervised alongside the state machines.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::watch;
+use tracing::{debug, error, info, warn};
+
+use crate::object::ObjectState;
+use crate::operator::Operator;
+use crate::state::SharedState;
+use crate::store::Store;
+
+/// Delay before restarting a background task which failed or exited early.
+const RESTART_BACKOFF_MIN: Duration = Duration::from_secs(1);
+/// Upper bound on the delay between restarts of a background task.
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(300);
+
+/// Passed to each run of a background task.
+pub struct TaskContext<O: Operator> {
+    /// Client for the cluster the runtime watches.
+    pub client: kube::Client,
+    /// Cache of watched resources, shared with the state machines.
+    pub store: Store,
+    /// State shared between the state machines.
+    pub shared: SharedState<<O::ObjectState as ObjectState>::SharedState>,
+    shutdown: watch::Receiver<bool>,
+}
+
+impl<O: Operator> Clone for TaskContext<O> {
+    fn clone(&self) -> Self {
+        TaskContext {
+            client: self.client.clone(),
+            store: self.store.clone(),
+            shared: Arc::clone(&self.shared),
+            shutdown: self.shutdown.clone(),
+        }
+    }
+}
+
+impl<O: Operator> TaskContext<O> {
+    /// Resolves once the runtime is shutting down. Long-running tasks should
+    /// return when this resolves so that shutdown does not have to wait for
+    /// the drain timeout.
+    pub async fn shutdown_requested(&self) {
+        let mut shutdown = self.shutdown.clone();
+        while !*shutdown.borrow() {
+            if shutdown.changed().await.is_err() {
+                futures::future::pending::<()>().await;
+            }
+        }
+    }
+
+    /// Whether the runtime is shutting down.
+    pub fn is_shutting_down(&self) -> bool {
+        *self.shutdown.borrow()
+    }
+}
+
+type TaskFn<O> =
+    Arc<dyn Fn(TaskContext<O>) -> BoxFuture<'static, anyhow::Result<()>> + Send + Sync>;
+
+/// A task registered with [OperatorRuntime](crate::OperatorRuntime) or
+/// [ControllerBuilder](crate::ControllerBuilder).
+pub(crate) struct BackgroundTask<O: Operator> {
+    name: String,
+    /// Run every `period`, or continuously if `None`.
+    period: Option<Duration>,
+    f: TaskFn<O>,
+}
+
+impl<O: Operator> Clone for BackgroundTask<O> {
+    fn clone(&self) -> Self {
+        BackgroundTask {
+            name: self.name.clone(),
+            period: self.period,
+            f: Arc::clone(&self.f),
+        }
+    }
+}
+
+impl<O: Operator> BackgroundTask<O> {
+    pub(crate) fn new<F, Fut>(name: &str, period: Option<Duration>, f: F) -> Self
+    where
+        F: Fn(TaskContext<O>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        BackgroundTask {
+            name: name.to_string(),
+            period,
+            f: Arc::new(move |context| f(context).boxed()),
+        }
+    }
+
+    /// Run the task until shutdown, restarting it with exponential backoff if
+    /// it fails, panics or, when it is not periodic, returns early. `_drain`
+    /// is held until the task has exited.
+    pub(crate) async fn supervise(self, context: TaskContext<O>, _drain: Sender<()>) {
+        info!(task = %self.name, period = ?self.period, "Starting background task.");
+        let mut interval = self.period.map(tokio::time::interval);
+        let mut backoff = RESTART_BACKOFF_MIN;
+        loop {
+            if let Some(ref mut interval) = interval {
+                tokio::select! {
+                    _ = interval.tick() => (),
+                    _ = context.shutdown_requested() => break,
+                }
+            }
+            let run = tokio::spawn((self.f)(context.clone()));
+            let failed = match run.await {
+                Ok(Ok(())) => {
+                    debug!(task = %self.name, "Background task run finished.");
+                    interval.is_none()
+                }
+                Ok(Err(error)) => {
+                    warn!(task = %self.name, ?error, "Background task failed.");
+                    true
+                }
+                Err(error) => {
+                    error!(task = %self.name, ?error, "Background task panicked.");
+                    true
+                }
+            };
+            if context.is_shutting_down() {
+                break;
+            }
+            if !failed {
+                backoff = RESTART_BACKOFF_MIN;
+                continue;
+            }
+            // Periodic tasks are simply retried on their next tick.
+            if interval.is_none() {
+                debug!(task = %self.name, ?backoff, "Restarting background task.");
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => (),
+                    _ = context.shutdown_requested() => break,
+                }
+                backoff = std::cmp::min(backoff * 2, RESTART_BACKOFF_MAX);
+            }
+        }
+        info!(task = %self.name, "Background task stopped.");
+    }
+}
+
+/// Build the context passed to background tasks.
+pub(crate) async fn task_context<O: Operator>(
+    client: kube::Client,
+    store: Store,
+    operator: &O,
+    shutdown: watch::Receiver<bool>,
+) -> TaskContext<O> {
+    TaskContext {
+        client,
+        store,
+        shared: operator.shared_state().await,
+        shutdown,
+    }
+}
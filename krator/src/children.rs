@@ -0,0 +1,133 @@
+//! Child state machines spawned by a state.
+
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::state::{next_with_timeout, resolve_transition, SharedState, State, Step};
+use crate::{Manifest, ObjectState};
+
+/// A set of child state machines, for example one per replica or dependent
+/// resource, whose results a state collects before transitioning.
+///
+/// Children run concurrently on the runtime and are aborted when the
+/// `Children` is dropped, so they are cancelled along with the parent state,
+/// for example when the parent object is deleted, its state times out or
+/// the runtime shuts down. Children do not patch status or run
+/// [StateMiddleware](crate::StateMiddleware), but otherwise follow the same
+/// transitions as top-level state machines.
+///
+/// ```no_run
+/// # use krator::{Children, Manifest, ObjectState, SharedState, State};
+/// # async fn fan_out<C: ObjectState, S: State<C> + Default>(
+/// #     shared: SharedState<C::SharedState>,
+/// #     replicas: Vec<(C, Manifest<C::Manifest>)>,
+/// # ) -> anyhow::Result<()> {
+/// let mut children = Children::new();
+/// for (object_state, manifest) in replicas {
+///     children.spawn(S::default(), shared.clone(), object_state, manifest);
+/// }
+/// for result in children.join().await {
+///     let _replica_state = result?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Children<C: ObjectState> {
+    tasks: Vec<JoinHandle<anyhow::Result<C>>>,
+}
+
+impl<C: ObjectState> Default for Children<C> {
+    fn default() -> Self {
+        Children { tasks: vec![] }
+    }
+}
+
+impl<C: ObjectState> Children<C> {
+    /// Create an empty set of children.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Start a child state machine in `state`, running it until it
+    /// completes.
+    pub fn spawn(
+        &mut self,
+        state: impl State<C>,
+        shared: SharedState<C::SharedState>,
+        object_state: C,
+        manifest: Manifest<C::Manifest>,
+    ) {
+        self.tasks.push(tokio::spawn(run_child(
+            Box::new(state),
+            shared,
+            object_state,
+            manifest,
+        )));
+    }
+
+    /// Number of children spawned.
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Whether no children were spawned.
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Wait for every child to complete. Returns, in the order the children
+    /// were spawned, each child's final object state, or the error it
+    /// completed with.
+    pub async fn join(mut self) -> Vec<anyhow::Result<C>> {
+        // The handles stay in `self` while waiting, so that children are
+        // still aborted if this future is dropped.
+        let mut results = Vec::with_capacity(self.tasks.len());
+        for task in self.tasks.iter_mut() {
+            results.push(match task.await {
+                Ok(result) => result,
+                Err(error) => Err(anyhow::anyhow!("Child state machine failed: {}", error)),
+            });
+        }
+        self.tasks.clear();
+        results
+    }
+}
+
+impl<C: ObjectState> Drop for Children<C> {
+    fn drop(&mut self) {
+        if !self.tasks.is_empty() {
+            debug!(
+                children = self.tasks.len(),
+                "Cancelling child state machines."
+            );
+        }
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+async fn run_child<C: ObjectState>(
+    mut state: Box<dyn State<C>>,
+    shared: SharedState<C::SharedState>,
+    mut object_state: C,
+    manifest: Manifest<C::Manifest>,
+) -> anyhow::Result<C> {
+    let mut attempts: u32 = 0;
+    loop {
+        let transition = next_with_timeout(state, &shared, &mut object_state, &manifest).await;
+        match resolve_transition(transition, &mut attempts) {
+            Step::Enter(next_state, delay) => {
+                state = next_state;
+                if let Some(delay) = delay {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+            Step::Complete(Ok(())) => return Ok(object_state),
+            Step::Complete(Err(error)) => {
+                warn!(?error, "Child state machine exited with error.");
+                return Err(error);
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Standard status conditions.
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+use k8s_openapi::chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// Whether a [Condition] holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ConditionStatus {
+    /// The condition holds.
+    True,
+    /// The condition does not hold.
+    False,
+    /// It is not known whether the condition holds.
+    Unknown,
+}
+
+impl From<bool> for ConditionStatus {
+    fn from(status: bool) -> Self {
+        if status {
+            ConditionStatus::True
+        } else {
+            ConditionStatus::False
+        }
+    }
+}
+
+/// One aspect of an object's current state, following the Kubernetes API
+/// conventions for `status.conditions`. Embed a `Vec<Condition>` in a status
+/// type and update it with [Conditions].
+///
+/// With the `schema` feature, `Condition` implements `JsonSchema` so that it
+/// can be used in custom resources.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Condition {
+    /// Type of the condition, in CamelCase, such as `Ready`.
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// Whether the condition holds.
+    pub status: ConditionStatus,
+    /// The last time `status` changed.
+    pub last_transition_time: Time,
+    /// The `metadata.generation` the condition was computed for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub observed_generation: Option<i64>,
+    /// Programmatic identifier, in CamelCase, for the reason of the last
+    /// transition.
+    #[serde(default)]
+    pub reason: String,
+    /// Human readable details about the last transition.
+    #[serde(default)]
+    pub message: String,
+}
+
+impl Condition {
+    /// A condition of type `type_` which transitioned to `status` now.
+    pub fn new(type_: impl Into<String>, status: impl Into<ConditionStatus>) -> Self {
+        Condition {
+            type_: type_.into(),
+            status: status.into(),
+            last_transition_time: Time(Utc::now()),
+            observed_generation: None,
+            reason: String::new(),
+            message: String::new(),
+        }
+    }
+
+    /// Set the reason of the condition.
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = reason.into();
+        self
+    }
+
+    /// Set the message of the condition.
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    /// Set the generation the condition was computed for.
+    pub fn with_observed_generation(mut self, generation: impl Into<Option<i64>>) -> Self {
+        self.observed_generation = generation.into();
+        self
+    }
+
+    /// Whether the condition holds.
+    pub fn is_true(&self) -> bool {
+        self.status == ConditionStatus::True
+    }
+}
+
+/// Operations on a list of conditions, keyed by type.
+pub trait Conditions {
+    /// The condition of type `type_`, if present.
+    fn get_condition(&self, type_: &str) -> Option<&Condition>;
+
+    /// Add `condition`, replacing any existing condition of the same type.
+    /// `lastTransitionTime` is only updated when the status changes, so
+    /// reasserting a condition keeps the time it originally transitioned.
+    /// Returns whether anything changed.
+    fn set_condition(&mut self, condition: Condition) -> bool;
+
+    /// Remove the condition of type `type_`. Returns whether it was present.
+    fn remove_condition(&mut self, type_: &str) -> bool;
+
+    /// Whether the condition of type `type_` is present and holds.
+    fn is_condition_true(&self, type_: &str) -> bool {
+        self.get_condition(type_)
+            .map(Condition::is_true)
+            .unwrap_or(false)
+    }
+}
+
+impl Conditions for Vec<Condition> {
+    fn get_condition(&self, type_: &str) -> Option<&Condition> {
+        self.iter().find(|condition| condition.type_ == type_)
+    }
+
+    fn set_condition(&mut self, mut condition: Condition) -> bool {
+        match self
+            .iter_mut()
+            .find(|existing| existing.type_ == condition.type_)
+        {
+            Some(existing) => {
+                if existing.status == condition.status {
+                    condition.last_transition_time = existing.last_transition_time.clone();
+                }
+                if *existing == condition {
+                    false
+                } else {
+                    *existing = condition;
+                    true
+                }
+            }
+            None => {
+                self.push(condition);
+                true
+            }
+        }
+    }
+
+    fn remove_condition(&mut self, type_: &str) -> bool {
+        let len = self.len();
+        self.retain(|condition| condition.type_ != type_);
+        self.len() != len
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use k8s_openapi::chrono::Duration;
+
+    #[test]
+    fn transition_time_only_changes_with_status() {
+        let mut conditions = vec![];
+        assert!(conditions.set_condition(Condition::new("Ready", false).with_reason("Pending")));
+        let original = Time(Utc::now() - Duration::minutes(5));
+        conditions[0].last_transition_time = original.clone();
+
+        assert!(!conditions.set_condition(Condition::new("Ready", false).with_reason("Pending")));
+        assert!(conditions.set_condition(Condition::new("Ready", false).with_reason("Waiting")));
+        assert_eq!(conditions[0].last_transition_time, original);
+        assert_eq!(conditions[0].reason, "Waiting");
+
+        assert!(conditions.set_condition(Condition::new("Ready", true)));
+        assert!(conditions[0].last_transition_time > original);
+        assert!(conditions.is_condition_true("Ready"));
+
+        assert!(conditions.remove_condition("Ready"));
+        assert!(conditions.get_condition("Ready").is_none());
+    }
+}
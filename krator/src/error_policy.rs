@@ -0,0 +1,140 @@
+//! Deciding how the runtime recovers from errors.
+
+use std::time::Duration;
+
+use crate::object::ObjectState;
+use crate::state::{State, StateHolder};
+use crate::util::Backoff;
+
+/// Where an error handled by an [ErrorPolicy] occurred.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorSource {
+    /// The state machine stopped with an error returned by the named state.
+    State(&'static str),
+    /// [registration_hook](crate::Operator::registration_hook) failed.
+    RegistrationHook,
+    /// [finalize_hook](crate::Operator::finalize_hook) failed.
+    FinalizeHook,
+    /// [deregistration_hook](crate::Operator::deregistration_hook) failed.
+    DeregistrationHook,
+    /// [handoff_hook](crate::Operator::handoff_hook) failed.
+    HandoffHook,
+    /// Writing the object's status failed, after conflicts were retried.
+    StatusPatch,
+    /// Applying the [DeregistrationPolicy](crate::DeregistrationPolicy)
+    /// failed.
+    Deregistration,
+}
+
+/// What the runtime does after an error, as decided by an [ErrorPolicy].
+pub enum ErrorAction<S: ObjectState> {
+    /// Try again after a delay from the backoff, given the number of
+    /// consecutive failures.
+    Retry(Backoff),
+    /// Try again after the delay.
+    RequeueAfter(Duration),
+    /// Log the error and move on without the failed step.
+    GiveUp,
+    /// Run the state machine from the given state. Only applies to errors of
+    /// the state machine and of the registration hook, and gives up
+    /// otherwise.
+    Enter(StateHolder<S>),
+}
+
+impl<S: ObjectState> ErrorAction<S> {
+    /// Run the state machine from `state`.
+    pub fn enter(state: impl State<S>) -> Self {
+        ErrorAction::Enter(StateHolder {
+            state: Box::new(state),
+        })
+    }
+
+    /// How long to wait before trying again after `failures` consecutive
+    /// failures, or `None` to not try again.
+    pub(crate) fn delay(&self, failures: u32) -> Option<Duration> {
+        match self {
+            ErrorAction::Retry(backoff) => Some(backoff.delay(failures)),
+            ErrorAction::RequeueAfter(after) => Some(*after),
+            ErrorAction::GiveUp | ErrorAction::Enter(_) => None,
+        }
+    }
+}
+
+/// Decides how the runtime recovers from errors of an object's state
+/// machine, its hooks, and the writes krator makes on its behalf. Errors are
+/// logged and passed to [error_hook](crate::Operator::error_hook) regardless.
+///
+/// When the state machine stops with an error, its status is set with
+/// [failed_with](crate::ObjectStatus::failed_with) first, and retrying
+/// restarts it from `InitialState`, or `DeletedState` once the object is
+/// being deleted.
+///
+/// ```
+/// # use krator::{ErrorAction, ErrorPolicy, ErrorSource, ObjectState};
+/// # use std::time::Duration;
+/// /// Requeues failed state machines and gives up on everything else.
+/// struct Requeue;
+///
+/// impl<S: ObjectState> ErrorPolicy<S> for Requeue {
+///     fn on_error(&self, source: ErrorSource, _error: &anyhow::Error, _failures: u32) -> ErrorAction<S> {
+///         match source {
+///             ErrorSource::State(_) => ErrorAction::RequeueAfter(Duration::from_secs(60)),
+///             _ => ErrorAction::GiveUp,
+///         }
+///     }
+/// }
+/// ```
+pub trait ErrorPolicy<S: ObjectState>: Send + Sync + 'static {
+    /// Decide what to do about `error`, which occurred in `source` after
+    /// `failures - 1` consecutive failures of the same step.
+    fn on_error(&self, source: ErrorSource, error: &anyhow::Error, failures: u32)
+        -> ErrorAction<S>;
+}
+
+/// The behaviour of the runtime without a custom [ErrorPolicy]: failed
+/// finalize hooks are retried with exponential backoff of up to five minutes,
+/// and everything else gives up. In particular, an object whose registration
+/// hook fails is not run, as before error policies existed;
+/// use [with_registration_retry](DefaultErrorPolicy::with_registration_retry)
+/// to retry the hook instead.
+///
+/// ```
+/// # use krator::{util::Backoff, DefaultErrorPolicy};
+/// let policy = DefaultErrorPolicy::default().with_registration_retry(Backoff::default());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DefaultErrorPolicy {
+    registration_retry: Option<Backoff>,
+}
+
+impl DefaultErrorPolicy {
+    /// Retry failed registration hooks after a delay from `backoff` instead
+    /// of giving up.
+    pub fn with_registration_retry(mut self, backoff: Backoff) -> Self {
+        self.registration_retry = Some(backoff);
+        self
+    }
+}
+
+impl<S: ObjectState> ErrorPolicy<S> for DefaultErrorPolicy {
+    fn on_error(
+        &self,
+        source: ErrorSource,
+        _error: &anyhow::Error,
+        _failures: u32,
+    ) -> ErrorAction<S> {
+        match source {
+            ErrorSource::FinalizeHook => ErrorAction::Retry(Backoff {
+                initial: Duration::from_secs(1),
+                max: Duration::from_secs(300),
+                multiplier: 2.0,
+                jitter: 0.0,
+            }),
+            ErrorSource::RegistrationHook => match self.registration_retry {
+                Some(ref backoff) => ErrorAction::Retry(backoff.clone()),
+                None => ErrorAction::GiveUp,
+            },
+            _ => ErrorAction::GiveUp,
+        }
+    }
+}
@@ -0,0 +1,331 @@
+//! Export an operator's state graph as DOT or Mermaid.
+//!
+//! The graph is discovered by walking [Transitions] from the operator's
+//! `InitialState` and `DeletedState`. With the `derive-graph` feature, the
+//! `TransitionTo` derive also implements [Transitions]. States whose edges are
+//! declared by hand, and states with no outgoing transitions, implement it
+//! manually:
+//!
+//! ```
+//! # use krator::graph::{StateNode, Transitions};
+//! # struct Roam;
+//! struct Tagged;
+//! struct Released;
+//!
+//! impl Transitions for Tagged {
+//!     fn transitions() -> Vec<StateNode> {
+//!         vec![StateNode::of::<Roam>()]
+//!     }
+//! }
+//! # impl Transitions for Roam {}
+//!
+//! // Terminal state.
+//! impl Transitions for Released {}
+//! ```
+
+use std::collections::HashSet;
+
+use crate::Operator;
+
+/// Lists the states a state may transition to.
+pub trait Transitions: 'static {
+    /// States reachable from this state in a single transition.
+    fn transitions() -> Vec<StateNode> {
+        vec![]
+    }
+}
+
+/// A state in the graph, along with a way to find its transitions.
+#[derive(Clone, Copy)]
+pub struct StateNode {
+    name: &'static str,
+    transitions: fn() -> Vec<StateNode>,
+}
+
+impl StateNode {
+    /// The node for state `T`.
+    pub fn of<T: Transitions>() -> Self {
+        StateNode {
+            name: std::any::type_name::<T>(),
+            transitions: T::transitions,
+        }
+    }
+}
+
+/// Output format for [export].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Graphviz DOT.
+    Dot,
+    /// Mermaid state diagram.
+    Mermaid,
+}
+
+/// The states and transitions reachable from an operator's initial and
+/// deleted states.
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    /// Type names of every state, in the order they were discovered.
+    pub states: Vec<&'static str>,
+    /// Transitions as `(from, to)` type names.
+    pub transitions: Vec<(&'static str, &'static str)>,
+    /// Type name of the initial state.
+    pub initial: &'static str,
+    /// Type name of the state entered when the object is deleted.
+    pub deleted: &'static str,
+}
+
+impl Graph {
+    /// Walk the graph of operator `O`.
+    pub fn of<O: Operator>() -> Self
+    where
+        O::InitialState: Transitions,
+        O::DeletedState: Transitions,
+    {
+        let initial = StateNode::of::<O::InitialState>();
+        let deleted = StateNode::of::<O::DeletedState>();
+        let mut graph = Graph {
+            initial: initial.name,
+            deleted: deleted.name,
+            ..Default::default()
+        };
+        let mut seen = HashSet::new();
+        let mut queue = vec![deleted, initial];
+        while let Some(node) = queue.pop() {
+            if !seen.insert(node.name) {
+                continue;
+            }
+            graph.states.push(node.name);
+            for next in (node.transitions)() {
+                graph.transitions.push((node.name, next.name));
+                queue.push(next);
+            }
+        }
+        graph
+    }
+
+    /// Check the graph for states which can leave an object stuck.
+    ///
+    /// States with no outgoing transitions are assumed to complete. Every
+    /// state reachable from `InitialState` must be able to reach such a
+    /// state, otherwise objects entering it, for example a cycle without an
+    /// exit, never complete. The `DeletedState` must be able to reach such a
+    /// state as well, otherwise deleted objects are never cleaned up.
+    /// `InitialState` and `DeletedState` sharing the same `ObjectState` is
+    /// already enforced by the compiler.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let terminal: HashSet<&str> = self
+            .states
+            .iter()
+            .copied()
+            .filter(|state| !self.transitions.iter().any(|(from, _)| from == state))
+            .collect();
+        let completes = |state: &'static str| {
+            self.reachable_from(state)
+                .iter()
+                .any(|state| terminal.contains(state))
+        };
+        let mut problems = vec![];
+        // Keep the order in which states were discovered.
+        let reachable = self.reachable_from(self.initial);
+        let stuck: Vec<String> = self
+            .states
+            .iter()
+            .copied()
+            .filter(|state| reachable.contains(state) && !completes(state))
+            .map(short_name)
+            .collect();
+        if !stuck.is_empty() {
+            problems.push(format!(
+                "states reachable from InitialState {} never complete: {}",
+                short_name(self.initial),
+                stuck.join(", ")
+            ));
+        }
+        if !completes(self.deleted) {
+            problems.push(format!(
+                "DeletedState {} never completes: no state reachable from it is terminal",
+                short_name(self.deleted)
+            ));
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            anyhow::bail!("Invalid state graph: {}", problems.join("; "))
+        }
+    }
+
+    /// States reachable from `start`, including itself.
+    fn reachable_from(&self, start: &'static str) -> HashSet<&'static str> {
+        let mut reachable = HashSet::new();
+        let mut queue = vec![start];
+        while let Some(state) = queue.pop() {
+            if reachable.insert(state) {
+                queue.extend(
+                    self.transitions
+                        .iter()
+                        .filter(|(from, _)| *from == state)
+                        .map(|(_, to)| *to),
+                );
+            }
+        }
+        reachable
+    }
+
+    /// Render the graph in Graphviz DOT format.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        dot.push_str("    \"[*]\" [shape=point];\n");
+        dot.push_str("    \"[deleted]\" [shape=point];\n");
+        for state in &self.states {
+            dot.push_str(&format!(
+                "    \"{}\" [label=\"{}\"];\n",
+                state,
+                short_name(state)
+            ));
+        }
+        dot.push_str(&format!("    \"[*]\" -> \"{}\";\n", self.initial));
+        dot.push_str(&format!(
+            "    \"[deleted]\" -> \"{}\" [style=dashed];\n",
+            self.deleted
+        ));
+        for (from, to) in &self.transitions {
+            dot.push_str(&format!("    \"{}\" -> \"{}\";\n", from, to));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Render the graph as a Mermaid state diagram.
+    pub fn to_mermaid(&self) -> String {
+        // Type names may contain characters Mermaid does not accept in
+        // identifiers, so states are referred to by index.
+        let id = |name: &str| {
+            let index = self
+                .states
+                .iter()
+                .position(|state| *state == name)
+                .expect("Transitions only reference discovered states.");
+            format!("s{}", index)
+        };
+        let mut mermaid = String::from("stateDiagram-v2\n");
+        for state in &self.states {
+            mermaid.push_str(&format!(
+                "    state \"{}\" as {}\n",
+                short_name(state),
+                id(state)
+            ));
+        }
+        mermaid.push_str(&format!("    [*] --> {}\n", id(self.initial)));
+        mermaid.push_str(&format!(
+            "    note left of {}: On deletion\n",
+            id(self.deleted)
+        ));
+        for (from, to) in &self.transitions {
+            mermaid.push_str(&format!("    {} --> {}\n", id(from), id(to)));
+        }
+        mermaid
+    }
+}
+
+/// Export the state graph of operator `O`.
+pub fn export<O: Operator>(format: Format) -> String
+where
+    O::InitialState: Transitions,
+    O::DeletedState: Transitions,
+{
+    let graph = Graph::of::<O>();
+    match format {
+        Format::Dot => graph.to_dot(),
+        Format::Mermaid => graph.to_mermaid(),
+    }
+}
+
+/// Strip module paths from a type name, including those of generic
+/// arguments.
+pub(crate) fn short_name(name: &str) -> String {
+    let mut short = String::with_capacity(name.len());
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            let segment_start = short
+                .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .map(|i| i + 1)
+                .unwrap_or(0);
+            short.truncate(segment_start);
+        } else {
+            short.push(c);
+        }
+    }
+    short
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(
+        states: &[&'static str],
+        transitions: &[(&'static str, &'static str)],
+        deleted: &'static str,
+    ) -> Graph {
+        Graph {
+            states: states.to_vec(),
+            transitions: transitions.to_vec(),
+            initial: states[0],
+            deleted,
+        }
+    }
+
+    #[test]
+    fn accepts_graph_where_every_state_completes() {
+        let graph = graph(
+            &["a::Init", "a::Work", "a::Done", "a::Deleted"],
+            &[
+                ("a::Init", "a::Work"),
+                ("a::Work", "a::Work"),
+                ("a::Work", "a::Done"),
+                ("a::Deleted", "a::Done"),
+            ],
+            "a::Deleted",
+        );
+        assert!(graph.validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_cycle_without_exit() {
+        let graph = graph(
+            &["a::Init", "a::Ping", "a::Pong", "a::Done", "a::Deleted"],
+            &[
+                ("a::Init", "a::Ping"),
+                ("a::Init", "a::Done"),
+                ("a::Ping", "a::Pong"),
+                ("a::Pong", "a::Ping"),
+            ],
+            "a::Deleted",
+        );
+        let error = graph.validate().unwrap_err().to_string();
+        assert!(
+            error.contains("states reachable from InitialState Init never complete: Ping, Pong"),
+            "{}",
+            error
+        );
+        assert!(!error.contains("DeletedState"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_deleted_state_which_never_completes() {
+        let graph = graph(
+            &["a::Init", "a::Deleted", "a::Retry"],
+            &[("a::Deleted", "a::Retry"), ("a::Retry", "a::Deleted")],
+            "a::Deleted",
+        );
+        let error = graph.validate().unwrap_err().to_string();
+        assert!(
+            error.contains("DeletedState Deleted never completes"),
+            "{}",
+            error
+        );
+    }
+}
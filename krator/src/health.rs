@@ -0,0 +1,242 @@
+//! Liveness and readiness of a [Manager](crate::Manager).
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use kube::api::GroupVersionKind;
+use serde::Serialize;
+use tokio::sync::watch;
+
+/// Tracks the connectivity and initial sync of a Manager's watchers, and
+/// whether it is leading. Cloning returns a handle to the same state.
+///
+/// The Manager is ready once every watcher has listed its objects and is
+/// connected, and healthy unless a watcher has been failing for longer than
+/// [with_unhealthy_after](Health::with_unhealthy_after).
+///
+/// With the `health-endpoint` feature, the status can be served over HTTP
+/// with [serve](Health::serve), or by the Manager with
+/// [with_health_endpoint](crate::Manager::with_health_endpoint).
+#[derive(Clone)]
+pub struct Health {
+    inner: Arc<Mutex<Inner>>,
+    /// Notified whenever a watcher changes.
+    changed_tx: Arc<watch::Sender<()>>,
+    changed_rx: watch::Receiver<()>,
+}
+
+struct Inner {
+    watchers: BTreeMap<(String, Option<String>), Watcher>,
+    leader: Option<bool>,
+    unhealthy_after: Duration,
+}
+
+#[derive(Default)]
+struct Watcher {
+    synced: bool,
+    /// When the watcher started failing, if its last attempt failed.
+    failing_since: Option<Instant>,
+}
+
+/// The health of a Manager at a point in time.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthStatus {
+    /// Whether no watcher has been failing for too long.
+    pub healthy: bool,
+    /// Whether every watcher has listed its objects and is connected.
+    pub ready: bool,
+    /// Whether this replica is leading, if leader election is used.
+    pub leader: Option<bool>,
+    /// Status of every watcher, ordered by kind and namespace.
+    pub watchers: Vec<WatcherStatus>,
+}
+
+/// The status of a single watcher.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatcherStatus {
+    /// The watched kind, as `group/version/kind`.
+    pub kind: String,
+    /// The watched namespace, or `None` for all namespaces.
+    pub namespace: Option<String>,
+    /// Whether the watcher has listed its objects at least once.
+    pub synced: bool,
+    /// Whether the last attempt to stream events succeeded.
+    pub connected: bool,
+    /// How long the watcher has been failing.
+    #[serde(serialize_with = "serialize_seconds", rename = "failingSeconds")]
+    pub failing_for: Option<Duration>,
+}
+
+fn serialize_seconds<S: serde::Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => serializer.serialize_some(&duration.as_secs_f64()),
+        None => serializer.serialize_none(),
+    }
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        let (changed_tx, changed_rx) = watch::channel(());
+        Health {
+            inner: Arc::new(Mutex::new(Inner {
+                watchers: BTreeMap::new(),
+                leader: None,
+                unhealthy_after: Duration::from_secs(300),
+            })),
+            changed_tx: Arc::new(changed_tx),
+            changed_rx,
+        }
+    }
+}
+
+fn watcher_key(gvk: &GroupVersionKind, namespace: &Option<String>) -> (String, Option<String>) {
+    (
+        format!("{}/{}/{}", gvk.group, gvk.version, gvk.kind),
+        namespace.clone(),
+    )
+}
+
+impl Health {
+    /// Create a tracker without watchers, which is ready and healthy.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Report the Manager as unhealthy once a watcher has been failing for
+    /// `duration`. Defaults to five minutes.
+    pub fn with_unhealthy_after(self, duration: Duration) -> Self {
+        self.lock().unhealthy_after = duration;
+        self
+    }
+
+    /// Report whether this replica is leading, for example from
+    /// [LeaderElection::on_change](crate::LeaderElection::on_change).
+    pub fn set_leader(&self, leading: bool) {
+        self.lock().leader = Some(leading);
+    }
+
+    /// The current health.
+    pub fn status(&self) -> HealthStatus {
+        let inner = self.lock();
+        let watchers: Vec<WatcherStatus> = inner
+            .watchers
+            .iter()
+            .map(|((kind, namespace), watcher)| WatcherStatus {
+                kind: kind.clone(),
+                namespace: namespace.clone(),
+                synced: watcher.synced,
+                connected: watcher.failing_since.is_none(),
+                failing_for: watcher.failing_since.map(|since| since.elapsed()),
+            })
+            .collect();
+        HealthStatus {
+            healthy: watchers.iter().all(|watcher| {
+                watcher
+                    .failing_for
+                    .map_or(true, |failing_for| failing_for < inner.unhealthy_after)
+            }),
+            ready: watchers
+                .iter()
+                .all(|watcher| watcher.synced && watcher.connected),
+            leader: inner.leader,
+            watchers,
+        }
+    }
+
+    /// Resolve once every watcher has listed its objects and is connected.
+    pub async fn wait_ready(&self) {
+        let mut changed = self.changed_rx.clone();
+        while !self.status().ready {
+            // The sender lives as long as `self`.
+            let _ = changed.changed().await;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<Inner> {
+        self.inner.lock().expect("Health lock poisoned.")
+    }
+
+    pub(crate) fn watcher_started(&self, gvk: &GroupVersionKind, namespace: &Option<String>) {
+        self.lock()
+            .watchers
+            .entry(watcher_key(gvk, namespace))
+            .or_default();
+        let _ = self.changed_tx.send(());
+    }
+
+    pub(crate) fn watcher_connected(
+        &self,
+        gvk: &GroupVersionKind,
+        namespace: &Option<String>,
+        synced: bool,
+    ) {
+        let mut inner = self.lock();
+        let watcher = inner
+            .watchers
+            .entry(watcher_key(gvk, namespace))
+            .or_default();
+        watcher.failing_since = None;
+        watcher.synced |= synced;
+        drop(inner);
+        let _ = self.changed_tx.send(());
+    }
+
+    pub(crate) fn watcher_failed(&self, gvk: &GroupVersionKind, namespace: &Option<String>) {
+        let mut inner = self.lock();
+        let watcher = inner
+            .watchers
+            .entry(watcher_key(gvk, namespace))
+            .or_default();
+        watcher.failing_since.get_or_insert_with(Instant::now);
+        drop(inner);
+        let _ = self.changed_tx.send(());
+    }
+
+    /// Serve `GET /healthz` and `GET /readyz` at `address`, responding with
+    /// the [status](Health::status) as JSON, and with `503 Service
+    /// Unavailable` while not healthy or ready. Runs until the server fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `address` cannot be bound, for example because it
+    /// is already in use.
+    #[cfg(feature = "health-endpoint")]
+    pub async fn serve(self, address: impl Into<std::net::SocketAddr>) -> anyhow::Result<()> {
+        use anyhow::Context;
+        use warp::http::StatusCode;
+        use warp::Filter;
+        let healthz = {
+            let health = self.clone();
+            warp::path!("healthz").map(move || {
+                let status = health.status();
+                let code = if status.healthy {
+                    StatusCode::OK
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE
+                };
+                warp::reply::with_status(warp::reply::json(&status), code)
+            })
+        };
+        let readyz = warp::path!("readyz").map(move || {
+            let status = self.status();
+            let code = if status.ready {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            };
+            warp::reply::with_status(warp::reply::json(&status), code)
+        });
+        let address = address.into();
+        let (_, server) = warp::serve(warp::get().and(healthz.or(readyz)))
+            .try_bind_ephemeral(address)
+            .with_context(|| format!("Failed to bind health endpoint to {}", address))?;
+        server.await;
+        Ok(())
+    }
+}
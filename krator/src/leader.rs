@@ -0,0 +1,361 @@
+//! Leader election using `coordination.k8s.io` Leases.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use k8s_openapi::api::coordination::v1::{Lease, LeaseSpec};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::MicroTime;
+use k8s_openapi::chrono::Utc;
+use kube::api::{Api, ObjectMeta, PostParams};
+use tokio::sync::{oneshot, watch};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+type LeadershipCallback = Arc<dyn Fn(bool) + Send + Sync>;
+
+/// Configuration for electing a single leader among replicas of an operator.
+///
+/// Only the replica holding the Lease watches and dispatches objects. Every
+/// replica uses the same Lease name and namespace, and a unique identity
+/// (such as the Pod name).
+///
+/// ```
+/// use krator::LeaderElection;
+/// let election = LeaderElection::new("default", "moose-operator", "moose-operator-0")
+///     .on_change(|leading| println!("Leading: {}", leading));
+/// ```
+#[derive(Clone)]
+pub struct LeaderElection {
+    namespace: String,
+    lease_name: String,
+    identity: String,
+    lease_duration: Duration,
+    renew_deadline: Duration,
+    renew_interval: Duration,
+    retry_interval: Duration,
+    callbacks: Vec<LeadershipCallback>,
+}
+
+impl LeaderElection {
+    /// Elect a leader using the Lease `lease_name` in `namespace`, identifying
+    /// this replica as `identity`.
+    pub fn new(namespace: &str, lease_name: &str, identity: &str) -> Self {
+        LeaderElection {
+            namespace: namespace.to_string(),
+            lease_name: lease_name.to_string(),
+            identity: identity.to_string(),
+            lease_duration: Duration::from_secs(15),
+            renew_deadline: Duration::from_secs(10),
+            renew_interval: Duration::from_secs(5),
+            retry_interval: Duration::from_secs(2),
+            callbacks: vec![],
+        }
+    }
+
+    /// How long other replicas wait after they last saw the Lease change
+    /// before taking it over.
+    pub fn with_lease_duration(mut self, duration: Duration) -> Self {
+        self.lease_duration = duration;
+        self
+    }
+
+    /// How long the leader keeps leading while it fails to renew the Lease.
+    /// Must be shorter than the lease duration, so that the leader steps
+    /// down before another replica can take over; otherwise two thirds of
+    /// the lease duration are used. Defaults to 10 seconds.
+    pub fn with_renew_deadline(mut self, deadline: Duration) -> Self {
+        self.renew_deadline = deadline;
+        self
+    }
+
+    /// How often the leader renews the Lease. Should be well below the lease
+    /// duration.
+    pub fn with_renew_interval(mut self, interval: Duration) -> Self {
+        self.renew_interval = interval;
+        self
+    }
+
+    /// How often replicas which are not leading try to acquire the Lease.
+    pub fn with_retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+
+    /// Register a callback which is invoked with `true` when this replica
+    /// becomes leader and with `false` when it loses leadership.
+    pub fn on_change<F>(mut self, f: F) -> Self
+    where
+        F: Fn(bool) + Send + Sync + 'static,
+    {
+        self.callbacks.push(Arc::new(f));
+        self
+    }
+
+    /// Start acquiring and renewing the Lease in the background. The returned
+    /// channel reflects whether this replica is currently leading. The Lease
+    /// is released once the returned task is released or dropped.
+    pub(crate) fn spawn(self, client: kube::Client) -> (watch::Receiver<bool>, LeaderTask) {
+        let (tx, rx) = watch::channel(false);
+        let (release, released) = oneshot::channel();
+        let handle = tokio::spawn(self.run(client, tx, released));
+        (rx, LeaderTask { handle, release })
+    }
+
+    async fn run(
+        self,
+        client: kube::Client,
+        tx: watch::Sender<bool>,
+        mut released: oneshot::Receiver<()>,
+    ) {
+        let api: Api<Lease> = Api::namespaced(client, &self.namespace);
+        let renew_deadline = if self.renew_deadline < self.lease_duration {
+            self.renew_deadline
+        } else {
+            warn!(
+                renew_deadline = ?self.renew_deadline,
+                lease_duration = ?self.lease_duration,
+                "Renew deadline is not shorter than the lease duration."
+            );
+            self.lease_duration * 2 / 3
+        };
+        let mut observed: Option<Observed> = None;
+        let mut leading = false;
+        let mut last_renewal: Option<Instant> = None;
+        loop {
+            let acquired = match self.try_acquire_or_renew(&api, &mut observed).await {
+                Ok(acquired) => acquired,
+                Err(error) => {
+                    warn!(
+                        lease = %self.lease_name,
+                        ?error,
+                        "Unable to acquire or renew Lease."
+                    );
+                    // Step down before the Lease could be taken over.
+                    leading
+                        && last_renewal
+                            .map(|renewal| renewal.elapsed() < renew_deadline)
+                            .unwrap_or(false)
+                }
+            };
+            if acquired && leading {
+                last_renewal = Some(Instant::now());
+            } else if acquired != leading {
+                if acquired {
+                    last_renewal = Some(Instant::now());
+                    info!(lease = %self.lease_name, identity = %self.identity, "Acquired leadership.");
+                } else {
+                    warn!(lease = %self.lease_name, identity = %self.identity, "Lost leadership.");
+                }
+                leading = acquired;
+                if !self.notify(&tx, leading) {
+                    debug!("Leadership receiver hung up, exiting.");
+                    return;
+                }
+            }
+            let interval = if leading {
+                self.renew_interval
+            } else {
+                self.retry_interval
+            };
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => (),
+                _ = &mut released => break,
+            }
+        }
+        if leading {
+            self.notify(&tx, false);
+            if let Err(error) = self.release(&api).await {
+                warn!(lease = %self.lease_name, ?error, "Unable to release Lease.");
+            } else {
+                info!(lease = %self.lease_name, identity = %self.identity, "Released leadership.");
+            }
+        }
+    }
+
+    /// Publish a change of leadership. Returns `false` if nobody is
+    /// listening anymore.
+    fn notify(&self, tx: &watch::Sender<bool>, leading: bool) -> bool {
+        if tx.send(leading).is_err() {
+            return false;
+        }
+        for callback in &self.callbacks {
+            callback(leading);
+        }
+        true
+    }
+
+    /// Returns whether this replica holds the Lease after the attempt.
+    /// Whether another replica's Lease expired is decided with the local
+    /// time at which its record last changed, so that clock skew between
+    /// replicas does not matter.
+    async fn try_acquire_or_renew(
+        &self,
+        api: &Api<Lease>,
+        observed: &mut Option<Observed>,
+    ) -> kube::Result<bool> {
+        let now = MicroTime(Utc::now());
+        let lease_duration_seconds = self.lease_duration.as_secs() as i32;
+        let lease = match api.get(&self.lease_name).await {
+            Ok(lease) => lease,
+            Err(kube::Error::Api(kube::error::ErrorResponse { code, .. })) if code == 404 => {
+                let lease = Lease {
+                    metadata: ObjectMeta {
+                        name: Some(self.lease_name.clone()),
+                        namespace: Some(self.namespace.clone()),
+                        ..Default::default()
+                    },
+                    spec: Some(LeaseSpec {
+                        holder_identity: Some(self.identity.clone()),
+                        lease_duration_seconds: Some(lease_duration_seconds),
+                        acquire_time: Some(now.clone()),
+                        renew_time: Some(now),
+                        lease_transitions: Some(0),
+                    }),
+                };
+                return match api.create(&PostParams::default(), &lease).await {
+                    Ok(_) => Ok(true),
+                    Err(kube::Error::Api(kube::error::ErrorResponse { code, .. }))
+                        if code == 409 =>
+                    {
+                        Ok(false)
+                    }
+                    Err(error) => Err(error),
+                };
+            }
+            Err(error) => return Err(error),
+        };
+
+        let mut spec = lease.spec.clone().unwrap_or_default();
+        let changed = match observed {
+            Some(ref observed) => observed.spec != spec,
+            None => true,
+        };
+        if changed {
+            *observed = Some(Observed {
+                spec: spec.clone(),
+                at: Instant::now(),
+            });
+        }
+        let holder = spec.holder_identity.clone().unwrap_or_default();
+        if holder == self.identity {
+            spec.renew_time = Some(now);
+            spec.lease_duration_seconds = Some(lease_duration_seconds);
+        } else {
+            let expired = match observed {
+                Some(ref observed) => {
+                    let duration = spec
+                        .lease_duration_seconds
+                        .unwrap_or(lease_duration_seconds)
+                        .max(0);
+                    observed.at.elapsed() >= Duration::from_secs(duration as u64)
+                }
+                None => true,
+            };
+            if !holder.is_empty() && !expired {
+                return Ok(false);
+            }
+            spec.holder_identity = Some(self.identity.clone());
+            spec.lease_duration_seconds = Some(lease_duration_seconds);
+            spec.acquire_time = Some(now.clone());
+            spec.renew_time = Some(now);
+            spec.lease_transitions = Some(spec.lease_transitions.unwrap_or(0) + 1);
+        }
+
+        // The replace carries the resourceVersion we read, so a concurrent
+        // update by another replica results in a conflict.
+        let lease = Lease {
+            spec: Some(spec),
+            ..lease
+        };
+        match api
+            .replace(&self.lease_name, &PostParams::default(), &lease)
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(kube::Error::Api(kube::error::ErrorResponse { code, .. })) if code == 409 => {
+                Ok(false)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Clear the holder of the Lease if this replica still holds it, so that
+    /// another replica can take over right away.
+    async fn release(&self, api: &Api<Lease>) -> kube::Result<()> {
+        let lease = api.get(&self.lease_name).await?;
+        let mut spec = lease.spec.clone().unwrap_or_default();
+        if spec.holder_identity.as_deref() != Some(self.identity.as_str()) {
+            return Ok(());
+        }
+        spec.holder_identity = None;
+        spec.lease_duration_seconds = Some(1);
+        spec.renew_time = Some(MicroTime(Utc::now()));
+        let lease = Lease {
+            spec: Some(spec),
+            ..lease
+        };
+        api.replace(&self.lease_name, &PostParams::default(), &lease)
+            .await?;
+        Ok(())
+    }
+}
+
+/// The Lease record as last seen by this replica, and when it was first
+/// seen.
+struct Observed {
+    spec: LeaseSpec,
+    at: Instant,
+}
+
+/// The background task of a [LeaderElection].
+pub(crate) struct LeaderTask {
+    handle: JoinHandle<()>,
+    release: oneshot::Sender<()>,
+}
+
+impl LeaderTask {
+    /// Stop leading and release the Lease if this replica holds it, waiting
+    /// until it has been released.
+    pub(crate) async fn release(self) {
+        let _ = self.release.send(());
+        let _ = self.handle.await;
+    }
+}
+
+/// Whether this replica is leading, as decided by the leader election of a
+/// [Manager](crate::Manager). Cloning returns a handle to the same state, so
+/// it can be kept in the [SharedState](crate::SharedState) of controllers.
+///
+/// Without leader election, every replica is leading.
+#[derive(Clone)]
+pub struct Leadership {
+    leader: watch::Receiver<bool>,
+}
+
+impl Leadership {
+    pub(crate) fn new(leader: watch::Receiver<bool>) -> Self {
+        Leadership { leader }
+    }
+
+    /// Whether this replica is currently leading.
+    pub fn is_leading(&self) -> bool {
+        *self.leader.borrow()
+    }
+
+    /// Resolve once this replica is leading, or once it is no longer leading
+    /// if `leading` is `false`.
+    pub async fn wait_for(&self, leading: bool) {
+        let mut leader = self.leader.clone();
+        wait_for_leadership(&mut leader, leading).await
+    }
+}
+
+/// Resolves once the leadership channel reports `leading`. Never resolves if
+/// the election task has exited.
+pub(crate) async fn wait_for_leadership(leader: &mut watch::Receiver<bool>, leading: bool) {
+    while *leader.borrow() != leading {
+        if leader.changed().await.is_err() {
+            futures::future::pending::<()>().await;
+        }
+    }
+}
@@ -2,33 +2,54 @@
 
 #![deny(missing_docs)]
 
+mod background;
+mod children;
+mod condition;
+mod error_policy;
+pub mod graph;
+pub mod health;
+mod leader;
 mod manifest;
+pub mod metrics;
 mod object;
 mod operator;
+pub mod owned;
+mod quarantine;
+mod rate_limit;
 mod runtime;
 mod store;
+pub mod tracker;
 pub mod util;
+mod write;
 
 #[cfg(feature = "admission-webhook")]
 pub mod admission;
 
 pub mod state;
+mod status;
 
-// TODO: Remove once webhooks are supported.
-#[cfg(not(feature = "admission-webhook"))]
 mod manager;
-#[cfg(not(feature = "admission-webhook"))]
-pub use manager::controller::ControllerBuilder;
-#[cfg(not(feature = "admission-webhook"))]
-pub use manager::Manager;
-
-pub use manifest::Manifest;
-pub use object::{ObjectState, ObjectStatus};
-pub use operator::Operator;
+pub use manager::controller::{ControllerBuilder, ControllerOverrides};
+pub use manager::{ControllerSelection, Manager, ManagerHandle, SupervisionPolicy};
+mod multicluster;
+pub use multicluster::MultiClusterRuntime;
+
+pub use background::TaskContext;
+pub use children::Children;
+pub use condition::{Condition, ConditionStatus, Conditions};
+pub use error_policy::{DefaultErrorPolicy, ErrorAction, ErrorPolicy, ErrorSource};
+pub use leader::{LeaderElection, Leadership};
+pub use manifest::{Manifest, ManifestDiff};
+pub use object::{ObjectKey, ObjectState, ObjectStatus, StateError};
 pub use operator::Watchable;
-pub use runtime::OperatorRuntime;
-pub use state::{SharedState, State, Transition, TransitionTo};
-pub use store::Store;
+pub use operator::{DeregistrationPolicy, Operator, PatchStrategy, StatusMode};
+pub use quarantine::{QuarantineHandle, QuarantinedObject};
+pub use rate_limit::RateLimit;
+pub use runtime::{OperatorRuntime, OverflowPolicy, PauseHandle, ShutdownHandle};
+pub use state::{
+    OnTimeout, SharedState, State, StateMiddleware, StateOutcome, Transition, TransitionTo,
+};
+pub use store::{KindSnapshot, MemoryBackend, Store, StoreBackend, StoreEvent, StoreSnapshot};
 
 #[cfg(feature = "derive")]
 #[allow(unused_imports)]
@@ -38,3 +59,12 @@ extern crate krator_derive;
 #[cfg(feature = "derive")]
 #[doc(hidden)]
 pub use krator_derive::*;
+
+/// Dependencies of code generated by the derive macros.
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use anyhow;
+    pub use async_trait;
+    pub use serde_json;
+}
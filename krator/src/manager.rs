@@ -1,66 +1,869 @@
 //! Defines types for registering controllers with runtime.
-use crate::{operator::Operator, store::Store};
+use crate::{
+    health::Health,
+    leader::{LeaderElection, Leadership},
+    metrics::ManagerMetrics,
+    operator::Operator,
+    runtime::{wait_shutdown, PauseHandle, ShutdownHandle},
+    store::{Store, StoreBackend},
+    util::Backoff,
+};
+use anyhow::Context;
+use kube::api::GroupVersionKind;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
 
 pub mod tasks;
-use tasks::{controller_tasks, OperatorTask};
+use tasks::{controller_tasks, StartupHook};
+
+mod selection;
+pub use selection::ControllerSelection;
+
+mod supervision;
+pub use supervision::SupervisionPolicy;
+use supervision::{SupervisedTask, Supervisor};
 
 pub mod controller;
-use controller::{Controller, ControllerBuilder};
-mod watch;
+#[cfg(feature = "admission-webhook")]
+use controller::ControllerWebhooks;
+use controller::{Controller, ControllerBuilder, ControllerOverrides};
+#[cfg(feature = "admission-webhook")]
+use k8s_openapi::api::admissionregistration::v1::ServiceReference;
+pub(crate) mod watch;
+
+mod metadata;
 
 /// Coordinates one or more controllers and the main entrypoint for starting
 /// the application.
 ///
-/// # Warning
-///
-/// This API does not support admissions webhooks yet, please
-/// use [OperatorRuntime](crate::runtime::OperatorRuntime).
+/// Admission webhooks registered with
+/// [ControllerBuilder::validates](ControllerBuilder::validates) and the like,
+/// and the [admission_hook](crate::Operator::admission_hook) of controllers
+/// built with [ControllerBuilder::with_admission_hook], are served together
+/// on port 8443 alongside the controllers, or by the server set with
+/// [with_admission_server](Manager::with_admission_server). Each controller's
+/// [admission_hook_tls](crate::Operator::admission_hook_tls) is served for
+/// the names its certificate is valid for, and the first one for others. It
+/// is reloaded as configured by the controller's
+/// [admission_hook_tls_refresh](crate::Operator::admission_hook_tls_refresh).
 pub struct Manager {
-    kubeconfig: kube::Config,
+    client: kube::Client,
     controllers: Vec<Controller>,
-    controller_tasks: Vec<OperatorTask>,
+    /// Controllers which run.
+    selection: ControllerSelection,
+    /// Names of every registered controller, including disabled ones.
+    registered: Vec<String>,
+    /// Name of each enabled controller, with the kind and namespace it
+    /// manages.
+    managed: Vec<(String, GroupVersionKind, Option<String>)>,
+    controller_tasks: Vec<SupervisedTask>,
+    startup_hooks: Vec<StartupHook>,
     store: Store,
+    watch_backoff: Backoff,
+    supervision: SupervisionPolicy,
+    pause: PauseHandle,
+    shutdown_tx: Arc<watch::Sender<bool>>,
+    shutdown_rx: watch::Receiver<bool>,
+    /// How long shutdown waits for controllers to stop.
+    shutdown_timeout: Option<Duration>,
+    /// Whether SIGTERM and SIGINT request shutdown.
+    handle_signals: bool,
+    health: Health,
+    /// Elects the replica whose controllers run.
+    leader_election: Option<LeaderElection>,
+    /// Whether this replica is leading, which it always is without leader
+    /// election.
+    leader_tx: Arc<watch::Sender<bool>>,
+    leader_rx: watch::Receiver<bool>,
+    /// Address `/healthz` and `/readyz` are served at.
+    #[cfg(feature = "health-endpoint")]
+    health_address: Option<std::net::SocketAddr>,
+    metrics: ManagerMetrics,
+    /// Address `/metrics` is served at.
+    #[cfg(feature = "metrics-endpoint")]
+    metrics_address: Option<std::net::SocketAddr>,
+    /// Address `/debug/store` is served at.
+    #[cfg(feature = "debug-endpoint")]
+    debug_address: Option<std::net::SocketAddr>,
+    /// Routes of every registered admission webhook.
+    #[cfg(feature = "admission-webhook")]
+    webhooks: Option<ControllerWebhooks>,
+    /// Name of the webhook configurations to reconcile, and the Service
+    /// they point at.
+    #[cfg(feature = "admission-webhook")]
+    webhook_configurations: Option<(String, ServiceReference)>,
+    /// Metrics and audit sinks of every admission webhook.
+    #[cfg(feature = "admission-webhook")]
+    admission: crate::admission::AdmissionObserver,
+    /// Serves the admission webhooks instead of the built-in server.
+    #[cfg(feature = "admission-webhook")]
+    admission_server: Option<Box<dyn crate::admission::AdmissionServer>>,
 }
 
 impl Manager {
     /// Create a new controller manager.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a client cannot be created from `kubeconfig`. Use
+    /// [from_client](Self::from_client) to handle this.
     pub fn new(kubeconfig: &kube::Config) -> Self {
+        Self::from_client(crate::runtime::client_from_config(kubeconfig))
+    }
+
+    /// Create a new controller manager using an existing client, which is
+    /// shared by every controller, for example one with custom middleware.
+    pub fn from_client(client: kube::Client) -> Self {
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let (leader_tx, leader_rx) = watch::channel(true);
+        let metrics = ManagerMetrics::new();
+        let store = Store::new().with_client(client.clone());
+        #[cfg(feature = "admission-webhook")]
+        let admission = {
+            let admission = crate::admission::AdmissionObserver::default();
+            let admission_metrics = crate::metrics::AdmissionMetrics::new();
+            metrics.set_admission(admission_metrics.clone());
+            admission.set_metrics(admission_metrics);
+            admission
+        };
         Manager {
             controllers: vec![],
+            selection: Default::default(),
+            registered: vec![],
+            managed: vec![],
             controller_tasks: vec![],
-            kubeconfig: kubeconfig.clone(),
-            store: Store::new(),
+            startup_hooks: vec![],
+            client,
+            store,
+            watch_backoff: Default::default(),
+            supervision: Default::default(),
+            pause: PauseHandle::new(),
+            shutdown_tx: Arc::new(shutdown_tx),
+            shutdown_rx,
+            shutdown_timeout: None,
+            handle_signals: true,
+            health: Health::new(),
+            leader_election: None,
+            leader_tx: Arc::new(leader_tx),
+            leader_rx,
+            #[cfg(feature = "health-endpoint")]
+            health_address: None,
+            metrics,
+            #[cfg(feature = "metrics-endpoint")]
+            metrics_address: None,
+            #[cfg(feature = "debug-endpoint")]
+            debug_address: None,
+            #[cfg(feature = "admission-webhook")]
+            webhooks: None,
+            #[cfg(feature = "admission-webhook")]
+            webhook_configurations: None,
+            #[cfg(feature = "admission-webhook")]
+            admission,
+            #[cfg(feature = "admission-webhook")]
+            admission_server: None,
         }
     }
 
-    /// Register a controller with the manager.
-    pub fn register_controller<C: Operator>(&mut self, builder: ControllerBuilder<C>) {
-        let (controller, tasks) =
-            controller_tasks(self.kubeconfig.clone(), builder, self.store.clone());
+    /// Change the backoff applied when watchers return errors.
+    pub fn with_watch_backoff(mut self, backoff: Backoff) -> Self {
+        self.watch_backoff = backoff;
+        self
+    }
+
+    /// Only run the controllers enabled by `selection`, for example one
+    /// parsed from a command line flag or read with
+    /// [ControllerSelection::from_env]. Controllers which are registered
+    /// afterwards and not enabled are skipped entirely, including their
+    /// admission webhooks. By default, every controller runs.
+    pub fn with_controller_selection(mut self, selection: ControllerSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Change how watchers and controllers which panic or stop before
+    /// shutdown is requested are restarted. See [SupervisionPolicy].
+    pub fn with_supervision(mut self, policy: SupervisionPolicy) -> Self {
+        self.supervision = policy;
+        self
+    }
+
+    /// Limit how long shutdown waits for controllers to drain before
+    /// [start](Self::start) returns. By default shutdown waits until every
+    /// running state machine has finished its current state.
+    pub fn with_shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = Some(timeout);
+        self
+    }
+
+    /// Whether SIGTERM and SIGINT request shutdown, which is the default.
+    /// Disable it to handle signals yourself and call
+    /// [ShutdownHandle::shutdown](crate::ShutdownHandle::shutdown).
+    pub fn with_signal_handling(mut self, enabled: bool) -> Self {
+        self.handle_signals = enabled;
+        self
+    }
+
+    /// Obtain a handle to the liveness and readiness of the manager, which
+    /// reflects its watchers while `start` is running.
+    pub fn health(&self) -> Health {
+        self.health.clone()
+    }
+
+    /// Serve `GET /healthz` and `GET /readyz` at `address` while `start` is
+    /// running, for the liveness and readiness probes of the Deployment. See
+    /// [Health].
+    #[cfg(feature = "health-endpoint")]
+    pub fn with_health_endpoint(mut self, address: impl Into<std::net::SocketAddr>) -> Self {
+        self.health_address = Some(address.into());
+        self
+    }
+
+    /// Only run controllers while this replica holds the Lease of `election`,
+    /// so that every controller follows leadership together. Controllers
+    /// built with
+    /// [without_leader_election](ControllerBuilder::without_leader_election)
+    /// run on every replica. When leadership is lost, controllers drain
+    /// their running state machines and wait to lead again, while their
+    /// caches stay up to date.
+    pub fn with_leader_election(mut self, election: LeaderElection) -> Self {
+        let _ = self.leader_tx.send(false);
+        self.health.set_leader(false);
+        self.leader_election = Some(election);
+        self
+    }
+
+    /// Obtain a handle reflecting whether this replica is leading, for
+    /// example to be kept in the shared state of controllers.
+    pub fn leadership(&self) -> Leadership {
+        Leadership::new(self.leader_rx.clone())
+    }
+
+    /// Obtain a handle to the metrics of every registered controller and of
+    /// the admission webhooks.
+    pub fn metrics(&self) -> ManagerMetrics {
+        self.metrics.clone()
+    }
+
+    /// Serve `GET /metrics` at `address` while `start` is running, in the
+    /// Prometheus text exposition format. See [ManagerMetrics].
+    #[cfg(feature = "metrics-endpoint")]
+    pub fn with_metrics_endpoint(mut self, address: impl Into<std::net::SocketAddr>) -> Self {
+        self.metrics_address = Some(address.into());
+        self
+    }
+
+    /// Obtain a handle to the [Store] caching the objects watched by every
+    /// registered controller.
+    pub fn store(&self) -> Store {
+        self.store.clone()
+    }
+
+    /// Cache at most `max_objects` objects of type `R` in the [Store],
+    /// evicting the least recently used ones and fetching them from the API
+    /// server when they are read again. See [Store::with_limit].
+    pub fn with_store_limit<R: k8s_openapi::Resource>(mut self, max_objects: usize) -> Self {
+        self.store = self.store.with_limit::<R>(max_objects);
+        self
+    }
+
+    /// Keep the objects cached for every controller in `backend` instead of
+    /// in memory. See [StoreBackend]. Handles obtained from
+    /// [store](Self::store) before keep the previous backend.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a controller was registered already, since it would keep
+    /// caching its objects in the previous backend.
+    pub fn with_store_backend(mut self, backend: impl StoreBackend) -> Self {
+        assert!(
+            self.registered.is_empty(),
+            "The store backend must be set before any controller is registered."
+        );
+        self.store = self.store.with_backend(backend);
+        self
+    }
+
+    /// Serve `GET /debug/store` at `address` while `start` is running,
+    /// dumping what the [Store] has cached. See [Store::serve].
+    #[cfg(feature = "debug-endpoint")]
+    pub fn with_debug_endpoint(mut self, address: impl Into<std::net::SocketAddr>) -> Self {
+        self.debug_address = Some(address.into());
+        self
+    }
+
+    /// Obtain a handle which requests a graceful shutdown of every registered
+    /// controller while `start` is running.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle::new(Arc::clone(&self.shutdown_tx))
+    }
+
+    /// Create and keep reconciled a `ValidatingWebhookConfiguration` and a
+    /// `MutatingWebhookConfiguration` named `name`, registering the webhooks
+    /// of every controller with the API server. They are sent through the
+    /// Service `service` in `namespace` on port 443, for objects of the
+    /// controller's type being created or updated.
+    ///
+    /// `name` should be a domain, such as `mooses.animals.com`, as webhook
+    /// names are derived from it. The `caBundle` is left to others, such as
+    /// `admission::CertificateBootstrap` or cert-manager's CA injector. The
+    /// operator needs permission to patch both kinds of configuration.
+    #[cfg(feature = "admission-webhook")]
+    pub fn with_webhook_configurations(
+        mut self,
+        name: &str,
+        namespace: &str,
+        service: &str,
+    ) -> Self {
+        self.webhook_configurations = Some((
+            name.to_string(),
+            ServiceReference {
+                name: service.to_string(),
+                namespace: namespace.to_string(),
+                port: Some(443),
+                path: None,
+            },
+        ));
+        self
+    }
+
+    /// Record the number of requests, decisions and the latency of every
+    /// admission webhook into `metrics`, which are also included in the
+    /// [metrics](Self::metrics) of the manager.
+    #[cfg(feature = "admission-webhook")]
+    pub fn with_admission_metrics(self, metrics: crate::metrics::AdmissionMetrics) -> Self {
+        self.metrics.set_admission(metrics.clone());
+        self.admission.set_metrics(metrics);
+        self
+    }
+
+    /// Record every decision of the admission webhooks, along with the
+    /// requesting user, in `sink`. Multiple sinks can be added.
+    #[cfg(feature = "admission-webhook")]
+    pub fn with_admission_audit(self, sink: impl crate::admission::AdmissionAudit) -> Self {
+        self.admission.add_audit(std::sync::Arc::new(sink));
+        self
+    }
+
+    /// Serve the admission webhooks with `server` instead of the built-in
+    /// server, for example to embed them into an existing HTTP server. The
+    /// TLS of the controllers is not used.
+    #[cfg(feature = "admission-webhook")]
+    pub fn with_admission_server(mut self, server: impl crate::admission::AdmissionServer) -> Self {
+        self.admission_server = Some(Box::new(server));
+        self
+    }
+
+    /// Obtain a handle which pauses and resumes dispatching for every
+    /// registered controller while `start` is running.
+    pub fn pause_handle(&self) -> PauseHandle {
+        self.pause.clone()
+    }
+
+    /// Stop dispatching `Applied` events to all controllers without tearing
+    /// down watches or per-object tasks.
+    pub fn pause(&self) {
+        self.pause.pause();
+    }
+
+    /// Resume dispatching `Applied` events to all controllers.
+    pub fn resume(&self) {
+        self.pause.resume();
+    }
+
+    /// Register a controller with the manager. Admission webhooks of every
+    /// controller are served together, so controllers for different types
+    /// can each register their own. Controllers which are not enabled by the
+    /// [controller selection](Self::with_controller_selection) are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the manager unchanged, if
+    ///
+    /// * a controller with the same [name](ControllerBuilder::with_name) is
+    ///   already registered,
+    /// * another controller manages the same kind in an overlapping
+    ///   namespace,
+    /// * a namespace is not a valid namespace name, or
+    /// * an admission webhook is registered twice at the same path, or at
+    ///   paths which would give it the same name in webhook configurations,
+    ///   by the controller or across controllers.
+    pub fn register_controller<C>(&mut self, builder: ControllerBuilder<C>) -> anyhow::Result<()>
+    where
+        C: Operator,
+        C::Manifest: kube::Resource<DynamicType = ()>,
+    {
+        let name = builder.name();
+        if self.registered.contains(&name) {
+            anyhow::bail!(
+                "Controller {} is registered twice, name one of them with ControllerBuilder::with_name",
+                name
+            );
+        }
+        if !self.selection.is_enabled(&name) {
+            info!(controller = %name, "Controller is disabled, not registering it.");
+            self.registered.push(name);
+            return Ok(());
+        }
+        builder
+            .validate()
+            .with_context(|| format!("Invalid controller {}", name))?;
+        let manages = builder.manages();
+        let conflict = self.managed.iter().find(|(_, gvk, namespace)| {
+            *gvk == manages.gvk
+                && (namespace.is_none()
+                    || manages.namespace.is_none()
+                    || *namespace == manages.namespace)
+        });
+        if let Some((other, _, _)) = conflict {
+            anyhow::bail!(
+                "Controllers {} and {} both manage {} in {}",
+                other,
+                name,
+                tasks::kind_name(&manages.gvk),
+                manages.namespace.as_deref().unwrap_or("all namespaces")
+            );
+        }
+        #[cfg(feature = "admission-webhook")]
+        if let Some(ref webhooks) = self.webhooks {
+            let registered: Vec<&str> = webhooks.handler.paths().collect();
+            if let Some(path) = builder
+                .webhook_paths()
+                .find(|path| registered.contains(path))
+            {
+                anyhow::bail!(
+                    "Admission webhook path {} of controller {} is registered by another controller",
+                    path,
+                    name
+                );
+            }
+            for path in builder.webhook_paths() {
+                let stem = crate::admission::name_stem(path);
+                if let Some(other) = registered
+                    .iter()
+                    .find(|other| crate::admission::name_stem(other) == stem)
+                {
+                    anyhow::bail!(
+                        "Admission webhook path {} of controller {} would have the same name in webhook configurations as path {} of another controller",
+                        path,
+                        name,
+                        other
+                    );
+                }
+            }
+        }
+        #[cfg(feature = "admission-webhook")]
+        let builder = {
+            let mut builder = builder;
+            builder.admission = self.admission.clone();
+            builder
+        };
+        let (controller, tasks, on_start) = controller_tasks(
+            self.client.clone(),
+            builder,
+            self.store.clone(),
+            self.pause.clone(),
+            self.shutdown_rx.clone(),
+            self.metrics.clone(),
+            self.leader_rx.clone(),
+        )
+        .with_context(|| format!("Invalid controller {}", name))?;
+        #[cfg(feature = "admission-webhook")]
+        let controller = {
+            let mut controller = controller;
+            if let Some(webhooks) = controller.webhooks.take() {
+                self.add_webhooks(webhooks)?;
+            }
+            controller
+        };
+        self.registered.push(name.clone());
+        self.managed.push((name, manages.gvk, manages.namespace));
         self.controllers.push(controller);
         self.controller_tasks.extend(tasks);
+        self.startup_hooks.push(on_start);
+        Ok(())
+    }
+
+    /// Register a controller with the manager, replacing the settings of
+    /// `builder` given in `overrides`, so that the same builder can be
+    /// scoped differently per deployment.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the controller cannot be registered, as for
+    /// [register_controller](Self::register_controller).
+    pub fn register_controller_with<C>(
+        &mut self,
+        builder: ControllerBuilder<C>,
+        overrides: ControllerOverrides,
+    ) -> anyhow::Result<()>
+    where
+        C: Operator,
+        C::Manifest: kube::Resource<DynamicType = ()>,
+    {
+        self.register_controller(builder.with_overrides(overrides))
+    }
+
+    /// Serve `webhooks` alongside those already registered.
+    #[cfg(feature = "admission-webhook")]
+    fn add_webhooks(&mut self, webhooks: ControllerWebhooks) -> anyhow::Result<()> {
+        match self.webhooks {
+            Some(ref mut existing) => {
+                existing.handler.merge(webhooks.handler)?;
+                existing.tls.extend(webhooks.tls);
+                existing.registrations.extend(webhooks.registrations);
+            }
+            None => self.webhooks = Some(webhooks),
+        }
+        Ok(())
     }
 
-    /// Start the manager, blocking forever.
-    pub async fn start(self) {
+    /// Start the manager in the background, for embedding it into an
+    /// application which manages its own lifecycle. The returned handle
+    /// shuts the manager down, waits for it to be ready and joins it. See
+    /// [start](Self::start), which does the work.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn spawn(self) -> ManagerHandle {
+        // Registered before the manager starts, so that it is not ready
+        // before its watchers have started.
+        for controller in &self.controllers {
+            for handle in std::iter::once(&controller.manages)
+                .chain(&controller.owns)
+                .chain(&controller.watches)
+            {
+                self.health
+                    .watcher_started(&handle.watch.gvk, &handle.watch.namespace);
+            }
+        }
+        let shutdown = self.shutdown_handle();
+        let health = self.health();
+        let (done_tx, done) = watch::channel(false);
+        let task = tokio::spawn(async move {
+            let result = self.start().await;
+            let _ = done_tx.send(true);
+            result
+        });
+        ManagerHandle {
+            shutdown,
+            health,
+            done,
+            task,
+        }
+    }
+
+    /// Start the manager, blocking until shutdown is requested, either by
+    /// SIGTERM or SIGINT or through a [ShutdownHandle](crate::ShutdownHandle).
+    ///
+    /// On shutdown, watchers stop, each controller stops dispatching events
+    /// and waits for its running state machines to finish their current
+    /// state, and then the admission webhook server stops. `start` returns
+    /// once every controller has drained or the
+    /// [shutdown timeout](Self::with_shutdown_timeout) elapses.
+    ///
+    /// Watchers and controllers which panic or stop early are restarted
+    /// according to the [supervision policy](Self::with_supervision).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error without starting any controller if a controller's
+    /// [on_start](crate::Operator::on_start) hook fails, and after shutting
+    /// down if a task was given up on while the supervision policy aborts on
+    /// failure, or if the admission webhook server failed, for example
+    /// because it cannot bind its port.
+    pub async fn start(self) -> anyhow::Result<()> {
         use futures::FutureExt;
         use tasks::launch_watcher;
 
+        for name in self.selection.names() {
+            if !self.registered.contains(name) {
+                warn!(controller = %name, "Selected controller is not registered.");
+            }
+        }
+
         let mut tasks = self.controller_tasks;
-        let client = kube::Client::try_from(self.kubeconfig)
-            .expect("Unable to create kube::Client from kubeconfig.");
+        // Tasks which run until every controller has stopped.
+        let mut services: Vec<tasks::OperatorTask> = Vec::new();
+        // Set when the admission webhook server fails, which shuts down the
+        // manager.
+        let server_failure: Arc<std::sync::Mutex<Option<anyhow::Error>>> = Default::default();
+        let client = self.client;
+
+        for on_start in self.startup_hooks {
+            on_start(client.clone())
+                .await
+                .context("Controller startup hook failed")?;
+        }
+
+        #[cfg(feature = "admission-webhook")]
+        if let Some(webhooks) = self.webhooks {
+            if let Some((name, service)) = self.webhook_configurations {
+                let configurations = crate::admission::WebhookConfigurations {
+                    name,
+                    service,
+                    registrations: webhooks.registrations,
+                };
+                configurations
+                    .reconcile(&client)
+                    .await
+                    .context("Failed to create webhook configurations")?;
+                services.push(configurations.run(client.clone()).boxed());
+            }
+            let server = match self.admission_server {
+                Some(server) => server,
+                None => {
+                    let mut served = Vec::with_capacity(webhooks.tls.len());
+                    for tls in webhooks.tls {
+                        let loaded = (tls.source)()
+                            .await
+                            .context("Failed to get admission webhook TLS")?;
+                        served.push((loaded, tls.refresh.map(|interval| (tls.source, interval))));
+                    }
+                    crate::admission::default_server(served)
+                }
+            };
+            let handler = webhooks.handler;
+            let shutdown = ShutdownHandle::new(Arc::clone(&self.shutdown_tx));
+            let server_failure = Arc::clone(&server_failure);
+            services.push(
+                async move {
+                    if let Err(error) = server.serve(handler).await {
+                        tracing::error!(?error, "Admission webhook server failed, shutting down.");
+                        *server_failure
+                            .lock()
+                            .expect("Server failure lock poisoned.") = Some(error);
+                        shutdown.shutdown();
+                    }
+                }
+                .boxed(),
+            );
+        }
 
         // TODO: Deduplicate Watchers
+        let backoff = self.watch_backoff;
         for controller in self.controllers {
-            tasks.push(launch_watcher(client.clone(), controller.manages).boxed());
-            for handle in controller.owns {
-                tasks.push(launch_watcher(client.clone(), handle).boxed());
+            for handle in std::iter::once(controller.manages)
+                .chain(controller.owns)
+                .chain(controller.watches)
+            {
+                // Registered up front, so the manager is not ready before
+                // every watcher has listed its objects.
+                self.health
+                    .watcher_started(&handle.watch.gvk, &handle.watch.namespace);
+                let name = match handle.watch.namespace {
+                    Some(ref namespace) => format!(
+                        "watcher {} in {}",
+                        tasks::kind_name(&handle.watch.gvk),
+                        namespace
+                    ),
+                    None => format!("watcher {}", tasks::kind_name(&handle.watch.gvk)),
+                };
+                let client = client.clone();
+                let backoff = backoff.clone();
+                let shutdown = self.shutdown_rx.clone();
+                let health = self.health.clone();
+                let metrics = self.metrics.watch(&controller.name, &handle.watch.gvk);
+                tasks.push(SupervisedTask::new(name, move || {
+                    launch_watcher(
+                        client.clone(),
+                        handle.clone(),
+                        backoff.clone(),
+                        shutdown.clone(),
+                        health.clone(),
+                        metrics.clone(),
+                    )
+                    .boxed()
+                }));
+            }
+        }
+
+        let mut leader_task = None;
+        if let Some(election) = self.leader_election {
+            let (mut leading, task) = election.spawn(client.clone());
+            leader_task = Some(task);
+            let leader_tx = Arc::clone(&self.leader_tx);
+            let health = self.health.clone();
+            services.push(
+                async move {
+                    loop {
+                        let leader = *leading.borrow();
+                        health.set_leader(leader);
+                        let _ = leader_tx.send(leader);
+                        if leading.changed().await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                .boxed(),
+            );
+        }
+
+        #[cfg(feature = "health-endpoint")]
+        if let Some(address) = self.health_address {
+            services.push(log_failure("health", self.health.clone().serve(address)).boxed());
+        }
+
+        #[cfg(feature = "metrics-endpoint")]
+        if let Some(address) = self.metrics_address {
+            services.push(log_failure("metrics", self.metrics.clone().serve(address)).boxed());
+        }
+
+        #[cfg(feature = "debug-endpoint")]
+        if let Some(address) = self.debug_address {
+            services.push(log_failure("debug", self.store.clone().serve(address)).boxed());
+        }
+
+        let supervisor = Supervisor::new(
+            self.supervision,
+            self.shutdown_rx.clone(),
+            ShutdownHandle::new(Arc::clone(&self.shutdown_tx)),
+        );
+        let controllers = futures::future::join_all(
+            tasks
+                .into_iter()
+                .map(|task| supervisor.clone().supervise(task)),
+        );
+        let services = async {
+            futures::future::join_all(services).await;
+            futures::future::pending::<()>().await
+        };
+        let handle = ShutdownHandle::new(Arc::clone(&self.shutdown_tx));
+        let handle_signals = self.handle_signals;
+        let signals = async move {
+            if handle_signals {
+                termination_signal().await;
+                info!("Got termination signal, shutting down.");
+                handle.shutdown();
             }
-            for handle in controller.watches {
-                tasks.push(launch_watcher(client.clone(), handle).boxed());
+            futures::future::pending::<()>().await
+        };
+        let timeout = self.shutdown_timeout;
+        let shutdown = self.shutdown_rx.clone();
+        let deadline = async move {
+            wait_shutdown(shutdown).await;
+            match timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => futures::future::pending().await,
+            }
+        };
+        // Dropping the services once the controllers have stopped shuts
+        // down the admission webhook server.
+        tokio::select! {
+            _ = controllers => info!("All controllers stopped."),
+            _ = services => (),
+            _ = signals => (),
+            _ = deadline => warn!(?timeout, "Timed out waiting for controllers to stop."),
+        }
+        if let Some(task) = leader_task {
+            task.release().await;
+        }
+        if let Some(error) = server_failure
+            .lock()
+            .expect("Server failure lock poisoned.")
+            .take()
+        {
+            return Err(error.context("Admission webhook server failed"));
+        }
+        match supervisor.failed() {
+            Some(task) => Err(anyhow::anyhow!(
+                "Controller task {} failed permanently",
+                task
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Handle to a [Manager] running in the background, returned by
+/// [Manager::spawn].
+pub struct ManagerHandle {
+    shutdown: ShutdownHandle,
+    health: Health,
+    /// Set once the manager has returned.
+    done: watch::Receiver<bool>,
+    task: tokio::task::JoinHandle<anyhow::Result<()>>,
+}
+
+impl ManagerHandle {
+    /// Request a graceful shutdown of the manager. Await
+    /// [join](Self::join) for it to complete.
+    pub fn shutdown(&self) {
+        self.shutdown.shutdown();
+    }
+
+    /// Obtain a handle to the liveness and readiness of the manager.
+    pub fn health(&self) -> Health {
+        self.health.clone()
+    }
+
+    /// Wait until every watcher of the manager has listed its objects and is
+    /// connected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manager stops before it is ready, for example
+    /// because a controller's startup hook failed.
+    pub async fn wait_ready(&self) -> anyhow::Result<()> {
+        let mut done = self.done.clone();
+        // The sender is dropped without being set if the manager panicked.
+        let stopped = async move {
+            while !*done.borrow() {
+                if done.changed().await.is_err() {
+                    break;
+                }
             }
+        };
+        tokio::select! {
+            _ = self.health.wait_ready() => Ok(()),
+            _ = stopped => Err(anyhow::anyhow!("Manager stopped before it was ready")),
         }
+    }
+
+    /// Wait for the manager to stop, returning the result of
+    /// [start](Manager::start).
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the manager, or an error if it panicked.
+    pub async fn join(self) -> anyhow::Result<()> {
+        self.task.await.context("Manager panicked")?
+    }
+}
+
+/// Log the error if the `name` endpoint fails, without affecting the
+/// controllers.
+#[cfg(any(
+    feature = "health-endpoint",
+    feature = "metrics-endpoint",
+    feature = "debug-endpoint"
+))]
+async fn log_failure(name: &str, serve: impl std::future::Future<Output = anyhow::Result<()>>) {
+    if let Err(error) = serve.await {
+        tracing::error!(endpoint = name, ?error, "Endpoint failed.");
+    }
+}
 
-        futures::future::join_all(tasks).await;
+/// Resolves once the process receives SIGTERM or SIGINT.
+async fn termination_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = terminate.recv() => (),
+                    _ = tokio::signal::ctrl_c() => (),
+                }
+                return;
+            }
+            Err(error) => warn!(?error, "Failed to listen for SIGTERM."),
+        }
+    }
+    if let Err(error) = tokio::signal::ctrl_c().await {
+        warn!(?error, "Failed to listen for SIGINT.");
+        futures::future::pending::<()>().await;
     }
 }
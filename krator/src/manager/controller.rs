@@ -1,20 +1,46 @@
 use super::watch::{Watch, WatchHandle};
 #[cfg(feature = "admission-webhook")]
-use crate::admission::WebhookFn;
+use crate::admission::{
+    webhook_fn, AdmissionHandler, AdmissionObserver, AdmissionVerdict, FailurePolicy, Operation,
+    SideEffects, TlsSource, Webhook, WebhookFn, WebhookKind, WebhookRegistration,
+};
+use crate::background::{BackgroundTask, TaskContext};
+use crate::graph::{Graph, Transitions};
+use crate::object::ObjectKey;
 use crate::operator::Watchable;
+use crate::state::StateMiddleware;
+use crate::store::{index_fn, IndexFn};
 use crate::Operator;
-use kube::api::ListParams;
+#[cfg(feature = "admission-webhook")]
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::LabelSelector;
+use kube::api::{DynamicObject, GroupVersionKind, ListParams};
+#[cfg(feature = "admission-webhook")]
+use std::future::Future;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Maps a changed object to the keys of the managed objects to notify.
+pub(crate) type MapFn = Arc<dyn Fn(&DynamicObject) -> Vec<ObjectKey> + Send + Sync>;
 
 /// Builder pattern for registering a controller or operator.
 pub struct ControllerBuilder<C: Operator> {
     /// The controller or operator singleton.
     pub(crate) controller: C,
+    /// Name of the controller in metrics.
+    name: Option<String>,
+    /// Whether the controller only runs while the Manager is leading.
+    pub(crate) follows_leader: bool,
     ///  List of watch configurations for objects that will simply be cached
     ///  locally.
     pub(crate) watches: Vec<Watch>,
     /// List of watch configurations for objects that will trigger
     /// notifications (based on OwnerReferences).
     pub(crate) owns: Vec<Watch>,
+    /// List of watch configurations for objects that will trigger
+    /// notifications for the objects they are mapped to.
+    pub(crate) mapped: Vec<(Watch, MapFn)>,
+    /// Indexes of watched kinds, added to the Store before they are cached.
+    pub(crate) indexes: Vec<(GroupVersionKind, String, IndexFn)>,
     /// Restrict our controller to act on a specific namespace.
     namespace: Option<String>,
     /// Restrict our controller to act on objects that match specific list
@@ -23,6 +49,33 @@ pub struct ControllerBuilder<C: Operator> {
     /// The buffer length for Tokio channels used to communicate between
     /// watcher tasks and runtime tasks.
     buffer: usize,
+    /// Tasks supervised alongside the controller's state machines.
+    pub(crate) background_tasks: Vec<BackgroundTask<C>>,
+    /// Run around every state executed by the controller.
+    pub(crate) middleware: Vec<Arc<dyn StateMiddleware<C::ObjectState>>>,
+    /// State graph checked before the Manager starts.
+    pub(crate) graph: Option<Graph>,
+    /// Admission webhooks served by the Manager.
+    #[cfg(feature = "admission-webhook")]
+    pub(crate) webhooks: Vec<Webhook<C>>,
+    /// Path [admission_hook](crate::Operator::admission_hook) is served at.
+    #[cfg(feature = "admission-webhook")]
+    pub(crate) admission_hook: Option<String>,
+    /// Failure policy of the controller's webhooks in the configurations
+    /// created by the Manager.
+    #[cfg(feature = "admission-webhook")]
+    pub(crate) webhook_failure_policy: FailurePolicy,
+    /// Namespace selector of the controller's webhooks in the configurations
+    /// created by the Manager.
+    #[cfg(feature = "admission-webhook")]
+    pub(crate) webhook_namespace_selector: Option<LabelSelector>,
+    /// Operations sent to the controller's webhooks by the configurations
+    /// created by the Manager.
+    #[cfg(feature = "admission-webhook")]
+    pub(crate) webhook_operations: Vec<Operation>,
+    /// Observers of the controller's webhooks, shared with the Manager.
+    #[cfg(feature = "admission-webhook")]
+    pub(crate) admission: AdmissionObserver,
 }
 
 impl<O: Operator> ControllerBuilder<O> {
@@ -30,11 +83,30 @@ impl<O: Operator> ControllerBuilder<O> {
     pub fn new(operator: O) -> Self {
         ControllerBuilder {
             controller: operator,
+            name: None,
+            follows_leader: true,
             watches: vec![],
             owns: vec![],
+            mapped: vec![],
+            indexes: vec![],
             namespace: None,
             list_params: Default::default(),
             buffer: 32,
+            background_tasks: vec![],
+            middleware: vec![],
+            graph: None,
+            #[cfg(feature = "admission-webhook")]
+            webhooks: vec![],
+            #[cfg(feature = "admission-webhook")]
+            admission_hook: None,
+            #[cfg(feature = "admission-webhook")]
+            webhook_failure_policy: Default::default(),
+            #[cfg(feature = "admission-webhook")]
+            webhook_namespace_selector: None,
+            #[cfg(feature = "admission-webhook")]
+            webhook_operations: vec![Operation::Create, Operation::Update],
+            #[cfg(feature = "admission-webhook")]
+            admission: Default::default(),
         }
     }
 
@@ -48,8 +120,86 @@ impl<O: Operator> ControllerBuilder<O> {
         self.buffer
     }
 
+    /// Name the controller in the metrics of the Manager and in its
+    /// [ControllerSelection](crate::ControllerSelection). Defaults to the
+    /// lowercased kind of the managed resource.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Run the controller on every replica, even if the Manager uses
+    /// [leader election](crate::Manager::with_leader_election), for example
+    /// for a controller which only exports metrics.
+    pub fn without_leader_election(mut self) -> Self {
+        self.follows_leader = false;
+        self
+    }
+
+    pub(crate) fn name(&self) -> String
+    where
+        O::Manifest: kube::Resource<DynamicType = ()>,
+    {
+        self.name
+            .clone()
+            .unwrap_or_else(|| <O::Manifest as kube::Resource>::kind(&()).to_lowercase())
+    }
+
+    /// Run `f` alongside the controller's state machines, restarting it with
+    /// exponential backoff if it fails, panics or returns early.
+    pub fn with_background_task<F, Fut>(mut self, name: &str, f: F) -> Self
+    where
+        F: Fn(TaskContext<O>) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.background_tasks
+            .push(BackgroundTask::new(name, None, f));
+        self
+    }
+
+    /// Run `f` every `period` alongside the controller's state machines.
+    pub fn with_periodic_task<F, Fut>(
+        mut self,
+        name: &str,
+        period: std::time::Duration,
+        f: F,
+    ) -> Self
+    where
+        F: Fn(TaskContext<O>) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.background_tasks
+            .push(BackgroundTask::new(name, Some(period), f));
+        self
+    }
+
+    /// Run `middleware` before and after every state executed by the
+    /// controller.
+    pub fn with_state_middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: StateMiddleware<O::ObjectState>,
+    {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Check the controller's state graph with
+    /// [Graph::validate](crate::graph::Graph::validate) before the Manager
+    /// starts.
+    pub fn with_graph_validation(mut self) -> Self
+    where
+        O::InitialState: Transitions,
+        O::DeletedState: Transitions,
+    {
+        self.graph = Some(Graph::of::<O>());
+        self
+    }
+
     /// Create watcher definition for the configured managed resource.
-    pub(crate) fn manages(&self) -> Watch {
+    pub(crate) fn manages(&self) -> Watch
+    where
+        O::Manifest: kube::Resource<DynamicType = ()>,
+    {
         Watch::new::<O::Manifest>(self.namespace.clone(), self.list_params.clone())
     }
 
@@ -66,6 +216,20 @@ impl<O: Operator> ControllerBuilder<O> {
         self
     }
 
+    /// Replace the settings given in `overrides`.
+    pub(crate) fn with_overrides(mut self, overrides: ControllerOverrides) -> Self {
+        if let Some(namespace) = overrides.namespace {
+            self.namespace = namespace;
+        }
+        if let Some(list_params) = overrides.list_params {
+            self.list_params = list_params;
+        }
+        if let Some(buffer) = overrides.buffer {
+            self.buffer = buffer;
+        }
+        self
+    }
+
     /// Watch all objects of given kind R. Cluster scoped and no list param
     /// restrictions.
     pub fn watches<R>(mut self) -> Self
@@ -76,6 +240,21 @@ impl<O: Operator> ControllerBuilder<O> {
         self
     }
 
+    /// Watch all objects of given kind R, caching only their metadata. Cluster
+    /// scoped and no list param restrictions.
+    ///
+    /// Only the metadata of the objects is requested from the API server,
+    /// as `PartialObjectMetadata`, and kept in the [Store](crate::Store), so
+    /// they cannot be read back as `R`.
+    pub fn watches_metadata_only<R>(mut self) -> Self
+    where
+        R: Watchable,
+    {
+        self.watches
+            .push(Watch::new::<R>(None, Default::default()).metadata_only());
+        self
+    }
+
     /// Watch objects of given kind R. Cluster scoped, but limited to objects
     /// matching supplied list params.
     pub fn watches_with_params<R>(mut self, list_params: ListParams) -> Self
@@ -114,8 +293,61 @@ impl<O: Operator> ControllerBuilder<O> {
         self
     }
 
+    /// Watch all objects of kind R, and re-reconcile the managed objects
+    /// `map_fn` returns for an object whenever it changes. Cluster scoped
+    /// and no list param restrictions.
+    ///
+    /// This covers relationships which OwnerReferences cannot express, such
+    /// as references across namespaces or in annotations. Like for
+    /// [owns](Self::owns), the latest manifest of each returned object is
+    /// delivered again and
+    /// [Manifest::dependent_changed](crate::Manifest::dependent_changed)
+    /// resolves. Keys of objects which are not managed are ignored.
+    pub fn watches_mapped<R, F>(mut self, map_fn: F) -> Self
+    where
+        R: Watchable,
+        F: Fn(&R) -> Vec<ObjectKey> + Send + Sync + 'static,
+    {
+        let map: MapFn =
+            Arc::new(
+                move |object| match crate::util::concrete_object::<R>(object.clone()) {
+                    Ok(object) => map_fn(&object),
+                    Err(error) => {
+                        warn!(?error, "Unable to deserialize object to map.");
+                        vec![]
+                    }
+                },
+            );
+        self.mapped
+            .push((Watch::new::<R>(None, Default::default()), map));
+        self
+    }
+
+    /// Index the cached objects of kind R, which the controller
+    /// [watches](Self::watches) or [owns](Self::owns), by the values `f`
+    /// returns for them, so that they can be looked up with
+    /// [Store::by_index](crate::Store::by_index) under `name`.
+    ///
+    /// Registering the controller fails if it does not watch objects of
+    /// kind R.
+    pub fn with_index<R, F>(mut self, name: &str, f: F) -> Self
+    where
+        R: Watchable,
+        F: Fn(&R) -> Vec<String> + Send + Sync + 'static,
+    {
+        let gvk = Watch::new::<R>(None, Default::default()).gvk;
+        self.indexes
+            .push((gvk, name.to_string(), index_fn::<R, F>(f)));
+        self
+    }
+
     /// Watch and subscribe to notifications based on OwnerReferences all
     /// objects of kind R. Cluster scoped and no list param restrictions.
+    ///
+    /// Whenever an owned object changes, its owner's latest manifest is
+    /// delivered again and
+    /// [Manifest::dependent_changed](crate::Manifest::dependent_changed)
+    /// resolves.
     pub fn owns<R>(mut self) -> Self
     where
         R: Watchable,
@@ -124,6 +356,19 @@ impl<O: Operator> ControllerBuilder<O> {
         self
     }
 
+    /// Watch and subscribe to notifications based on OwnerReferences all
+    /// objects of kind R, caching only their metadata. Cluster scoped and no
+    /// list param restrictions. See
+    /// [watches_metadata_only](Self::watches_metadata_only).
+    pub fn owns_metadata_only<R>(mut self) -> Self
+    where
+        R: Watchable,
+    {
+        self.owns
+            .push(Watch::new::<R>(None, Default::default()).metadata_only());
+        self
+    }
+
     /// Watch and subscribe to notifications based on OwnerReferences
     /// objects of kind R. Cluster scoped, but limited to objects matching
     /// supplied list params.
@@ -166,35 +411,339 @@ impl<O: Operator> ControllerBuilder<O> {
     }
 
     /// Registers a validating webhook at the path "/$GROUP/$VERSION/$KIND".
-    /// Multiple webhooks can be registered, but must be at different paths.
+    /// Multiple webhooks can be registered, but must be at different paths,
+    /// also across the controllers of a Manager.
+    /// Changes the webhook makes to the object are ignored.
+    ///
+    /// ```no_run
+    /// # use krator::admission::AdmissionResult;
+    /// # fn example<O: krator::Operator>(builder: krator::ControllerBuilder<O>) {
+    /// let builder = builder
+    ///     .validates_at_path("/validate/name", |manifest| async move {
+    ///         AdmissionResult::Allow(manifest)
+    ///     })
+    ///     .validates_at_path("/validate/labels", |manifest| async move {
+    ///         AdmissionResult::Allow(manifest)
+    ///     });
+    /// # }
+    /// ```
     #[cfg(feature = "admission-webhook")]
-    pub(crate) fn validates(self, _f: &WebhookFn<O>) -> Self {
-        todo!()
+    pub fn validates<F, Fut>(self, f: F) -> Self
+    where
+        F: Fn(O::Manifest) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: Into<AdmissionVerdict<O::Manifest>>,
+        O::Manifest: kube::Resource<DynamicType = ()>,
+    {
+        let path = default_webhook_path::<O::Manifest>();
+        self.validates_at_path(&path, f)
     }
 
     /// Registers a validating webhook at the supplied path.
     #[cfg(feature = "admission-webhook")]
-    pub(crate) fn validates_at_path(self, _path: &str, _f: &WebhookFn<O>) -> Self {
-        todo!()
+    pub fn validates_at_path<F, Fut>(self, path: &str, f: F) -> Self
+    where
+        F: Fn(O::Manifest) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: Into<AdmissionVerdict<O::Manifest>>,
+    {
+        self.with_webhook(
+            path,
+            WebhookKind::Validating,
+            SideEffects::None,
+            webhook_fn::<O, _, _>(f),
+        )
     }
 
     /// Registers a mutating webhook at the path "/$GROUP/$VERSION/$KIND".
-    /// Multiple webhooks can be registered, but must be at different paths.
+    /// Multiple webhooks can be registered, but must be at different paths,
+    /// also across the controllers of a Manager.
+    /// Changes the webhook makes to the object are sent back as a JSON Patch.
     #[cfg(feature = "admission-webhook")]
-    pub(crate) fn mutates(self, _f: &WebhookFn<O>) -> Self {
-        todo!()
+    pub fn mutates<F, Fut>(self, f: F) -> Self
+    where
+        F: Fn(O::Manifest) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: Into<AdmissionVerdict<O::Manifest>>,
+        O::Manifest: kube::Resource<DynamicType = ()>,
+    {
+        let path = default_webhook_path::<O::Manifest>();
+        self.mutates_at_path(&path, f)
     }
 
     /// Registers a mutating webhook at the supplied path.
     #[cfg(feature = "admission-webhook")]
-    pub(crate) fn mutates_at_path(self, _path: &str, _f: &WebhookFn<O>) -> Self {
-        todo!()
+    pub fn mutates_at_path<F, Fut>(self, path: &str, f: F) -> Self
+    where
+        F: Fn(O::Manifest) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: Into<AdmissionVerdict<O::Manifest>>,
+    {
+        self.with_webhook(
+            path,
+            WebhookKind::Mutating,
+            SideEffects::None,
+            webhook_fn::<O, _, _>(f),
+        )
+    }
+
+    /// Registers an already boxed webhook at the supplied path, for example
+    /// one shared between controllers. Registering the controller fails if
+    /// another webhook is registered at `path`.
+    ///
+    /// `side_effects` is declared in the configurations created with
+    /// [Manager::with_webhook_configurations](crate::Manager::with_webhook_configurations).
+    /// Only webhooks declaring [SideEffects::NoneOnDryRun] are given a client
+    /// through [AdmissionContext](crate::admission::AdmissionContext), and
+    /// only for requests which are not a dry run.
+    #[cfg(feature = "admission-webhook")]
+    pub fn with_webhook(
+        mut self,
+        path: &str,
+        kind: WebhookKind,
+        side_effects: SideEffects,
+        f: Arc<WebhookFn<O>>,
+    ) -> Self {
+        self.webhooks.push(Webhook {
+            path: path.to_string(),
+            kind,
+            side_effects,
+            f,
+        });
+        self
+    }
+
+    /// Serves the operator's [admission_hook](crate::Operator::admission_hook)
+    /// as a mutating webhook at the path "/$GROUP/$VERSION/$KIND", as
+    /// [OperatorRuntime](crate::OperatorRuntime) does at any path.
+    /// Registering the controller fails if another webhook is registered at
+    /// the path.
+    #[cfg(feature = "admission-webhook")]
+    pub fn with_admission_hook(self) -> Self
+    where
+        O::Manifest: kube::Resource<DynamicType = ()>,
+    {
+        let path = default_webhook_path::<O::Manifest>();
+        self.with_admission_hook_at_path(&path)
+    }
+
+    /// Serves the operator's [admission_hook](crate::Operator::admission_hook)
+    /// as a mutating webhook at the supplied path.
+    /// Registering the controller fails if another webhook is registered at
+    /// `path`.
+    #[cfg(feature = "admission-webhook")]
+    pub fn with_admission_hook_at_path(mut self, path: &str) -> Self {
+        self.admission_hook = Some(path.to_string());
+        self
+    }
+
+    /// Paths of the controller's admission webhooks.
+    #[cfg(feature = "admission-webhook")]
+    pub(crate) fn webhook_paths(&self) -> impl Iterator<Item = &str> {
+        self.webhooks
+            .iter()
+            .map(|webhook| webhook.path.as_str())
+            .chain(self.admission_hook.as_deref())
+    }
+
+    /// Check for mistakes in the configuration, which would otherwise only
+    /// show once the controller runs.
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        let namespaces = self.namespace.iter().chain(
+            self.watches
+                .iter()
+                .chain(self.owns.iter())
+                .chain(self.mapped.iter().map(|(watch, _)| watch))
+                .filter_map(|watch| watch.namespace.as_ref()),
+        );
+        for namespace in namespaces {
+            validate_namespace(namespace)?;
+        }
+        for (gvk, name, _) in &self.indexes {
+            let watched = self
+                .watches
+                .iter()
+                .chain(self.owns.iter())
+                .chain(self.mapped.iter().map(|(watch, _)| watch))
+                .any(|watch| &watch.gvk == gvk);
+            if !watched {
+                anyhow::bail!(
+                    "Index {} is defined for {}/{} {}, which is not watched",
+                    name,
+                    gvk.group,
+                    gvk.version,
+                    gvk.kind
+                );
+            }
+        }
+        #[cfg(feature = "admission-webhook")]
+        {
+            let mut paths = std::collections::HashSet::new();
+            let mut stems = std::collections::HashMap::new();
+            for path in self.webhook_paths() {
+                if !path.starts_with('/') {
+                    anyhow::bail!("Admission webhook path {} does not start with /", path);
+                }
+                if !paths.insert(path) {
+                    anyhow::bail!("Admission webhook path {} is registered twice", path);
+                }
+                if let Some(other) = stems.insert(crate::admission::name_stem(path), path) {
+                    anyhow::bail!(
+                        "Admission webhook paths {} and {} would have the same name in webhook configurations",
+                        other,
+                        path
+                    );
+                }
+            }
+            if self.webhook_operations.is_empty() {
+                anyhow::bail!("Admission webhooks are registered for no operations");
+            }
+        }
+        Ok(())
+    }
+
+    /// Set what the API server does when the controller's webhooks cannot be
+    /// called, in the configurations created with
+    /// [Manager::with_webhook_configurations](crate::Manager::with_webhook_configurations).
+    /// Defaults to [FailurePolicy::Fail].
+    #[cfg(feature = "admission-webhook")]
+    pub fn with_webhook_failure_policy(mut self, failure_policy: FailurePolicy) -> Self {
+        self.webhook_failure_policy = failure_policy;
+        self
+    }
+
+    /// Only send the controller's webhooks objects in namespaces matching
+    /// `selector`, in the configurations created with
+    /// [Manager::with_webhook_configurations](crate::Manager::with_webhook_configurations).
+    #[cfg(feature = "admission-webhook")]
+    pub fn with_webhook_namespace_selector(mut self, selector: LabelSelector) -> Self {
+        self.webhook_namespace_selector = Some(selector);
+        self
+    }
+
+    /// Send the controller's webhooks requests for `operations`, in the
+    /// configurations created with
+    /// [Manager::with_webhook_configurations](crate::Manager::with_webhook_configurations).
+    /// Webhooks get the existing object for [Operation::Delete]. Defaults to
+    /// [Operation::Create] and [Operation::Update].
+    #[cfg(feature = "admission-webhook")]
+    pub fn with_webhook_operations(mut self, operations: &[Operation]) -> Self {
+        self.webhook_operations = operations.to_vec();
+        self
+    }
+}
+
+/// Settings of a [ControllerBuilder] decided when it is registered with
+/// [Manager::register_controller_with](crate::Manager::register_controller_with),
+/// for example from deployment configuration. Settings which are not
+/// overridden keep the value of the builder.
+///
+/// ```no_run
+/// # use krator::ControllerOverrides;
+/// let namespace = std::env::var("WATCH_NAMESPACE").ok();
+/// let overrides = match namespace {
+///     Some(namespace) => ControllerOverrides::new().namespaced(&namespace),
+///     None => ControllerOverrides::new().cluster_wide(),
+/// };
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ControllerOverrides {
+    namespace: Option<Option<String>>,
+    list_params: Option<ListParams>,
+    buffer: Option<usize>,
+}
+
+impl ControllerOverrides {
+    /// Create overrides which leave every setting of the builder as is.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Only manage objects in `namespace`. Watched and owned objects keep
+    /// the namespace they were registered with.
+    pub fn namespaced(mut self, namespace: &str) -> Self {
+        self.namespace = Some(Some(namespace.to_string()));
+        self
+    }
+
+    /// Manage objects in all namespaces, even if the builder is
+    /// [namespaced](ControllerBuilder::namespaced).
+    pub fn cluster_wide(mut self) -> Self {
+        self.namespace = Some(None);
+        self
+    }
+
+    /// Only manage objects matching `list_params`, replacing those of
+    /// [with_params](ControllerBuilder::with_params).
+    pub fn with_params(mut self, list_params: ListParams) -> Self {
+        self.list_params = Some(list_params);
+        self
+    }
+
+    /// Change the length of buffer used for internal communication channels.
+    pub fn with_buffer(mut self, buffer: usize) -> Self {
+        self.buffer = Some(buffer);
+        self
+    }
+}
+
+/// The path "/$GROUP/$VERSION/$KIND", leaving out the group of core
+/// resources.
+#[cfg(feature = "admission-webhook")]
+fn default_webhook_path<R: kube::Resource<DynamicType = ()>>() -> String {
+    let group = R::group(&());
+    let mut path = String::new();
+    if !group.is_empty() {
+        path.push('/');
+        path.push_str(&group);
+    }
+    path.push_str(&format!("/{}/{}", R::version(&()), R::kind(&())));
+    path
+}
+
+/// Check that `namespace` is a valid namespace name, an RFC 1123 label.
+fn validate_namespace(namespace: &str) -> anyhow::Result<()> {
+    let valid = !namespace.is_empty()
+        && namespace.len() <= 63
+        && namespace
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !namespace.starts_with('-')
+        && !namespace.ends_with('-');
+    if valid {
+        Ok(())
+    } else {
+        anyhow::bail!(
+            "Invalid namespace {:?}: must consist of at most 63 lowercase alphanumeric characters or '-', and start and end with an alphanumeric character",
+            namespace
+        )
     }
 }
 
-#[derive(Clone)]
 pub struct Controller {
+    /// Name of the controller in metrics.
+    pub(crate) name: String,
     pub manages: WatchHandle,
     pub owns: Vec<WatchHandle>,
     pub watches: Vec<WatchHandle>,
+    #[cfg(feature = "admission-webhook")]
+    pub webhooks: Option<ControllerWebhooks>,
+}
+
+/// Handler of the admission webhooks of one or more controllers, along with
+/// the TLS configuration of each controller to serve them with.
+#[cfg(feature = "admission-webhook")]
+pub struct ControllerWebhooks {
+    pub handler: AdmissionHandler,
+    pub tls: Vec<WebhookTls>,
+    /// The webhooks, for registration with the API server.
+    pub(crate) registrations: Vec<WebhookRegistration>,
+}
+
+/// TLS of a controller's admission webhooks.
+#[cfg(feature = "admission-webhook")]
+pub struct WebhookTls {
+    pub source: TlsSource,
+    /// How often `source` is loaded again.
+    pub refresh: Option<std::time::Duration>,
 }
@@ -0,0 +1,64 @@
+//! Watching only the metadata of objects.
+
+use std::task::{Context, Poll};
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use http::header::{HeaderValue, ACCEPT};
+use http::{Request, Response};
+use hyper::Body;
+use kube::api::DynamicObject;
+use kube::Client;
+
+/// Asks the API server for `PartialObjectMetadata` items of a list.
+const LIST: &str = "application/json;as=PartialObjectMetadataList;g=meta.k8s.io;v=v1";
+/// Asks the API server for `PartialObjectMetadata` objects in watch events.
+const WATCH: &str = "application/json;as=PartialObjectMetadata;g=meta.k8s.io;v=v1";
+
+/// Wrap `client` so that lists and watches only transfer the metadata of
+/// objects. The objects received through it have the type
+/// `meta.k8s.io/v1 PartialObjectMetadata` and no other fields; see
+/// [restore_types].
+pub(crate) fn metadata_client(client: Client) -> Client {
+    // Only used with `Api::all_with` and `Api::namespaced_with`.
+    Client::new(MetadataOnly { inner: client }, "default")
+}
+
+/// Set the type of an object received through a [metadata_client] back to
+/// the watched kind.
+pub(crate) fn restore_types(
+    mut object: DynamicObject,
+    types: &kube::api::TypeMeta,
+) -> DynamicObject {
+    object.types = Some(types.clone());
+    object
+}
+
+#[derive(Clone)]
+struct MetadataOnly {
+    inner: Client,
+}
+
+impl tower::Service<Request<Body>> for MetadataOnly {
+    type Response = Response<Body>;
+    type Error = kube::Error;
+    type Future = BoxFuture<'static, Result<Response<Body>, kube::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        let watch = request
+            .uri()
+            .query()
+            .map(|query| query.split('&').any(|pair| pair == "watch=true"))
+            .unwrap_or(false);
+        let accept = if watch { WATCH } else { LIST };
+        request
+            .headers_mut()
+            .insert(ACCEPT, HeaderValue::from_static(accept));
+        let inner = self.inner.clone();
+        async move { inner.send(request).await }.boxed()
+    }
+}
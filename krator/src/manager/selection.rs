@@ -0,0 +1,162 @@
+//! Choosing which registered controllers a Manager runs.
+
+use std::collections::BTreeSet;
+use std::convert::Infallible;
+use std::str::FromStr;
+
+/// Which controllers a [Manager](crate::Manager) runs, by the name given
+/// with [ControllerBuilder::with_name](crate::ControllerBuilder::with_name),
+/// so that one binary can ship many controllers while each deployment only
+/// enables some of them.
+///
+/// Parsed from a comma-separated list, like the `--controllers` flag of
+/// `kube-controller-manager`: `foo` enables the controller `foo`, `-foo`
+/// disables it and `*` enables every controller which is not disabled. A list
+/// without `foo` entries enables every controller which is not disabled, as
+/// does an empty list.
+///
+/// ```
+/// use krator::ControllerSelection;
+/// let selection: ControllerSelection = "moose,-elk".parse().unwrap();
+/// assert!(selection.is_enabled("moose"));
+/// assert!(!selection.is_enabled("elk"));
+/// assert!(!selection.is_enabled("deer"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ControllerSelection {
+    enabled: BTreeSet<String>,
+    disabled: BTreeSet<String>,
+    all: bool,
+}
+
+impl ControllerSelection {
+    /// Enable every controller.
+    pub fn all() -> Self {
+        Default::default()
+    }
+
+    /// Parse the selection from the environment variable `name`, enabling
+    /// every controller if it is not set.
+    pub fn from_env(name: &str) -> Self {
+        std::env::var(name)
+            .map(|value| value.parse().unwrap_or_default())
+            .unwrap_or_default()
+    }
+
+    /// Enable the controller `name`, and no longer every controller unless
+    /// `*` was given.
+    pub fn enable(mut self, name: &str) -> Self {
+        self.disabled.remove(name);
+        self.enabled.insert(name.to_string());
+        self
+    }
+
+    /// Disable the controller `name`.
+    pub fn disable(mut self, name: &str) -> Self {
+        self.enabled.remove(name);
+        self.disabled.insert(name.to_string());
+        self
+    }
+
+    /// Whether the controller `name` runs.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        if self.disabled.contains(name) {
+            false
+        } else {
+            self.all || self.enabled.is_empty() || self.enabled.contains(name)
+        }
+    }
+
+    /// Names which were enabled or disabled explicitly.
+    pub(crate) fn names(&self) -> impl Iterator<Item = &String> {
+        self.enabled.iter().chain(self.disabled.iter())
+    }
+}
+
+impl FromStr for ControllerSelection {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut selection = ControllerSelection::default();
+        for item in s.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            selection = match item {
+                "*" => ControllerSelection {
+                    all: true,
+                    ..selection
+                },
+                _ => match item.strip_prefix('-') {
+                    Some(name) => selection.disable(name.trim()),
+                    None => selection.enable(item),
+                },
+            };
+        }
+        Ok(selection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> ControllerSelection {
+        s.parse().unwrap()
+    }
+
+    fn names(selection: &ControllerSelection) -> Vec<&str> {
+        selection.names().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn empty_enables_everything() {
+        for s in ["", " ", ",", " , ,"] {
+            let selection = parse(s);
+            assert!(selection.is_enabled("moose"), "{:?}", s);
+            assert!(selection.is_enabled("elk"), "{:?}", s);
+            assert!(names(&selection).is_empty(), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn include_list_enables_only_listed() {
+        let selection = parse("moose, elk");
+        assert!(selection.is_enabled("moose"));
+        assert!(selection.is_enabled("elk"));
+        assert!(!selection.is_enabled("deer"));
+    }
+
+    #[test]
+    fn exclude_list_enables_everything_else() {
+        let selection = parse("-moose,- elk");
+        assert!(!selection.is_enabled("moose"));
+        assert!(!selection.is_enabled("elk"));
+        assert!(selection.is_enabled("deer"));
+    }
+
+    #[test]
+    fn star_with_excludes() {
+        let selection = parse("*,moose,-elk");
+        assert!(selection.is_enabled("moose"));
+        assert!(!selection.is_enabled("elk"));
+        assert!(selection.is_enabled("deer"));
+    }
+
+    #[test]
+    fn later_entries_win() {
+        let selection = parse("moose,-moose,elk,-deer,deer");
+        assert!(!selection.is_enabled("moose"));
+        assert!(selection.is_enabled("elk"));
+        assert!(selection.is_enabled("deer"));
+        assert!(!selection.is_enabled("bison"));
+    }
+
+    // Names are not known while parsing, so unknown names are kept and
+    // reported by the Manager once every controller is registered.
+    #[test]
+    fn unknown_names_are_kept() {
+        let selection = parse("unknown,-missing");
+        assert!(!selection.is_enabled("moose"));
+        assert!(selection.is_enabled("unknown"));
+        assert!(!selection.is_enabled("missing"));
+        assert_eq!(names(&selection), vec!["unknown", "missing"]);
+    }
+}
@@ -0,0 +1,156 @@
+//! Restarting controller tasks which stop unexpectedly.
+
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+use super::tasks::OperatorTask;
+use crate::runtime::{wait_shutdown, ShutdownHandle};
+use crate::util::Backoff;
+
+/// How a [Manager](crate::Manager) handles a controller task, such as a
+/// watcher or a controller's [OperatorRuntime](crate::OperatorRuntime),
+/// which panics or stops before shutdown is requested.
+///
+/// By default, such tasks are restarted indefinitely, waiting according to
+/// the default [Backoff] between restarts.
+#[derive(Clone, Debug, Default)]
+pub struct SupervisionPolicy {
+    backoff: Backoff,
+    max_restarts: Option<u32>,
+    abort_on_failure: bool,
+}
+
+impl SupervisionPolicy {
+    /// Restart failed tasks indefinitely with the default backoff.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Change the backoff applied between restarts of a task.
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Give up on a task once it failed again after `max` consecutive
+    /// restarts. A task which ran for longer than the backoff's
+    /// [max](Backoff::max) before failing is no longer counted as failing
+    /// consecutively.
+    pub fn with_max_restarts(mut self, max: u32) -> Self {
+        self.max_restarts = Some(max);
+        self
+    }
+
+    /// Shut down the whole Manager once a task is given up on, making
+    /// [start](crate::Manager::start) return an error, instead of running
+    /// the remaining controllers degraded.
+    pub fn with_abort_on_failure(mut self) -> Self {
+        self.abort_on_failure = true;
+        self
+    }
+}
+
+/// A controller task which can be started again after it stopped.
+pub(crate) struct SupervisedTask {
+    /// Describes the task in logs and errors.
+    name: String,
+    /// Creates a fresh run of the task.
+    factory: Box<dyn FnMut() -> OperatorTask + Send>,
+}
+
+impl SupervisedTask {
+    pub(crate) fn new<F>(name: String, factory: F) -> Self
+    where
+        F: FnMut() -> OperatorTask + Send + 'static,
+    {
+        SupervisedTask {
+            name,
+            factory: Box::new(factory),
+        }
+    }
+}
+
+/// Aborts a spawned run when the supervisor is dropped, for example when
+/// shutdown times out.
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Runs the tasks of a Manager according to a [SupervisionPolicy].
+#[derive(Clone)]
+pub(crate) struct Supervisor {
+    policy: SupervisionPolicy,
+    shutdown: watch::Receiver<bool>,
+    handle: ShutdownHandle,
+    /// Name of the first task given up on while aborting on failure.
+    failed: Arc<Mutex<Option<String>>>,
+}
+
+impl Supervisor {
+    pub(crate) fn new(
+        policy: SupervisionPolicy,
+        shutdown: watch::Receiver<bool>,
+        handle: ShutdownHandle,
+    ) -> Self {
+        Supervisor {
+            policy,
+            shutdown,
+            handle,
+            failed: Default::default(),
+        }
+    }
+
+    /// The task which made the Manager shut down, if any.
+    pub(crate) fn failed(&self) -> Option<String> {
+        self.failed
+            .lock()
+            .expect("Supervisor lock poisoned.")
+            .clone()
+    }
+
+    /// Run `task` until shutdown is requested, restarting it whenever it
+    /// panics or returns before that, unless it is given up on.
+    pub(crate) async fn supervise(self, mut task: SupervisedTask) {
+        let mut restarts: u32 = 0;
+        loop {
+            let started = Instant::now();
+            let mut run = AbortOnDrop(tokio::spawn((task.factory)()));
+            let outcome = (&mut run.0).await;
+            if *self.shutdown.borrow() {
+                break;
+            }
+            match outcome {
+                Ok(()) => warn!(task = %task.name, "Controller task stopped unexpectedly."),
+                Err(error) => error!(task = %task.name, %error, "Controller task panicked."),
+            }
+            if started.elapsed() > self.policy.backoff.max {
+                restarts = 0;
+            }
+            if matches!(self.policy.max_restarts, Some(max) if restarts >= max) {
+                error!(task = %task.name, restarts, "Giving up on controller task.");
+                if self.policy.abort_on_failure {
+                    self.failed
+                        .lock()
+                        .expect("Supervisor lock poisoned.")
+                        .get_or_insert_with(|| task.name.clone());
+                    self.handle.shutdown();
+                }
+                break;
+            }
+            restarts += 1;
+            let delay = self.policy.backoff.delay(restarts);
+            info!(task = %task.name, restarts, ?delay, "Restarting controller task.");
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => (),
+                _ = wait_shutdown(self.shutdown.clone()) => break,
+            }
+        }
+    }
+}
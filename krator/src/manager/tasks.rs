@@ -2,26 +2,61 @@
 //! [Manager](crate::manager::Manager).
 
 use std::future::Future;
+use std::sync::Arc;
 
 use futures::FutureExt;
 
 use kube::{api::ApiResource, api::GroupVersionKind, Resource};
 use kube_runtime::watcher::Event;
+use tokio::sync::watch;
 use tracing::{debug, info, warn};
 
 use crate::{
+    background::BackgroundTask,
+    health::Health,
+    leader::wait_for_leadership,
     manager::controller::ControllerBuilder,
+    metrics::{ManagerMetrics, StateMetrics, WatchMetrics},
+    object::ObjectKey,
     operator::Operator,
-    store::Store,
-    util::{concrete_event, DynamicEvent, PrettyEvent},
+    runtime::{wait_shutdown, PauseHandle},
+    state::StateMiddleware,
+    store::{IndexFn, Store},
+    util::{concrete_event, Backoff, DynamicEvent, PrettyEvent},
 };
 
+use super::controller::MapFn;
+use super::metadata::{metadata_client, restore_types};
+use super::supervision::SupervisedTask;
 use super::watch::WatchHandle;
 use super::Controller;
 
+/// A receiver shared by the runs of a supervised task, so that it survives
+/// restarts.
+type SharedReceiver<T> = Arc<tokio::sync::Mutex<tokio::sync::mpsc::Receiver<T>>>;
+
+fn shared<T>(rx: tokio::sync::mpsc::Receiver<T>) -> SharedReceiver<T> {
+    Arc::new(tokio::sync::Mutex::new(rx))
+}
+
+/// `group/version/kind`, to name tasks.
+pub(crate) fn kind_name(gvk: &GroupVersionKind) -> String {
+    format!("{}/{}/{}", gvk.group, gvk.version, gvk.kind)
+}
+
 /// Watcher task which forwards [DynamicEvent](crate::util::DynamicEvent) to
-/// a [channel](tokio::sync::mpsc::channel).
-pub(crate) async fn launch_watcher(client: kube::Client, handle: WatchHandle) {
+/// a [channel](tokio::sync::mpsc::channel). Errors are retried according to
+/// `backoff`. Connectivity and the initial list are reported to `health`,
+/// and queued events and restarts to `metrics`. Returns once shutdown is
+/// requested, closing the channel.
+pub(crate) async fn launch_watcher(
+    client: kube::Client,
+    handle: WatchHandle,
+    backoff: Backoff,
+    shutdown: watch::Receiver<bool>,
+    health: Health,
+    metrics: WatchMetrics,
+) {
     use futures::StreamExt;
     use futures::TryStreamExt;
 
@@ -29,6 +64,14 @@ pub(crate) async fn launch_watcher(client: kube::Client, handle: WatchHandle) {
         watch=?handle.watch,
         "Starting Watcher."
     );
+    let gvk = handle.watch.gvk.clone();
+    let namespace = handle.watch.namespace.clone();
+    let metadata_only = handle.watch.metadata_only;
+    let client = if metadata_only {
+        metadata_client(client)
+    } else {
+        client
+    };
     let api: kube::Api<kube::api::DynamicObject> = match handle.watch.namespace {
         Some(namespace) => kube::Api::namespaced_with(
             client,
@@ -37,197 +80,666 @@ pub(crate) async fn launch_watcher(client: kube::Client, handle: WatchHandle) {
         ),
         None => kube::Api::all_with(client, &ApiResource::from_gvk(&handle.watch.gvk)),
     };
-    let mut watcher = kube_runtime::watcher(api, handle.watch.list_params).boxed();
+    // Bookmarks let a dropped watch resume instead of re-listing.
+    let list_params = kube::api::ListParams {
+        bookmarks: true,
+        ..handle.watch.list_params
+    };
+    let resource = ApiResource::from_gvk(&gvk);
+    let types = kube::api::TypeMeta {
+        api_version: resource.api_version,
+        kind: resource.kind,
+    };
+    let mut watcher = kube_runtime::watcher(api, list_params).boxed();
+    let mut failures: u32 = 0;
     loop {
-        match watcher.try_next().await {
+        let next = tokio::select! {
+            next = watcher.try_next() => next,
+            _ = wait_shutdown(shutdown.clone()) => {
+                info!(
+                    gvk=?gvk,
+                    ?namespace,
+                    "Shutdown requested, stopping Watcher."
+                );
+                break;
+            }
+        };
+        match next {
             Ok(Some(event)) => {
+                failures = 0;
+                health.watcher_connected(&gvk, &namespace, matches!(event, Event::Restarted(_)));
+                let event = if metadata_only {
+                    restore_event_types(event, &types)
+                } else {
+                    event
+                };
                 debug!(
                     event = ?PrettyEvent::from(&event),
                     "Handling event."
                 );
-                handle.tx.send(event).await.unwrap()
+                if handle.tx.send(event).await.is_err() {
+                    debug!(gvk=?gvk, "Event receiver dropped, stopping Watcher.");
+                    break;
+                }
+                metrics.enqueued();
             }
             Ok(None) => break,
-            Err(error) => warn!(?error, "Error streaming object events."),
+            Err(error) => {
+                failures = failures.saturating_add(1);
+                health.watcher_failed(&gvk, &namespace);
+                metrics.restarted();
+                let delay = backoff.delay(failures);
+                warn!(
+                    gvk=?gvk,
+                    ?error,
+                    failures,
+                    ?delay,
+                    "Error streaming object events."
+                );
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => (),
+                    _ = wait_shutdown(shutdown.clone()) => (),
+                }
+            }
         }
     }
 }
 
+/// Give the `PartialObjectMetadata` objects of a metadata-only watch the
+/// watched kind.
+fn restore_event_types(event: DynamicEvent, types: &kube::api::TypeMeta) -> DynamicEvent {
+    let restore = |object| restore_types(object, types);
+    match event {
+        Event::Applied(object) => Event::Applied(restore(object)),
+        Event::Deleted(object) => Event::Deleted(restore(object)),
+        Event::Restarted(objects) => Event::Restarted(objects.into_iter().map(restore).collect()),
+    }
+}
+
+/// What a controller's [OperatorRuntime](crate::OperatorRuntime) receives
+/// from the other tasks of the controller.
+struct RuntimeInputs {
+    /// Events of the managed objects.
+    managed: SharedReceiver<DynamicEvent>,
+    /// Owners of changed `owned` objects.
+    owners: SharedReceiver<ObjectKey>,
+    /// Kinds which are `watched` or `owned`, which must be cached before
+    /// objects are dispatched.
+    cached: Vec<GroupVersionKind>,
+    /// Scope of the managed objects, to re-list them after a restart.
+    namespace: Option<String>,
+    list_params: kube::api::ListParams,
+    /// Whether a previous run of the runtime stopped unexpectedly.
+    restarted: bool,
+    /// Metrics of the managed objects' queue and state machines.
+    queue: WatchMetrics,
+    states: StateMetrics,
+    /// Whether this replica is leading, unless the controller runs on every
+    /// replica.
+    leader: Option<watch::Receiver<bool>>,
+    shutdown: watch::Receiver<bool>,
+}
+
 /// Task for executing a single Controller / Operator. Listens for
 /// [DynamicEvent](crate::util::DynamicEvent) on a
 /// [channel](tokio::sync::mpsc::channel) and forwards them to a Krator
-/// [OperatorRuntime](crate::OperatorRuntime).
+/// [OperatorRuntime](crate::OperatorRuntime), along with notifications for
+/// the owners of changed `owned` objects. Nothing is dispatched until the
+/// `watched` and `owned` objects have been cached. After a restart, the
+/// managed objects are re-listed, since the state of the previous run is
+/// lost. Once shutdown is requested, running state machines are drained
+/// before returning.
+///
+/// When following a leader, events are discarded while this replica is not
+/// leading. Once leadership is lost, running state machines are drained and
+/// a new runtime is started when it is acquired again.
 ///
 /// # Errors
 ///
 /// A warning will be logged if a `DynamicEvent` cannot be converted to a
 /// concrete `Event<O::Manifest>`.
-async fn launch_runtime<O: Operator>(
-    kubeconfig: kube::Config,
-    controller: O,
-    mut rx: tokio::sync::mpsc::Receiver<DynamicEvent>,
+async fn launch_runtime<O>(
+    client: kube::Client,
+    controller: Arc<O>,
+    inputs: RuntimeInputs,
     store: Store,
-) {
+    pause: PauseHandle,
+    background_tasks: Vec<BackgroundTask<O>>,
+    middleware: Vec<Arc<dyn StateMiddleware<O::ObjectState>>>,
+) where
+    O: Operator,
+    O::Manifest: Resource<DynamicType = ()>,
+{
     info!(
         group = &*O::Manifest::group(&()),
         version = &*O::Manifest::version(&()),
         kind = &*O::Manifest::kind(&()),
         "Starting OperatorRuntime."
     );
-    let mut runtime =
-        crate::OperatorRuntime::new_with_store(&kubeconfig, controller, Default::default(), store);
-    while let Some(dynamic_event) = rx.recv().await {
-        debug!(
-            group=&*O::Manifest::group(&()),
-            version=&*O::Manifest::version(&()),
-            kind=&*O::Manifest::kind(&()),
-            event = ?PrettyEvent::from(&dynamic_event),
-            "Handling managed event."
+    let RuntimeInputs {
+        managed,
+        owners,
+        cached,
+        namespace,
+        list_params,
+        mut restarted,
+        queue,
+        states,
+        leader,
+        shutdown,
+    } = inputs;
+    let mut rx = managed.lock_owned().await;
+    let mut owners = owners.lock_owned().await;
+    loop {
+        if let Some(ref leader) = leader {
+            let mut leading = leader.clone();
+            if !*leading.borrow() {
+                info!(
+                    group = &*O::Manifest::group(&()),
+                    version = &*O::Manifest::version(&()),
+                    kind = &*O::Manifest::kind(&()),
+                    "Waiting for leadership."
+                );
+            }
+            loop {
+                tokio::select! {
+                    _ = wait_for_leadership(&mut leading, true) => break,
+                    _ = wait_shutdown(shutdown.clone()) => return,
+                    Some(_) = rx.recv() => {
+                        // Objects are re-listed once leading, so nothing
+                        // is missed.
+                        queue.dequeued();
+                        restarted = true;
+                    }
+                    Some(_) = owners.recv() => (),
+                }
+            }
+        }
+
+        let mut paused = pause.subscribe();
+        let runtime = crate::OperatorRuntime::from_parts(
+            client.clone(),
+            Arc::clone(&controller),
+            Arc::new(()),
+            Some(list_params.clone()),
+            store.clone(),
         );
+        let runtime = match namespace {
+            Some(ref namespace) => runtime.namespaced(namespace),
+            None => runtime,
+        };
+        let mut runtime = runtime
+            .with_pause_handle(pause.clone())
+            .with_state_metrics(states.clone())
+            .with_background_tasks(background_tasks.clone())
+            .with_state_middlewares(middleware.clone());
+        let mut lost = Box::pin(async {
+            match leader {
+                Some(ref leader) => {
+                    let mut leading = leader.clone();
+                    wait_for_leadership(&mut leading, false).await
+                }
+                None => futures::future::pending::<()>().await,
+            }
+        });
+        if !cached.is_empty() {
+            debug!(?cached, "Waiting for watched objects to be cached.");
+            tokio::select! {
+                _ = store.wait_synced(&cached) => (),
+                _ = wait_shutdown(shutdown.clone()) => {
+                    runtime.drain().await;
+                    return;
+                }
+            }
+        }
+        runtime.resolve_status_options().await;
+        runtime.spawn_background_tasks().await;
+        if restarted {
+            runtime.periodic_resync().await;
+        }
+        let stopped = loop {
+            let dynamic_event = tokio::select! {
+                // Watchers close the channel on shutdown, which is not an error.
+                biased;
+                _ = wait_shutdown(shutdown.clone()) => {
+                    info!(
+                        group = &*O::Manifest::group(&()),
+                        version = &*O::Manifest::version(&()),
+                        kind = &*O::Manifest::kind(&()),
+                        "Shutdown requested, stopping OperatorRuntime."
+                    );
+                    break true;
+                }
+                _ = &mut lost => {
+                    warn!(
+                        group = &*O::Manifest::group(&()),
+                        version = &*O::Manifest::version(&()),
+                        kind = &*O::Manifest::kind(&()),
+                        "Lost leadership, stopping OperatorRuntime."
+                    );
+                    break false;
+                }
+                event = rx.recv() => match event {
+                    Some(event) => {
+                        queue.dequeued();
+                        event
+                    }
+                    None => {
+                        warn!(
+                            group = &*O::Manifest::group(&()),
+                            version = &*O::Manifest::version(&()),
+                            kind = &*O::Manifest::kind(&()),
+                            "Managed Sender dropped."
+                        );
+                        break true;
+                    }
+                },
+                Ok(()) = paused.changed() => {
+                    runtime.catch_up_after_pause().await;
+                    continue;
+                }
+                Some(owner) = owners.recv() => {
+                    runtime.notify_owner(owner).await;
+                    continue;
+                }
+            };
+            debug!(
+                group=&*O::Manifest::group(&()),
+                version=&*O::Manifest::version(&()),
+                kind=&*O::Manifest::kind(&()),
+                event = ?PrettyEvent::from(&dynamic_event),
+                "Handling managed event."
+            );
 
-        match concrete_event::<O::Manifest>(dynamic_event.clone()) {
-            Ok(event) => runtime.handle_event(event).await,
-            Err(e) => {
-                warn!(
-                    group=&*O::Manifest::group(&()),
-                    version=&*O::Manifest::version(&()),
-                    kind=&*O::Manifest::kind(&()),
-                    error=?e,
-                    "Error deserializing dynamic object: {:#?}", dynamic_event
-                );
+            match concrete_event::<O::Manifest>(dynamic_event.clone()) {
+                Ok(event) => runtime.handle_event(event).await,
+                Err(e) => {
+                    warn!(
+                        group=&*O::Manifest::group(&()),
+                        version=&*O::Manifest::version(&()),
+                        kind=&*O::Manifest::kind(&()),
+                        error=?e,
+                        "Error deserializing dynamic object: {:#?}", dynamic_event
+                    );
+                }
             }
+        };
+        runtime.drain().await;
+        if stopped {
+            return;
         }
+        // The next runtime starts without the state of this one.
+        restarted = true;
     }
-    warn!(
-        group = &*O::Manifest::group(&()),
-        version = &*O::Manifest::version(&()),
-        kind = &*O::Manifest::kind(&()),
-        "Managed Sender dropped."
-    );
 }
 
-/// Task for monitoring `watched` or `owned` resources. Listens for
+/// Managed objects to notify when `owned` or mapped resources change.
+#[derive(Clone)]
+struct Owners {
+    /// Keys of the managed objects related to a changed object.
+    map: MapFn,
+    tx: tokio::sync::mpsc::Sender<ObjectKey>,
+}
+
+impl Owners {
+    /// Notify the objects of the controller's kind `R` owning a changed
+    /// object. Owners are in the same namespace, unless they are
+    /// cluster-scoped.
+    fn by_owner_references<R: Resource<DynamicType = ()>>(
+        tx: tokio::sync::mpsc::Sender<ObjectKey>,
+    ) -> Self {
+        let api_version = R::api_version(&()).to_string();
+        let kind = R::kind(&()).to_string();
+        Owners {
+            map: Arc::new(move |object| {
+                object
+                    .metadata
+                    .owner_references
+                    .iter()
+                    .flatten()
+                    .filter(|owner| owner.api_version == api_version && owner.kind == kind)
+                    .map(|owner| {
+                        ObjectKey::new(object.metadata.namespace.clone(), owner.name.clone())
+                    })
+                    .collect()
+            }),
+            tx,
+        }
+    }
+
+    fn of(&self, object: &kube::api::DynamicObject) -> Vec<ObjectKey> {
+        (self.map)(object)
+    }
+}
+
+/// Task for monitoring `watched`, `owned` or mapped resources. Listens for
 /// [DynamicEvent](crate::util::DynamicEvent) on a
 /// [channel](tokio::sync::mpsc::channel) and updates
-/// [Store](crate::store::Store).
+/// [Store](crate::store::Store). With `owners`, the managed objects related
+/// to changed objects are notified after the store is updated. The `indexes`
+/// are added to the store before any object is cached.
 ///
 /// # Errors
 ///
 /// Will warn on and drop objects with no `metadata.name` field set.
-///
-/// # TODO
-///
-/// * Support notifications for `owned` resources.
 async fn launch_watches(
-    mut rx: tokio::sync::mpsc::Receiver<DynamicEvent>,
+    rx: SharedReceiver<DynamicEvent>,
     gvk: GroupVersionKind,
+    namespace: Option<String>,
     store: Store,
+    owners: Option<Owners>,
+    indexes: Vec<(String, IndexFn)>,
+    metrics: WatchMetrics,
 ) {
+    for (name, f) in indexes {
+        store.add_index_gvk(&gvk, &name, f).await;
+    }
+    let mut rx = rx.lock_owned().await;
     while let Some(dynamic_event) = rx.recv().await {
+        metrics.dequeued();
         debug!(
             gvk=?gvk,
             event = ?PrettyEvent::from(&dynamic_event),
             "Handling watched event."
         );
-        match dynamic_event {
-            Event::Applied(dynamic_object) => {
-                let namespace = dynamic_object.metadata.namespace.clone();
-                let name = match dynamic_object.metadata.name.clone() {
-                    Some(name) => name,
-                    None => {
-                        warn!(
-                            gvk=?gvk,
-                            "Object without name."
-                        );
-                        continue;
-                    }
-                };
-                store
-                    .insert_gvk(namespace, name, &gvk, dynamic_object)
-                    .await;
-            }
-            Event::Deleted(dynamic_object) => {
-                let namespace = dynamic_object.metadata.namespace.clone();
-                let name = match dynamic_object.metadata.name.clone() {
-                    Some(name) => name,
-                    None => {
-                        warn!(
-                            gvk=?gvk,
-                            "Object without name."
-                        );
-                        continue;
-                    }
-                };
-                store.delete_gvk(namespace, name, &gvk).await;
-            }
-            Event::Restarted(dynamic_objects) => {
-                store.reset(&gvk).await;
-                for dynamic_object in dynamic_objects {
-                    let namespace = dynamic_object.metadata.namespace.clone();
-                    let name = match dynamic_object.metadata.name.clone() {
-                        Some(name) => name,
-                        None => {
-                            warn!(
-                                gvk=?gvk,
-                                "Object without name."
-                            );
-                            continue;
-                        }
-                    };
-                    store
-                        .insert_gvk(namespace, name, &gvk, dynamic_object)
-                        .await;
+        let notify: std::collections::HashSet<ObjectKey> = match owners {
+            Some(ref owners) => match dynamic_event {
+                Event::Applied(ref object) | Event::Deleted(ref object) => {
+                    owners.of(object).into_iter().collect()
+                }
+                Event::Restarted(ref objects) => objects
+                    .iter()
+                    .flat_map(|object| owners.of(object))
+                    .collect(),
+            },
+            None => Default::default(),
+        };
+        store.apply(&gvk, namespace.as_deref(), dynamic_event).await;
+        if let Some(ref owners) = owners {
+            for owner in notify {
+                if owners.tx.send(owner).await.is_err() {
+                    debug!(gvk=?gvk, "Owner receiver dropped, no longer notifying owners.");
+                    break;
                 }
             }
         }
     }
 }
 
+/// The indexes of the kind `gvk` among those of a controller.
+fn indexes_of(
+    indexes: &[(GroupVersionKind, String, IndexFn)],
+    gvk: &GroupVersionKind,
+) -> Vec<(String, IndexFn)> {
+    indexes
+        .iter()
+        .filter(|(indexed, _, _)| indexed == gvk)
+        .map(|(_, name, f)| (name.clone(), Arc::clone(f)))
+        .collect()
+}
+
 /// Shorthand for the opaque Future type of the tasks in this module. These
 /// must be `awaited` in order to execute.
 pub(crate) type OperatorTask = std::pin::Pin<Box<dyn Future<Output = ()> + Send>>;
 
+/// Runs a controller's [on_start](crate::Operator::on_start) hook.
+pub(crate) type StartupHook = Box<
+    dyn FnOnce(kube::Client) -> std::pin::Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>
+        + Send,
+>;
+
 /// Generates the `async` tasks needed to run a single controller / operator.
 ///
 /// In general, converts a
 /// [ControllerBuilder](crate::manager::controller::ControllerBuilder) to a
-/// `Vec` of [OperatorTask](crate::manager::tasks::OperatorTask) which can be
-/// executed using [join_all](futures::future::join_all).
-pub(crate) fn controller_tasks<C: Operator>(
-    kubeconfig: kube::Config,
+/// `Vec` of [SupervisedTask](super::supervision::SupervisedTask) which are
+/// run by the Manager's supervisor, along with the controller's startup
+/// hook, which must complete before they are started.
+///
+/// # Errors
+///
+/// Returns an error if the controller's admission webhooks cannot be served
+/// together.
+pub(crate) fn controller_tasks<C>(
+    client: kube::Client,
     controller: ControllerBuilder<C>,
     store: Store,
-) -> (Controller, Vec<OperatorTask>) {
+    pause: PauseHandle,
+    shutdown: watch::Receiver<bool>,
+    metrics: ManagerMetrics,
+    leader: watch::Receiver<bool>,
+) -> anyhow::Result<(Controller, Vec<SupervisedTask>, StartupHook)>
+where
+    C: Operator,
+    C::Manifest: Resource<DynamicType = ()>,
+{
     let mut watches = Vec::new();
     let mut owns = Vec::new();
     let mut tasks = Vec::new();
     let buffer = controller.buffer();
+    let name = controller.name();
 
     // Create main Operator task.
     let (manages, rx) = controller.manages().handle(buffer);
-    let task = launch_runtime(kubeconfig, controller.controller, rx, store.clone()).boxed();
-    tasks.push(task);
+    let managed = shared(rx);
+    let (owners_tx, owners_rx) = tokio::sync::mpsc::channel(buffer);
+    let owners_rx = shared(owners_rx);
+    let operator = Arc::new(controller.controller);
+    let startup_operator = Arc::clone(&operator);
+    let graph = controller.graph;
+    let on_start: StartupHook = Box::new(move |client| {
+        async move {
+            if let Some(graph) = graph {
+                graph.validate()?;
+            }
+            startup_operator.on_start(client).await
+        }
+        .boxed()
+    });
+    #[cfg(feature = "admission-webhook")]
+    let webhooks = {
+        let webhook_client = client.clone();
+        let admission_hook = controller.admission_hook.map(|path| {
+            let operator = Arc::clone(&operator);
+            let f: Arc<crate::admission::WebhookFn<C>> = Arc::new(move |manifest, _context| {
+                let operator = Arc::clone(&operator);
+                async move { operator.admission_hook(manifest).await }.boxed()
+            });
+            crate::admission::Webhook {
+                path,
+                kind: crate::admission::WebhookKind::Mutating,
+                side_effects: crate::admission::SideEffects::None,
+                f,
+            }
+        });
+        let webhooks: Vec<_> = controller
+            .webhooks
+            .into_iter()
+            .chain(admission_hook)
+            .collect();
+        let registrations: Vec<_> = webhooks
+            .iter()
+            .map(|webhook| {
+                crate::admission::WebhookRegistration::new::<C::Manifest>(
+                    &webhook.path,
+                    webhook.kind,
+                    &controller.webhook_operations,
+                    controller.webhook_failure_policy,
+                    controller.webhook_namespace_selector.clone(),
+                    webhook.side_effects,
+                )
+            })
+            .collect();
+        webhooks
+            .into_iter()
+            .map(|webhook| {
+                crate::admission::webhook_handler(
+                    webhook,
+                    controller.admission.clone(),
+                    webhook_client.clone(),
+                )
+            })
+            .try_fold(
+                None,
+                |merged: Option<crate::admission::AdmissionHandler>, other| match merged {
+                    Some(mut handler) => handler.merge(other).map(|()| Some(handler)),
+                    None => Ok(Some(other)),
+                },
+            )?
+            .map(|handler| {
+                let operator = Arc::clone(&operator);
+                super::controller::ControllerWebhooks {
+                    handler,
+                    tls: vec![super::controller::WebhookTls {
+                        refresh: operator.admission_hook_tls_refresh(),
+                        source: crate::admission::tls_source(operator),
+                    }],
+                    registrations,
+                }
+            })
+    };
+    let cached: Vec<GroupVersionKind> = controller
+        .watches
+        .iter()
+        .chain(controller.owns.iter())
+        .chain(controller.mapped.iter().map(|(watch, _)| watch))
+        .map(|watch| watch.gvk.clone())
+        .collect();
+    let namespace = manages.watch.namespace.clone();
+    let list_params = manages.watch.list_params.clone();
+    let background_tasks = controller.background_tasks;
+    let middleware = controller.middleware;
+    let runtime_store = store.clone();
+    let queue = metrics.watch(&name, &manages.watch.gvk);
+    let states = metrics.controller(&name, &manages.watch.gvk);
+    let leader = if controller.follows_leader {
+        Some(leader)
+    } else {
+        None
+    };
+    let mut restarted = false;
+    tasks.push(SupervisedTask::new(
+        format!("runtime {}", kind_name(&manages.watch.gvk)),
+        move || {
+            let inputs = RuntimeInputs {
+                managed: Arc::clone(&managed),
+                owners: Arc::clone(&owners_rx),
+                cached: cached.clone(),
+                namespace: namespace.clone(),
+                list_params: list_params.clone(),
+                restarted,
+                queue: queue.clone(),
+                states: states.clone(),
+                leader: leader.clone(),
+                shutdown: shutdown.clone(),
+            };
+            restarted = true;
+            launch_runtime(
+                client.clone(),
+                Arc::clone(&operator),
+                inputs,
+                runtime_store.clone(),
+                pause.clone(),
+                background_tasks.clone(),
+                middleware.clone(),
+            )
+            .boxed()
+        },
+    ));
 
     for watch in controller.watches {
         let (handle, rx) = watch.handle(buffer);
-        let task = launch_watches(rx, handle.watch.gvk.clone(), store.clone()).boxed();
+        let rx = shared(rx);
+        let gvk = handle.watch.gvk.clone();
+        let namespace = handle.watch.namespace.clone();
+        let store = store.clone();
+        let metrics = metrics.watch(&name, &gvk);
+        let indexes = indexes_of(&controller.indexes, &gvk);
+        tasks.push(SupervisedTask::new(
+            format!("cache {}", kind_name(&gvk)),
+            move || {
+                launch_watches(
+                    Arc::clone(&rx),
+                    gvk.clone(),
+                    namespace.clone(),
+                    store.clone(),
+                    None,
+                    indexes.clone(),
+                    metrics.clone(),
+                )
+                .boxed()
+            },
+        ));
         watches.push(handle);
-        tasks.push(task);
     }
 
     for own in controller.owns {
         let (handle, rx) = own.handle(buffer);
-        let task = launch_watches(rx, handle.watch.gvk.clone(), store.clone()).boxed();
+        let rx = shared(rx);
+        let gvk = handle.watch.gvk.clone();
+        let namespace = handle.watch.namespace.clone();
+        let store = store.clone();
+        let metrics = metrics.watch(&name, &gvk);
+        let indexes = indexes_of(&controller.indexes, &gvk);
+        let owners = Owners::by_owner_references::<C::Manifest>(owners_tx.clone());
+        tasks.push(SupervisedTask::new(
+            format!("cache {}", kind_name(&gvk)),
+            move || {
+                launch_watches(
+                    Arc::clone(&rx),
+                    gvk.clone(),
+                    namespace.clone(),
+                    store.clone(),
+                    Some(owners.clone()),
+                    indexes.clone(),
+                    metrics.clone(),
+                )
+                .boxed()
+            },
+        ));
         owns.push(handle);
-        tasks.push(task);
     }
 
-    (
+    for (watch, map) in controller.mapped {
+        let (handle, rx) = watch.handle(buffer);
+        let rx = shared(rx);
+        let gvk = handle.watch.gvk.clone();
+        let namespace = handle.watch.namespace.clone();
+        let store = store.clone();
+        let metrics = metrics.watch(&name, &gvk);
+        let indexes = indexes_of(&controller.indexes, &gvk);
+        let owners = Owners {
+            map,
+            tx: owners_tx.clone(),
+        };
+        tasks.push(SupervisedTask::new(
+            format!("cache {}", kind_name(&gvk)),
+            move || {
+                launch_watches(
+                    Arc::clone(&rx),
+                    gvk.clone(),
+                    namespace.clone(),
+                    store.clone(),
+                    Some(owners.clone()),
+                    indexes.clone(),
+                    metrics.clone(),
+                )
+                .boxed()
+            },
+        ));
+        watches.push(handle);
+    }
+
+    Ok((
         Controller {
+            name,
             manages,
             owns,
             watches,
+            #[cfg(feature = "admission-webhook")]
+            webhooks,
         },
         tasks,
-    )
+        on_start,
+    ))
 }
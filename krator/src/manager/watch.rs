@@ -13,6 +13,8 @@ pub struct Watch {
     pub namespace: Option<String>,
     /// Restrict to objects matching list params (default watches everything).
     pub list_params: ListParams,
+    /// Only list and watch the metadata of objects.
+    pub metadata_only: bool,
 }
 
 impl Watch {
@@ -27,9 +29,16 @@ impl Watch {
             gvk,
             namespace,
             list_params,
+            metadata_only: false,
         }
     }
 
+    /// Only keep the metadata of watched objects.
+    pub fn metadata_only(mut self) -> Self {
+        self.metadata_only = true;
+        self
+    }
+
     pub fn handle(
         self,
         buffer: usize,
@@ -1,7 +1,15 @@
+use crate::owned::{ChildSummary, FailingChild};
 use crate::store::Store;
 use core::pin::Pin;
 use core::task::{Context, Poll};
-use tokio::sync::watch::{channel, Receiver, Sender};
+use k8s_openapi::api::core::v1::ObjectReference;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use kube::ResourceExt;
+use kube_runtime::events::{Recorder, Reporter};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch::{channel, Receiver, Ref, Sender};
 use tokio_stream::{wrappers::WatchStream, Stream};
 
 /// Wrapper for `ObjectState::Manifest` type which reflects
@@ -20,8 +28,21 @@ where
     /// Use to access [Store](crate::store::Store) and read watched resource
     /// cache.
     pub store: Store,
+    client: Option<kube::Client>,
+    cluster: Option<String>,
+    /// Reporter and reference to the object, used to record Events.
+    events: Option<(Reporter, ObjectReference)>,
+    /// Status most recently written by the runtime.
+    pub(crate) last_status: LastStatus,
+    /// Changed whenever an owned object changes.
+    dependents: Option<Receiver<()>>,
+    /// The version of the manifest delivered before the latest one.
+    pub(crate) previous: Arc<Mutex<Option<T>>>,
 }
 
+/// Status written by the runtime, shared between a manifest and its clones.
+pub(crate) type LastStatus = Arc<Mutex<Option<serde_json::Value>>>;
+
 impl<T> Clone for Manifest<T>
 where
     T: Clone + Sync + Send + std::marker::Unpin + 'static,
@@ -31,6 +52,12 @@ where
             rx: self.rx.clone(),
             stream: WatchStream::new(self.rx.clone()),
             store: self.store.clone(),
+            client: self.client.clone(),
+            cluster: self.cluster.clone(),
+            events: self.events.clone(),
+            last_status: self.last_status.clone(),
+            dependents: self.dependents.clone(),
+            previous: self.previous.clone(),
         }
     }
 }
@@ -43,13 +70,370 @@ where
     pub fn new(inner: T, store: Store) -> (Sender<T>, Self) {
         let (tx, rx) = channel(inner);
         let stream = WatchStream::new(rx.clone());
-        (tx, Manifest { rx, stream, store })
+        (
+            tx,
+            Manifest {
+                rx,
+                stream,
+                store,
+                client: None,
+                cluster: None,
+                events: None,
+                last_status: Default::default(),
+                dependents: None,
+                previous: Default::default(),
+            },
+        )
+    }
+
+    pub(crate) fn with_client(mut self, client: kube::Client, cluster: Option<String>) -> Self {
+        self.client = Some(client);
+        self.cluster = cluster;
+        self
+    }
+
+    pub(crate) fn with_events(mut self, reporter: Reporter, reference: ObjectReference) -> Self {
+        self.events = Some((reporter, reference));
+        self
+    }
+
+    pub(crate) fn with_dependents(mut self, dependents: Receiver<()>) -> Self {
+        self.dependents = Some(dependents);
+        self
+    }
+
+    /// Resolves once an object owned by this object changed since the last
+    /// call, which also re-delivers the latest manifest on the stream.
+    /// Changes are only noticed for owned objects watched with
+    /// [ControllerBuilder::owns](crate::ControllerBuilder::owns) in a
+    /// [Manager](crate::Manager), and never resolves otherwise.
+    pub async fn dependent_changed(&mut self) {
+        match self.dependents {
+            Some(ref mut dependents) if dependents.changed().await.is_ok() => (),
+            _ => futures::future::pending().await,
+        }
     }
 
     /// Obtain a clone of the latest object manifest.
     pub fn latest(&self) -> T {
         self.rx.borrow().clone()
     }
+
+    /// Borrow the latest object manifest without cloning it. New versions
+    /// cannot be delivered while the returned guard is alive, so drop it
+    /// before awaiting.
+    pub fn latest_ref(&self) -> Ref<'_, T> {
+        self.rx.borrow()
+    }
+
+    /// Run `f` against the latest object manifest without cloning it, for
+    /// example to read a single field.
+    pub fn with_latest<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.rx.borrow())
+    }
+
+    /// Look up an object of type `R` in the [Store](crate::store::Store),
+    /// for example one referenced by the spec. Objects are only cached if
+    /// their kind is watched. See [Store::get](crate::store::Store::get).
+    ///
+    /// ```no_run
+    /// # use k8s_openapi::api::core::v1::{ConfigMap, Pod};
+    /// # use krator::Manifest;
+    /// # async fn resolve(manifest: Manifest<Pod>) -> anyhow::Result<()> {
+    /// let config = manifest
+    ///     .lookup::<ConfigMap>(Some("default"), "settings")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn lookup<R>(&self, namespace: Option<&str>, name: &str) -> anyhow::Result<Option<R>>
+    where
+        R: k8s_openapi::Resource + Clone + DeserializeOwned + 'static,
+    {
+        self.store.get::<R>(namespace, name).await
+    }
+
+    /// Wait for a version of the manifest for which `predicate` returns
+    /// `true`, when given the latest version at the time of the call and
+    /// the new version, and return it. Versions for which it returns `false`,
+    /// such as those only changing the status written by the operator
+    /// itself, are skipped without waking the caller.
+    ///
+    /// Returns `None` once no more versions are delivered, for example
+    /// because the object's state machine is stopping.
+    pub async fn changed_where(&self, mut predicate: impl FnMut(&T, &T) -> bool) -> Option<T> {
+        let mut rx = self.rx.clone();
+        let baseline = self.latest();
+        loop {
+            if rx.changed().await.is_err() {
+                return None;
+            }
+            let latest = rx.borrow().clone();
+            if predicate(&baseline, &latest) {
+                return Some(latest);
+            }
+        }
+    }
+
+    /// Wait until the latest version of the manifest satisfies `predicate`,
+    /// for example until a finalizer is present, and return it. Returns
+    /// immediately if it already does, and `None` if `timeout` elapses or no
+    /// more versions are delivered first.
+    ///
+    /// ```no_run
+    /// # use k8s_openapi::api::core::v1::Pod;
+    /// # use krator::Manifest;
+    /// # use std::time::Duration;
+    /// # async fn scheduled(manifest: Manifest<Pod>) {
+    /// let pod = manifest
+    ///     .wait_for(
+    ///         |pod| pod.spec.as_ref().and_then(|spec| spec.node_name.as_ref()).is_some(),
+    ///         Duration::from_secs(30),
+    ///     )
+    ///     .await;
+    /// # }
+    /// ```
+    pub async fn wait_for(
+        &self,
+        mut predicate: impl FnMut(&T) -> bool,
+        timeout: std::time::Duration,
+    ) -> Option<T> {
+        let mut rx = self.rx.clone();
+        let satisfied = async move {
+            loop {
+                {
+                    let latest = rx.borrow();
+                    if predicate(&latest) {
+                        return Some(latest.clone());
+                    }
+                }
+                if rx.changed().await.is_err() {
+                    return None;
+                }
+            }
+        };
+        tokio::time::timeout(timeout, satisfied)
+            .await
+            .unwrap_or(None)
+    }
+
+    /// Obtain a clone of the version of the object manifest which was
+    /// delivered before the latest one, or `None` if only one version has
+    /// been delivered so far.
+    pub fn previous(&self) -> Option<T> {
+        self.previous
+            .lock()
+            .expect("Previous manifest lock poisoned.")
+            .clone()
+    }
+
+    /// The client for the cluster the object lives in, when the manifest
+    /// was created by the runtime.
+    pub fn client(&self) -> Option<&kube::Client> {
+        self.client.as_ref()
+    }
+
+    /// A recorder which publishes Kubernetes Events about the object, when
+    /// the manifest was created by the runtime. Events are reported as coming
+    /// from the runtime's
+    /// [event reporter](crate::OperatorRuntime::with_event_reporter).
+    pub fn recorder(&self) -> Option<Recorder> {
+        match (&self.client, &self.events) {
+            (Some(client), Some((reporter, reference))) => Some(Recorder::new(
+                client.clone(),
+                reporter.clone(),
+                reference.clone(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// The status most recently written by the runtime for this object,
+    /// merged from every successful status patch, or `None` before the first.
+    /// Unlike the status in [latest](Manifest::latest), it does not lag
+    /// behind while the watch catches up. Fields which were never written,
+    /// such as those set by other controllers, are missing.
+    pub fn last_status<S: DeserializeOwned>(&self) -> anyhow::Result<Option<S>> {
+        let status = self
+            .last_status
+            .lock()
+            .expect("Last status lock poisoned.")
+            .clone();
+        match status {
+            Some(status) => Ok(Some(serde_json::from_value(status)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The name of the cluster the object lives in, when running in a
+    /// [MultiClusterRuntime](crate::MultiClusterRuntime).
+    pub fn cluster(&self) -> Option<&str> {
+        self.cluster.as_deref()
+    }
+}
+
+impl<T> Manifest<T>
+where
+    T: serde::Serialize + Clone + Sync + Send + std::marker::Unpin + 'static,
+{
+    /// Which top-level fields differ between the [previous](Self::previous)
+    /// and the [latest](Self::latest) version of the manifest. Every field
+    /// of the latest version counts as changed if there is no previous one.
+    ///
+    /// ```
+    /// # use k8s_openapi::api::core::v1::ConfigMap;
+    /// # use krator::{Manifest, Store};
+    /// # let (_tx, manifest) = Manifest::new(ConfigMap::default(), Store::new());
+    /// if manifest.diff()?.spec_changed() {
+    ///     println!("Reconciling the new spec.");
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * If either version cannot be serialized.
+    pub fn diff(&self) -> anyhow::Result<ManifestDiff> {
+        let previous = match self.previous() {
+            Some(previous) => Some(serde_json::to_value(previous)?),
+            None => None,
+        };
+        let latest = serde_json::to_value(self.latest())?;
+        Ok(ManifestDiff::between(previous.as_ref(), &latest))
+    }
+}
+
+impl<T> Manifest<T>
+where
+    T: kube::Resource + Clone + Sync + Send + std::marker::Unpin + 'static,
+{
+    /// Obtain a clone of the metadata of the latest object manifest, such as
+    /// its name, labels or deletion timestamp, without cloning the rest of
+    /// the object.
+    pub fn meta(&self) -> ObjectMeta {
+        self.with_latest(|latest| latest.meta().clone())
+    }
+
+    /// Wait for a version of the manifest with a different
+    /// `metadata.generation`, which the API server only increments when the
+    /// spec changes, and return it. See
+    /// [changed_where](Self::changed_where).
+    pub async fn generation_changed(&self) -> Option<T> {
+        self.changed_where(|baseline, latest| {
+            baseline.meta().generation != latest.meta().generation
+        })
+        .await
+    }
+
+    /// Summarize the readiness of the cached objects of type `R` owned by
+    /// this object, as judged by `readiness`, which returns why an object is
+    /// not ready. [ready_condition](crate::owned::ready_condition) covers
+    /// most built-in types. Owned objects are only cached when they are
+    /// watched, for example with
+    /// [ControllerBuilder::owns](crate::ControllerBuilder::owns).
+    ///
+    /// ```no_run
+    /// # use k8s_openapi::api::apps::v1::StatefulSet;
+    /// # use krator::Manifest;
+    /// use krator::owned::ready_condition;
+    /// # async fn summarize(manifest: Manifest<StatefulSet>) -> anyhow::Result<()> {
+    /// let pods = manifest
+    ///     .owned_summary(ready_condition::<k8s_openapi::api::core::v1::Pod>)
+    ///     .await?;
+    /// println!("{}", pods.message());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn owned_summary<R>(
+        &self,
+        readiness: impl Fn(&R) -> Result<(), String>,
+    ) -> anyhow::Result<ChildSummary>
+    where
+        R: kube::Resource + k8s_openapi::Resource + Clone + DeserializeOwned + 'static,
+    {
+        let uid = match self.meta().uid {
+            Some(uid) => uid,
+            None => return Ok(Default::default()),
+        };
+        let mut summary = ChildSummary::default();
+        for child in self.store.owned_by::<R>(&uid).await? {
+            summary.total += 1;
+            match readiness(&child) {
+                Ok(()) => summary.ready += 1,
+                Err(message) => summary.failing.push(FailingChild {
+                    namespace: child.namespace(),
+                    name: child.name(),
+                    message,
+                }),
+            }
+        }
+        Ok(summary)
+    }
+}
+
+/// The top-level fields of an object which differ between two versions of
+/// its manifest, as returned by [Manifest::diff].
+///
+/// Changes to `metadata.resourceVersion` and `metadata.managedFields` are
+/// ignored, since they change with every write.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ManifestDiff {
+    changed: BTreeSet<String>,
+}
+
+impl ManifestDiff {
+    fn between(previous: Option<&serde_json::Value>, latest: &serde_json::Value) -> Self {
+        fn fields(value: Option<&serde_json::Value>) -> serde_json::Map<String, serde_json::Value> {
+            let mut fields = match value {
+                Some(serde_json::Value::Object(fields)) => fields.clone(),
+                _ => Default::default(),
+            };
+            if let Some(serde_json::Value::Object(metadata)) = fields.get_mut("metadata") {
+                metadata.remove("resourceVersion");
+                metadata.remove("managedFields");
+            }
+            fields
+        }
+        let previous = fields(previous);
+        let latest = fields(Some(latest));
+        let changed = previous
+            .keys()
+            .chain(latest.keys())
+            .filter(|field| previous.get(*field) != latest.get(*field))
+            .cloned()
+            .collect();
+        ManifestDiff { changed }
+    }
+
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty()
+    }
+
+    /// Whether the top-level field `field`, such as `data`, changed.
+    pub fn changed(&self, field: &str) -> bool {
+        self.changed.contains(field)
+    }
+
+    /// The top-level fields which changed, in alphabetical order.
+    pub fn fields(&self) -> impl Iterator<Item = &str> {
+        self.changed.iter().map(String::as_str)
+    }
+
+    /// Whether `spec` changed.
+    pub fn spec_changed(&self) -> bool {
+        self.changed("spec")
+    }
+
+    /// Whether `metadata` changed.
+    pub fn metadata_changed(&self) -> bool {
+        self.changed("metadata")
+    }
+
+    /// Whether `status` changed.
+    pub fn status_changed(&self) -> bool {
+        self.changed("status")
+    }
 }
 
 impl<T> Stream for Manifest<T>
@@ -93,4 +477,26 @@ mod test {
         handle_2.await.ok();
         handle_3.await.ok();
     }
+
+    #[test]
+    fn diff_ignores_resource_version() {
+        let previous = serde_json::json!({
+            "metadata": {"name": "moose", "resourceVersion": "1"},
+            "spec": {"height": 1},
+            "status": {"phase": "Pending"},
+        });
+        let latest = serde_json::json!({
+            "metadata": {"name": "moose", "resourceVersion": "2"},
+            "spec": {"height": 2},
+            "status": {"phase": "Pending"},
+        });
+        let diff = ManifestDiff::between(Some(&previous), &latest);
+        assert!(diff.spec_changed());
+        assert!(!diff.metadata_changed());
+        assert!(!diff.status_changed());
+        assert_eq!(diff.fields().collect::<Vec<_>>(), vec!["spec"]);
+
+        let diff = ManifestDiff::between(None, &latest);
+        assert!(diff.spec_changed() && diff.metadata_changed() && diff.status_changed());
+    }
 }
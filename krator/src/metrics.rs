@@ -0,0 +1,607 @@
+//! Metrics collected while running state machines, admission webhooks and
+//! the controllers of a Manager.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use kube::api::GroupVersionKind;
+
+use crate::graph::short_name;
+
+/// Upper bounds, in seconds, of the buckets of [Histogram].
+const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0,
+];
+
+/// Per-state counters and durations, shared between every state machine of
+/// a runtime. Cloning returns a handle to the same metrics, so that they can
+/// be read or exported while the runtime runs.
+///
+/// ```
+/// use krator::metrics::StateMetrics;
+/// let metrics = StateMetrics::new();
+/// // runtime.with_state_metrics(metrics.clone());
+/// println!("{}", metrics.render());
+/// ```
+#[derive(Clone, Default)]
+pub struct StateMetrics {
+    states: Arc<Mutex<BTreeMap<String, StateStats>>>,
+    quarantine: Arc<Mutex<QuarantineStats>>,
+    /// Number of objects whose reconciliation failed permanently.
+    failed_objects: Arc<AtomicU64>,
+}
+
+/// Counts of objects put into quarantine, see
+/// [with_quarantine](crate::OperatorRuntime::with_quarantine).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct QuarantineStats {
+    /// Number of times an object was quarantined.
+    pub total: u64,
+    /// Number of objects currently in quarantine.
+    pub current: u64,
+}
+
+/// Metrics for a single state.
+#[derive(Clone, Debug, Default)]
+pub struct StateStats {
+    /// Number of times the state was entered.
+    pub entries: u64,
+    /// Number of times the state returned an error, either by retrying or by
+    /// completing the state machine with an error.
+    pub failures: u64,
+    /// Time spent in `State::next`.
+    pub duration: Histogram,
+}
+
+/// Distribution of durations.
+#[derive(Clone, Debug)]
+pub struct Histogram {
+    /// Upper bound in seconds and number of observations less than or equal
+    /// to it, for each bucket.
+    pub buckets: Vec<(f64, u64)>,
+    /// Sum of all observations, in seconds.
+    pub sum: f64,
+    /// Number of observations.
+    pub count: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Histogram {
+            buckets: DURATION_BUCKETS.iter().map(|bound| (*bound, 0)).collect(),
+            sum: 0.0,
+            count: 0,
+        }
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bound, count) in self.buckets.iter_mut() {
+            if seconds <= *bound {
+                *count += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+impl StateMetrics {
+    /// Create empty metrics.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Record a single execution of `state`.
+    pub(crate) fn record(&self, state: &str, duration: Duration, failed: bool) {
+        let mut states = self.states.lock().expect("State metrics lock poisoned.");
+        let stats = states.entry(short_name(state)).or_default();
+        stats.entries += 1;
+        if failed {
+            stats.failures += 1;
+        }
+        stats.duration.observe(duration);
+    }
+
+    pub(crate) fn quarantined(&self) {
+        let mut quarantine = self
+            .quarantine
+            .lock()
+            .expect("State metrics lock poisoned.");
+        quarantine.total += 1;
+        quarantine.current += 1;
+    }
+
+    pub(crate) fn released(&self) {
+        let mut quarantine = self
+            .quarantine
+            .lock()
+            .expect("State metrics lock poisoned.");
+        quarantine.current = quarantine.current.saturating_sub(1);
+    }
+
+    pub(crate) fn failed_permanently(&self) {
+        self.failed_objects.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Number of objects whose state machine stopped with an error which the
+    /// [ErrorPolicy](crate::ErrorPolicy) gave up on, or whose registration
+    /// failed for good.
+    pub fn failed_objects(&self) -> u64 {
+        self.failed_objects.load(Ordering::Relaxed)
+    }
+
+    /// Current counts of quarantined objects.
+    pub fn quarantine(&self) -> QuarantineStats {
+        *self
+            .quarantine
+            .lock()
+            .expect("State metrics lock poisoned.")
+    }
+
+    /// Current metrics, by state name.
+    pub fn snapshot(&self) -> BTreeMap<String, StateStats> {
+        self.states
+            .lock()
+            .expect("State metrics lock poisoned.")
+            .clone()
+    }
+
+    /// Render the metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let states = self.snapshot();
+        let mut out = String::new();
+        // Writing to a String cannot fail.
+        let _ = writeln!(
+            out,
+            "# HELP krator_state_entries_total Number of times a state was entered."
+        );
+        let _ = writeln!(out, "# TYPE krator_state_entries_total counter");
+        for (state, stats) in &states {
+            let _ = writeln!(
+                out,
+                "krator_state_entries_total{{state=\"{}\"}} {}",
+                state, stats.entries
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP krator_state_failures_total Number of times a state returned an error."
+        );
+        let _ = writeln!(out, "# TYPE krator_state_failures_total counter");
+        for (state, stats) in &states {
+            let _ = writeln!(
+                out,
+                "krator_state_failures_total{{state=\"{}\"}} {}",
+                state, stats.failures
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP krator_state_duration_seconds Time spent executing a state."
+        );
+        let _ = writeln!(out, "# TYPE krator_state_duration_seconds histogram");
+        for (state, stats) in &states {
+            for (bound, count) in &stats.duration.buckets {
+                let _ = writeln!(
+                    out,
+                    "krator_state_duration_seconds_bucket{{state=\"{}\",le=\"{}\"}} {}",
+                    state, bound, count
+                );
+            }
+            let _ = writeln!(
+                out,
+                "krator_state_duration_seconds_bucket{{state=\"{}\",le=\"+Inf\"}} {}",
+                state, stats.duration.count
+            );
+            let _ = writeln!(
+                out,
+                "krator_state_duration_seconds_sum{{state=\"{}\"}} {}",
+                state, stats.duration.sum
+            );
+            let _ = writeln!(
+                out,
+                "krator_state_duration_seconds_count{{state=\"{}\"}} {}",
+                state, stats.duration.count
+            );
+        }
+        let quarantine = self.quarantine();
+        let _ = writeln!(
+            out,
+            "# HELP krator_quarantined_total Number of times an object was quarantined."
+        );
+        let _ = writeln!(out, "# TYPE krator_quarantined_total counter");
+        let _ = writeln!(out, "krator_quarantined_total {}", quarantine.total);
+        let _ = writeln!(
+            out,
+            "# HELP krator_quarantined_objects Number of objects in quarantine."
+        );
+        let _ = writeln!(out, "# TYPE krator_quarantined_objects gauge");
+        let _ = writeln!(out, "krator_quarantined_objects {}", quarantine.current);
+        let _ = writeln!(
+            out,
+            "# HELP krator_failed_objects_total Number of objects whose reconciliation failed permanently."
+        );
+        let _ = writeln!(out, "# TYPE krator_failed_objects_total counter");
+        let _ = writeln!(out, "krator_failed_objects_total {}", self.failed_objects());
+        out
+    }
+}
+
+/// Per-resource counts and latencies of admission requests, shared between
+/// every webhook served. Cloning returns a handle to the same metrics.
+#[derive(Clone, Default)]
+pub struct AdmissionMetrics {
+    resources: Arc<Mutex<BTreeMap<String, AdmissionStats>>>,
+}
+
+/// Metrics for the admission requests for a single resource.
+#[derive(Clone, Debug, Default)]
+pub struct AdmissionStats {
+    /// Number of requests reviewed.
+    pub requests: u64,
+    /// Number of requests permitted.
+    pub allowed: u64,
+    /// Number of requests denied.
+    pub denied: u64,
+    /// Time taken to review a request.
+    pub duration: Histogram,
+}
+
+impl AdmissionMetrics {
+    /// Create empty metrics.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Record a single review of a request for `resource`.
+    pub(crate) fn record(&self, resource: &str, duration: Duration, allowed: bool) {
+        let mut resources = self
+            .resources
+            .lock()
+            .expect("Admission metrics lock poisoned.");
+        let stats = resources.entry(resource.to_string()).or_default();
+        stats.requests += 1;
+        if allowed {
+            stats.allowed += 1;
+        } else {
+            stats.denied += 1;
+        }
+        stats.duration.observe(duration);
+    }
+
+    /// Current metrics, by resource, such as `mooses.animals.com`.
+    pub fn snapshot(&self) -> BTreeMap<String, AdmissionStats> {
+        self.resources
+            .lock()
+            .expect("Admission metrics lock poisoned.")
+            .clone()
+    }
+
+    /// Render the metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let resources = self.snapshot();
+        let mut out = String::new();
+        // Writing to a String cannot fail.
+        let _ = writeln!(
+            out,
+            "# HELP krator_admission_requests_total Number of admission requests reviewed."
+        );
+        let _ = writeln!(out, "# TYPE krator_admission_requests_total counter");
+        for (resource, stats) in &resources {
+            let _ = writeln!(
+                out,
+                "krator_admission_requests_total{{resource=\"{}\"}} {}",
+                resource, stats.requests
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP krator_admission_decisions_total Number of admission requests by decision."
+        );
+        let _ = writeln!(out, "# TYPE krator_admission_decisions_total counter");
+        for (resource, stats) in &resources {
+            let _ = writeln!(
+                out,
+                "krator_admission_decisions_total{{resource=\"{}\",allowed=\"true\"}} {}",
+                resource, stats.allowed
+            );
+            let _ = writeln!(
+                out,
+                "krator_admission_decisions_total{{resource=\"{}\",allowed=\"false\"}} {}",
+                resource, stats.denied
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP krator_admission_duration_seconds Time taken to review an admission request."
+        );
+        let _ = writeln!(out, "# TYPE krator_admission_duration_seconds histogram");
+        for (resource, stats) in &resources {
+            for (bound, count) in &stats.duration.buckets {
+                let _ = writeln!(
+                    out,
+                    "krator_admission_duration_seconds_bucket{{resource=\"{}\",le=\"{}\"}} {}",
+                    resource, bound, count
+                );
+            }
+            let _ = writeln!(
+                out,
+                "krator_admission_duration_seconds_bucket{{resource=\"{}\",le=\"+Inf\"}} {}",
+                resource, stats.duration.count
+            );
+            let _ = writeln!(
+                out,
+                "krator_admission_duration_seconds_sum{{resource=\"{}\"}} {}",
+                resource, stats.duration.sum
+            );
+            let _ = writeln!(
+                out,
+                "krator_admission_duration_seconds_count{{resource=\"{}\"}} {}",
+                resource, stats.duration.count
+            );
+        }
+        out
+    }
+}
+
+/// Identifies the series of a controller of a Manager for a kind.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Labels {
+    controller: String,
+    group: String,
+    version: String,
+    kind: String,
+}
+
+impl Labels {
+    fn new(controller: &str, gvk: &GroupVersionKind) -> Self {
+        Labels {
+            controller: controller.to_string(),
+            group: gvk.group.clone(),
+            version: gvk.version.clone(),
+            kind: gvk.kind.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for Labels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "controller=\"{}\",group=\"{}\",version=\"{}\",kind=\"{}\"",
+            self.controller, self.group, self.version, self.kind
+        )
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    /// State metrics of each controller, labeled with its managed kind.
+    controllers: BTreeMap<Labels, StateMetrics>,
+    /// Watch events waiting to be handled, labeled with the watched kind.
+    queues: BTreeMap<Labels, u64>,
+    /// Failed watches, labeled with the watched kind.
+    watch_restarts: BTreeMap<Labels, u64>,
+    admission: Option<AdmissionMetrics>,
+}
+
+/// Metrics of every controller registered with a
+/// [Manager](crate::Manager), labeled by controller name and kind, along
+/// with the metrics of its admission webhooks. Cloning returns a handle to
+/// the same metrics.
+///
+/// With the `metrics-endpoint` feature, they can be served over HTTP with
+/// [serve](ManagerMetrics::serve), or by the Manager with
+/// [with_metrics_endpoint](crate::Manager::with_metrics_endpoint).
+#[derive(Clone, Default)]
+pub struct ManagerMetrics {
+    registry: Arc<Mutex<Registry>>,
+}
+
+/// Records the queue depth and restarts of a single watch of a controller.
+#[derive(Clone)]
+pub(crate) struct WatchMetrics {
+    metrics: ManagerMetrics,
+    labels: Labels,
+}
+
+impl WatchMetrics {
+    /// An event was queued for the controller.
+    pub(crate) fn enqueued(&self) {
+        *self
+            .metrics
+            .lock()
+            .queues
+            .entry(self.labels.clone())
+            .or_default() += 1;
+    }
+
+    /// The controller took an event from the queue.
+    pub(crate) fn dequeued(&self) {
+        let mut registry = self.metrics.lock();
+        let depth = registry.queues.entry(self.labels.clone()).or_default();
+        *depth = depth.saturating_sub(1);
+    }
+
+    /// The watch failed and is restarted.
+    pub(crate) fn restarted(&self) {
+        *self
+            .metrics
+            .lock()
+            .watch_restarts
+            .entry(self.labels.clone())
+            .or_default() += 1;
+    }
+}
+
+impl ManagerMetrics {
+    /// Create empty metrics.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<Registry> {
+        self.registry
+            .lock()
+            .expect("Manager metrics lock poisoned.")
+    }
+
+    /// Register the controller `controller` managing `gvk`, returning the
+    /// metrics to give its runtime.
+    pub(crate) fn controller(&self, controller: &str, gvk: &GroupVersionKind) -> StateMetrics {
+        self.lock()
+            .controllers
+            .entry(Labels::new(controller, gvk))
+            .or_default()
+            .clone()
+    }
+
+    /// Metrics of the watch of `gvk` by the controller `controller`.
+    pub(crate) fn watch(&self, controller: &str, gvk: &GroupVersionKind) -> WatchMetrics {
+        let labels = Labels::new(controller, gvk);
+        let mut registry = self.lock();
+        registry.queues.entry(labels.clone()).or_default();
+        registry.watch_restarts.entry(labels.clone()).or_default();
+        WatchMetrics {
+            metrics: self.clone(),
+            labels,
+        }
+    }
+
+    /// Include `metrics` of the admission webhooks.
+    pub(crate) fn set_admission(&self, metrics: AdmissionMetrics) {
+        self.lock().admission = Some(metrics);
+    }
+
+    /// Render the metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let (controllers, queues, watch_restarts, admission) = {
+            let registry = self.lock();
+            let controllers: Vec<(Labels, BTreeMap<String, StateStats>)> = registry
+                .controllers
+                .iter()
+                .map(|(labels, metrics)| (labels.clone(), metrics.snapshot()))
+                .collect();
+            (
+                controllers,
+                registry.queues.clone(),
+                registry.watch_restarts.clone(),
+                registry.admission.clone(),
+            )
+        };
+        let mut out = String::new();
+        // Writing to a String cannot fail.
+        let _ = writeln!(
+            out,
+            "# HELP krator_reconciles_total Number of times a controller entered a state."
+        );
+        let _ = writeln!(out, "# TYPE krator_reconciles_total counter");
+        for (labels, states) in &controllers {
+            for (state, stats) in states {
+                let _ = writeln!(
+                    out,
+                    "krator_reconciles_total{{{},state=\"{}\"}} {}",
+                    labels, state, stats.entries
+                );
+            }
+        }
+        let _ = writeln!(
+            out,
+            "# HELP krator_reconcile_failures_total Number of times a controller's state returned an error."
+        );
+        let _ = writeln!(out, "# TYPE krator_reconcile_failures_total counter");
+        for (labels, states) in &controllers {
+            for (state, stats) in states {
+                let _ = writeln!(
+                    out,
+                    "krator_reconcile_failures_total{{{},state=\"{}\"}} {}",
+                    labels, state, stats.failures
+                );
+            }
+        }
+        let _ = writeln!(
+            out,
+            "# HELP krator_reconcile_duration_seconds Time a controller spent executing a state."
+        );
+        let _ = writeln!(out, "# TYPE krator_reconcile_duration_seconds histogram");
+        for (labels, states) in &controllers {
+            for (state, stats) in states {
+                for (bound, count) in &stats.duration.buckets {
+                    let _ = writeln!(
+                        out,
+                        "krator_reconcile_duration_seconds_bucket{{{},state=\"{}\",le=\"{}\"}} {}",
+                        labels, state, bound, count
+                    );
+                }
+                let _ = writeln!(
+                    out,
+                    "krator_reconcile_duration_seconds_bucket{{{},state=\"{}\",le=\"+Inf\"}} {}",
+                    labels, state, stats.duration.count
+                );
+                let _ = writeln!(
+                    out,
+                    "krator_reconcile_duration_seconds_sum{{{},state=\"{}\"}} {}",
+                    labels, state, stats.duration.sum
+                );
+                let _ = writeln!(
+                    out,
+                    "krator_reconcile_duration_seconds_count{{{},state=\"{}\"}} {}",
+                    labels, state, stats.duration.count
+                );
+            }
+        }
+        let _ = writeln!(
+            out,
+            "# HELP krator_queue_depth Number of watch events waiting to be handled by a controller."
+        );
+        let _ = writeln!(out, "# TYPE krator_queue_depth gauge");
+        for (labels, depth) in &queues {
+            let _ = writeln!(out, "krator_queue_depth{{{}}} {}", labels, depth);
+        }
+        let _ = writeln!(
+            out,
+            "# HELP krator_watch_restarts_total Number of times a watch failed and was restarted."
+        );
+        let _ = writeln!(out, "# TYPE krator_watch_restarts_total counter");
+        for (labels, restarts) in &watch_restarts {
+            let _ = writeln!(
+                out,
+                "krator_watch_restarts_total{{{}}} {}",
+                labels, restarts
+            );
+        }
+        if let Some(admission) = admission {
+            out.push_str(&admission.render());
+        }
+        out
+    }
+
+    /// Serve `GET /metrics` at `address`, responding with the
+    /// [rendered](ManagerMetrics::render) metrics. Runs until the server
+    /// fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `address` cannot be bound, for example because it
+    /// is already in use.
+    #[cfg(feature = "metrics-endpoint")]
+    pub async fn serve(self, address: impl Into<std::net::SocketAddr>) -> anyhow::Result<()> {
+        use anyhow::Context;
+        use warp::Filter;
+        let metrics = warp::path!("metrics").map(move || {
+            warp::reply::with_header(self.render(), "content-type", "text/plain; version=0.0.4")
+        });
+        let address = address.into();
+        let (_, server) = warp::serve(warp::get().and(metrics))
+            .try_bind_ephemeral(address)
+            .with_context(|| format!("Failed to bind metrics endpoint to {}", address))?;
+        server.await;
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+//! Runs a single operator against several clusters.
+
+use std::sync::Arc;
+
+use kube::api::{ListParams, Resource};
+use tokio::sync::watch;
+use tracing::info;
+
+use crate::operator::Operator;
+use crate::runtime::{OperatorRuntime, ShutdownHandle};
+use crate::store::Store;
+
+/// Runs the same [Operator] against a set of clusters, with one
+/// [OperatorRuntime] per cluster. States can tell the clusters apart through
+/// [Manifest::cluster](crate::Manifest::cluster), and reach the object's
+/// cluster through [Manifest::client](crate::Manifest::client). Objects are
+/// keyed by cluster, so same-named objects in different clusters are
+/// reconciled independently.
+///
+/// The runtimes do not serve the operator's admission webhook; run an
+/// [OperatorRuntime] alongside to serve it.
+pub struct MultiClusterRuntime<O: Operator> {
+    operator: Arc<O>,
+    dyntype: Arc<<O::Manifest as Resource>::DynamicType>,
+    params: Option<ListParams>,
+    runtimes: Vec<OperatorRuntime<O>>,
+    shutdown_tx: Arc<watch::Sender<bool>>,
+    shutdown_rx: watch::Receiver<bool>,
+}
+
+impl<O: Operator> MultiClusterRuntime<O> {
+    /// Create a new runtime with optional ListParams, which apply to every
+    /// cluster.
+    pub fn new(operator: O, params: Option<ListParams>) -> Self
+    where
+        <O::Manifest as Resource>::DynamicType: Default,
+    {
+        Self::new_dynamic(operator, Default::default(), params)
+    }
+
+    /// Create a new runtime for a manifest type whose API resource is only
+    /// known at runtime. See
+    /// [OperatorRuntime::new_dynamic](crate::OperatorRuntime::new_dynamic).
+    pub fn new_dynamic(
+        operator: O,
+        dyntype: <O::Manifest as Resource>::DynamicType,
+        params: Option<ListParams>,
+    ) -> Self {
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        MultiClusterRuntime {
+            operator: Arc::new(operator),
+            dyntype: Arc::new(dyntype),
+            params,
+            runtimes: vec![],
+            shutdown_tx: Arc::new(shutdown_tx),
+            shutdown_rx,
+        }
+    }
+
+    /// Run the operator against the cluster described by `kubeconfig`,
+    /// identified by `name`.
+    pub fn with_cluster(self, name: &str, kubeconfig: &kube::Config) -> Self {
+        self.with_cluster_configured(name, kubeconfig, |runtime| runtime)
+    }
+
+    /// Run the operator against the cluster described by `kubeconfig`,
+    /// identified by `name`, customizing that cluster's runtime with
+    /// `configure`.
+    pub fn with_cluster_configured<F>(
+        mut self,
+        name: &str,
+        kubeconfig: &kube::Config,
+        configure: F,
+    ) -> Self
+    where
+        F: FnOnce(OperatorRuntime<O>) -> OperatorRuntime<O>,
+    {
+        let runtime = OperatorRuntime::from_parts(
+            crate::runtime::client_from_config(kubeconfig),
+            Arc::clone(&self.operator),
+            Arc::clone(&self.dyntype),
+            self.params.clone(),
+            Store::new().for_cluster(name),
+        )
+        .with_cluster(name)
+        .shutdown_on(self.shutdown_rx.clone());
+        self.runtimes.push(configure(runtime));
+        self
+    }
+
+    /// Obtain a handle which can be used to gracefully shut down every
+    /// cluster's runtime while `start` is running.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle::new(Arc::clone(&self.shutdown_tx))
+    }
+
+    /// Start the operator in every cluster. Blocks until all runtimes have
+    /// shut down.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error from any cluster's runtime, for example when
+    /// the operator's [on_start](Operator::on_start) hook fails for it. The
+    /// other clusters are shut down.
+    pub async fn start(&mut self) -> anyhow::Result<()> {
+        info!(
+            clusters = self.runtimes.len(),
+            "Starting MultiClusterRuntime."
+        );
+        let shutdown_tx = &self.shutdown_tx;
+        let results =
+            futures::future::join_all(self.runtimes.iter_mut().map(|runtime| async move {
+                let result = runtime.start().await;
+                if result.is_err() {
+                    let _ = shutdown_tx.send(true);
+                }
+                result
+            }))
+            .await;
+        results.into_iter().collect()
+    }
+}
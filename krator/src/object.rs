@@ -1,28 +1,73 @@
 use kube::api::{Resource, ResourceExt};
 
-#[derive(Hash, Eq, PartialEq, Clone)]
+/// Identifies an object by its namespace, if it is namespaced, and its
+/// name, along with the cluster it lives in when running as part of a
+/// [MultiClusterRuntime](crate::MultiClusterRuntime).
+#[derive(Hash, Eq, PartialEq, Ord, PartialOrd, Clone, Debug)]
 pub struct ObjectKey {
+    cluster: Option<String>,
     namespace: Option<String>,
     name: String,
 }
 
 impl ObjectKey {
+    /// Key of the object `name` in `namespace`, or of the cluster-scoped
+    /// object `name` if `namespace` is `None`.
     pub fn new(namespace: Option<String>, name: String) -> Self {
-        ObjectKey { namespace, name }
+        ObjectKey {
+            cluster: None,
+            namespace,
+            name,
+        }
+    }
+
+    /// Key of the same object in the cluster named `cluster`, or outside of
+    /// a [MultiClusterRuntime](crate::MultiClusterRuntime) if `None`.
+    pub fn with_cluster(mut self, cluster: Option<String>) -> Self {
+        self.cluster = cluster;
+        self
     }
 
+    /// Name of the cluster the object lives in, when running as part of a
+    /// [MultiClusterRuntime](crate::MultiClusterRuntime).
+    pub fn cluster(&self) -> Option<&str> {
+        self.cluster.as_deref()
+    }
+
+    /// Name of the object.
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// Namespace of the object, if it is namespaced.
     pub fn namespace(&self) -> Option<&String> {
         self.namespace.as_ref()
     }
+
+    /// Assign the key to one of `count` shards. Uses FNV-1a so that every
+    /// replica agrees on the assignment, regardless of how it was built. The
+    /// cluster is not taken into account, since the objects of each cluster
+    /// are sharded by the runtime of that cluster.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is zero.
+    pub fn shard(&self, count: u64) -> u64 {
+        assert!(count > 0, "Objects cannot be assigned to zero shards.");
+        let mut hash: u64 = 0xcbf29ce484222325;
+        let namespace = self.namespace.as_deref().unwrap_or_default();
+        for byte in namespace.bytes().chain(Some(b'/')).chain(self.name.bytes()) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        hash % count
+    }
 }
 
 impl<R: Resource> From<&R> for ObjectKey {
     fn from(object: &R) -> ObjectKey {
         ObjectKey {
+            cluster: None,
             namespace: object.namespace(),
             name: object.name(),
         }
@@ -59,4 +104,86 @@ pub trait ObjectStatus {
     /// This can mean different things for different resources and will be used to emit
     /// an error if the state machine does not exit gracefully.
     fn failed(e: &str) -> Self;
+    /// Produce a status which marks an object as failed with the supplied
+    /// error, used instead of [failed](ObjectStatus::failed) when a state
+    /// machine exits with an error. Override it to derive machine-readable
+    /// reasons or condition types from the error. Defaults to calling
+    /// `failed` with the debug representation of the error.
+    fn failed_with(error: &StateError) -> Self
+    where
+        Self: Sized,
+    {
+        Self::failed(&format!("{:?}", error.error))
+    }
+}
+
+/// An error which stopped an object's state machine.
+#[derive(Debug)]
+pub struct StateError<'a> {
+    /// The error returned by the state.
+    pub error: &'a anyhow::Error,
+    /// Type name of the state which returned the error.
+    pub state: &'static str,
+    /// Number of consecutive failed attempts of the state, including the last.
+    pub attempts: u32,
+}
+
+impl<'a> StateError<'a> {
+    /// The error followed by its sources, outermost first.
+    pub fn chain(&self) -> anyhow::Chain<'a> {
+        self.error.chain()
+    }
+
+    /// The innermost source of the error.
+    pub fn root_cause(&self) -> &'a (dyn std::error::Error + 'static) {
+        self.error.root_cause()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(namespace: Option<&str>, name: &str) -> ObjectKey {
+        ObjectKey::new(namespace.map(String::from), name.to_string())
+    }
+
+    // Replicas built from different versions must keep agreeing on the
+    // assignment, so these values must never change.
+    #[test]
+    fn shard_is_stable() {
+        let cases = [
+            (Some("default"), "nginx", 0xd41257455e0f9375, 0, 5),
+            (Some("default"), "redis", 0x987e9a54764ffba6, 2, 6),
+            (Some("herd"), "moose-3", 0x39533147b8edf496, 1, 6),
+            (Some("kube-system"), "coredns", 0x7cef5141c294ab37, 0, 7),
+            (None, "moose", 0x0565e7b6f39a6cfb, 0, 11),
+        ];
+        for (namespace, name, hash, of_three, of_sixteen) in cases {
+            let key = key(namespace, name);
+            assert_eq!(key.shard(u64::MAX), hash, "{:?}", key);
+            assert_eq!(key.shard(3), of_three, "{:?}", key);
+            assert_eq!(key.shard(16), of_sixteen, "{:?}", key);
+        }
+    }
+
+    #[test]
+    fn shard_distinguishes_namespace_from_name() {
+        assert_ne!(
+            key(Some("a"), "b").shard(u64::MAX),
+            key(None, "a/b").shard(u64::MAX)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "zero shards")]
+    fn rejects_zero_shards() {
+        key(Some("default"), "moose").shard(0);
+    }
+
+    #[test]
+    fn single_shard_owns_everything() {
+        assert_eq!(key(Some("default"), "nginx").shard(1), 0);
+        assert_eq!(key(None, "moose").shard(1), 0);
+    }
 }
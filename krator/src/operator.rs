@@ -1,6 +1,8 @@
 use serde::Serialize;
 use std::fmt::Debug;
+use std::sync::Arc;
 
+use crate::error_policy::{DefaultErrorPolicy, ErrorPolicy};
 use crate::object::{ObjectState, ObjectStatus};
 use crate::state::{SharedState, State};
 use crate::Manifest;
@@ -10,6 +12,11 @@ use kube::api::Resource;
 use crate::admission::AdmissionTls;
 
 /// Trait alias for types which can be watched.
+///
+/// Both namespaced and cluster-scoped resources (such as `Node`,
+/// `Namespace` or cluster-scoped CRDs) are watchable. Objects without a
+/// namespace are accessed through `Api::all`, and are keyed in the
+/// [Store](crate::Store) with a namespace of `None`.
 pub trait Watchable:
     Resource<DynamicType = ()> + serde::de::DeserializeOwned + Clone + Send + 'static
 {
@@ -20,11 +27,69 @@ impl<T> Watchable for T where
 {
 }
 
+/// Determines what happens to an object once its `DeletedState` has completed
+/// and [deregistration_hook](Operator::deregistration_hook) has run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum DeregistrationPolicy {
+    /// Delete the object with a grace period of zero.
+    #[default]
+    Delete,
+    /// Only remove the supplied finalizer from the object, and let Kubernetes
+    /// finish deleting it once any other finalizers are gone.
+    RemoveFinalizerOnly(String),
+    /// Leave the object alone.
+    None,
+}
+
+/// Where the status reported by states is written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StatusMode {
+    /// Patch the `status` subresource.
+    #[default]
+    Subresource,
+    /// Patch the `status` field of the object itself, for custom resources
+    /// whose definition does not enable the status subresource.
+    Inline,
+    /// Check the CustomResourceDefinition when the runtime starts and use
+    /// the subresource if it is enabled. Resources without a definition,
+    /// such as built-in types, use the subresource.
+    Detect,
+}
+
+/// How status updates are encoded. Ignored when status is updated with
+/// server-side apply.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PatchStrategy {
+    /// JSON merge patch (RFC 7386).
+    #[default]
+    Merge,
+    /// Strategic merge patch. Only supported for built-in resource types.
+    Strategic,
+    /// JSON Patch (RFC 6902). The status is translated into one operation per
+    /// field: nested objects are descended into, `null` removes a field and
+    /// any other value is added, replacing what was there. Objects which are
+    /// descended into, including `status` itself, and fields which are
+    /// removed must already exist, otherwise the whole patch is rejected.
+    Json,
+}
+
 #[async_trait::async_trait]
 /// Interface for creating an operator.
 pub trait Operator: 'static + Sync + Send {
     /// Type representing the specification of the object in the Kubernetes API.
-    type Manifest: Watchable + Serialize + Debug + Sync + Default + std::marker::Unpin;
+    ///
+    /// Usually a [Watchable] type. Types whose API resource is only known at
+    /// runtime, such as `DynamicObject`, can be used with
+    /// [OperatorRuntime::new_dynamic](crate::OperatorRuntime::new_dynamic).
+    type Manifest: Resource
+        + serde::de::DeserializeOwned
+        + Serialize
+        + Clone
+        + Send
+        + Sync
+        + Debug
+        + std::marker::Unpin
+        + 'static;
 
     /// Type describing the status of the object.
     type Status: ObjectStatus + Send;
@@ -47,7 +112,50 @@ pub trait Operator: 'static + Sync + Send {
     /// Create a reference to state shared between state machines.
     async fn shared_state(&self) -> SharedState<<Self::ObjectState as ObjectState>::SharedState>;
 
-    /// Called before the state machine is run.
+    /// Called once before any objects are watched, for example to ensure
+    /// CRDs exist or to warm caches. If it fails, the runtime does not start.
+    async fn on_start(&self, _client: kube::Client) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Called with every watch event before it is dispatched, for example to
+    /// record metrics or audit events. Use
+    /// [PrettyEvent](crate::util::PrettyEvent) for a compact summary of the
+    /// event. Events are observed even when they are subsequently dropped,
+    /// for example because the runtime is paused or shutting down.
+    async fn on_event(&self, _event: &kube_runtime::watcher::Event<Self::Manifest>) {}
+
+    /// Decide whether to start a state machine for an object, for example
+    /// based on an annotation or a class field. Objects for which this
+    /// returns `false` never get a task. Once a task has been started, it
+    /// keeps receiving updates for the object regardless of this predicate.
+    fn should_reconcile(&self, _manifest: &Self::Manifest) -> bool {
+        true
+    }
+
+    /// Only deliver updates of an object to its state machine when
+    /// `metadata.generation` changes, which the API server does for changes
+    /// of the spec and when deletion is requested, but not for status or
+    /// metadata-only changes. Deletions are always delivered, and objects
+    /// without a generation receive every update. Defaults to `false`.
+    fn generation_changes_only(&self) -> bool {
+        false
+    }
+
+    /// How long to wait after an object changed before delivering its
+    /// manifest to the state machine, so that a burst of updates wakes it
+    /// once with the final version. Deletions arriving meanwhile are
+    /// delivered once the window has passed. Defaults to
+    /// `None`, which delivers every version as soon as the state machine
+    /// picks it up.
+    fn manifest_debounce(&self) -> Option<std::time::Duration> {
+        None
+    }
+
+    /// Called before the state machine is run. If it returns an error, the
+    /// state machine is not run, unless the
+    /// [error_policy](Operator::error_policy) retries the hook or enters a
+    /// state.
     async fn registration_hook(
         &self,
         mut _manifest: Manifest<Self::Manifest>,
@@ -55,12 +163,22 @@ pub trait Operator: 'static + Sync + Send {
         Ok(())
     }
 
+    /// How long [registration_hook](Operator::registration_hook) may run
+    /// before it is cancelled and fails, which is handled by the
+    /// [error_policy](Operator::error_policy) like any other failure of the
+    /// hook. Defaults to `None`, which waits for it indefinitely.
+    fn registration_hook_timeout(&self) -> Option<std::time::Duration> {
+        None
+    }
+
     #[cfg(feature = "admission-webhook")]
     /// Invoked when object is created or modified. Can mutate the and / or deny the request.
+    /// Return an [AdmissionResult](crate::admission::AdmissionResult) with `.into()`, or add
+    /// warnings with [with_warning](crate::admission::AdmissionResult::with_warning).
     async fn admission_hook(
         &self,
         manifest: Self::Manifest,
-    ) -> crate::admission::AdmissionResult<Self::Manifest>;
+    ) -> crate::admission::AdmissionVerdict<Self::Manifest>;
 
     #[cfg(feature = "admission-webhook")]
     /// Gets called by the operator if the admission-webhook feature is enabled. The function should
@@ -69,6 +187,79 @@ pub trait Operator: 'static + Sync + Send {
     /// to convert the Kubernetes secret an [AdmissionTls]
     async fn admission_hook_tls(&self) -> anyhow::Result<AdmissionTls>;
 
+    #[cfg(feature = "admission-webhook")]
+    /// How often [admission_hook_tls](Operator::admission_hook_tls) is called
+    /// again to pick up a renewed certificate, for example after cert-manager
+    /// rotated the Secret. The server keeps listening while the certificate
+    /// is swapped. Returns `None` to only load the certificate at startup.
+    /// Defaults to one minute.
+    fn admission_hook_tls_refresh(&self) -> Option<std::time::Duration> {
+        Some(std::time::Duration::from_secs(60))
+    }
+
+    /// Decides how the runtime recovers when a state machine exits with an
+    /// error, a hook fails, or a write to the object fails. Defaults to
+    /// [DefaultErrorPolicy].
+    fn error_policy(&self) -> Arc<dyn ErrorPolicy<Self::ObjectState>> {
+        Arc::new(DefaultErrorPolicy::default())
+    }
+
+    /// Called whenever a state machine exits with an error, a status patch
+    /// fails, or another hook returns an error. Errors are always logged as
+    /// well.
+    async fn error_hook(&self, mut _manifest: Manifest<Self::Manifest>, _error: &anyhow::Error) {}
+
+    /// Called when the object's state machine stopped with an error which
+    /// the [error_policy](Operator::error_policy) gave up on, or the
+    /// registration hook failed for good, for example to alert on it. The
+    /// state machine is not run again until the runtime restarts, though the
+    /// object is still deregistered once deleted. [error_hook](Operator::error_hook) is called with the error
+    /// as well.
+    async fn failure_hook(&self, mut _manifest: Manifest<Self::Manifest>, _error: &anyhow::Error) {}
+
+    /// Called when the runtime shuts down or loses leadership while the
+    /// object's state machine is running. Use it to persist whatever
+    /// [initialize_object_state](Operator::initialize_object_state) needs for
+    /// the next leader to resume, for example in the object's status.
+    async fn handoff_hook(
+        &self,
+        mut _manifest: Manifest<Self::Manifest>,
+        _object_state: &mut Self::ObjectState,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Determines where status is written. Defaults to the status
+    /// subresource.
+    fn status_mode(&self) -> StatusMode {
+        StatusMode::Subresource
+    }
+
+    /// Determines how status updates are encoded. Defaults to JSON merge
+    /// patch.
+    fn status_patch_strategy(&self) -> PatchStrategy {
+        PatchStrategy::Merge
+    }
+
+    /// Determines what krator does with the object after deregistration.
+    /// Defaults to force deleting it.
+    fn deregistration_policy(&self) -> DeregistrationPolicy {
+        DeregistrationPolicy::Delete
+    }
+
+    /// Called once the `DeletedState` has completed, while the object still
+    /// exists and before krator applies the
+    /// [deregistration_policy](Operator::deregistration_policy). Use it for
+    /// cleanup which must finish before the object can go away. If it returns
+    /// an error, it is retried with exponential backoff.
+    async fn finalize_hook(
+        &self,
+        mut _manifest: Manifest<Self::Manifest>,
+        _object_state: &mut Self::ObjectState,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     /// Called before the state machine is run.
     async fn deregistration_hook(
         &self,
@@ -76,4 +267,12 @@ pub trait Operator: 'static + Sync + Send {
     ) -> anyhow::Result<()> {
         Ok(())
     }
+
+    /// How long [deregistration_hook](Operator::deregistration_hook) may run
+    /// before it is cancelled and fails, which is handled by the
+    /// [error_policy](Operator::error_policy) like any other failure of the
+    /// hook. Defaults to `None`, which waits for it indefinitely.
+    fn deregistration_hook_timeout(&self) -> Option<std::time::Duration> {
+        None
+    }
 }
@@ -0,0 +1,73 @@
+//! Readiness of the objects owned by an operator's objects.
+
+use serde::Serialize;
+
+/// Aggregated readiness of the objects owned by an object, for inclusion in
+/// its status. Built by [Manifest::owned_summary](crate::Manifest::owned_summary).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChildSummary {
+    /// Number of owned objects.
+    pub total: usize,
+    /// Number of owned objects which are ready.
+    pub ready: usize,
+    /// Owned objects which are not ready, ordered by namespace and name.
+    pub failing: Vec<FailingChild>,
+}
+
+/// An owned object which is not ready.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailingChild {
+    /// Namespace of the object, if it is namespaced.
+    pub namespace: Option<String>,
+    /// Name of the object.
+    pub name: String,
+    /// Why the object is not ready.
+    pub message: String,
+}
+
+impl ChildSummary {
+    /// Whether every owned object is ready.
+    pub fn all_ready(&self) -> bool {
+        self.ready == self.total
+    }
+
+    /// Human readable summary, such as `2/3 ready; web-1: Back-off restarting`.
+    pub fn message(&self) -> String {
+        let mut message = format!("{}/{} ready", self.ready, self.total);
+        for child in &self.failing {
+            message.push_str(&format!("; {}: {}", child.name, child.message));
+        }
+        message
+    }
+}
+
+/// Readiness according to the `Ready` or `Available` condition in
+/// `status.conditions`, as reported by most built-in types. Objects with
+/// neither condition, such as ConfigMaps, are ready.
+pub fn ready_condition<R: Serialize>(child: &R) -> Result<(), String> {
+    let value = serde_json::to_value(child).map_err(|error| error.to_string())?;
+    let conditions = match value.pointer("/status/conditions") {
+        Some(serde_json::Value::Array(conditions)) => conditions,
+        _ => return Ok(()),
+    };
+    let condition = conditions.iter().find(|condition| {
+        matches!(
+            condition.get("type").and_then(|t| t.as_str()),
+            Some("Ready") | Some("Available")
+        )
+    });
+    match condition {
+        None => Ok(()),
+        Some(condition) if condition.get("status").and_then(|s| s.as_str()) == Some("True") => {
+            Ok(())
+        }
+        Some(condition) => Err(["message", "reason"]
+            .iter()
+            .filter_map(|field| condition.get(field).and_then(|v| v.as_str()))
+            .find(|text| !text.is_empty())
+            .unwrap_or("Not ready")
+            .to_string()),
+    }
+}
@@ -0,0 +1,212 @@
+//! Parking objects whose state machines keep failing.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::Notify;
+
+use crate::metrics::StateMetrics;
+use crate::object::ObjectKey;
+
+/// Objects which were quarantined after failing too many times in a row,
+/// see [with_quarantine](crate::OperatorRuntime::with_quarantine).
+/// Quarantined objects are not retried until they are requeued, the spec of
+/// the object changes, the object is deleted, or the runtime restarts.
+/// Cloning returns a handle to the same set.
+#[derive(Clone, Default)]
+pub struct QuarantineHandle {
+    objects: Arc<Mutex<BTreeMap<ObjectKey, Entry>>>,
+}
+
+struct Entry {
+    failures: u32,
+    error: String,
+    since: Instant,
+    release: Arc<Notify>,
+}
+
+/// An object in quarantine.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantinedObject {
+    /// Cluster of the object, when running as part of a
+    /// [MultiClusterRuntime](crate::MultiClusterRuntime).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cluster: Option<String>,
+    /// Namespace of the object, if it is namespaced.
+    pub namespace: Option<String>,
+    /// Name of the object.
+    pub name: String,
+    /// Consecutive failures before the object was quarantined.
+    pub failures: u32,
+    /// The error of the last failure.
+    pub error: String,
+    /// Time since the object was quarantined.
+    #[serde(serialize_with = "serialize_seconds", rename = "secondsInQuarantine")]
+    pub time_in_quarantine: Duration,
+}
+
+fn serialize_seconds<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+impl QuarantineHandle {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Every quarantined object, ordered by cluster, namespace and name.
+    pub fn list(&self) -> Vec<QuarantinedObject> {
+        self.lock()
+            .iter()
+            .map(|(key, entry)| QuarantinedObject {
+                cluster: key.cluster().map(str::to_string),
+                namespace: key.namespace().cloned(),
+                name: key.name().to_string(),
+                failures: entry.failures,
+                error: entry.error.clone(),
+                time_in_quarantine: entry.since.elapsed(),
+            })
+            .collect()
+    }
+
+    /// Whether the object is quarantined, in any cluster.
+    pub fn contains(&self, namespace: Option<&str>, name: &str) -> bool {
+        self.lock().keys().any(|key| matches(key, namespace, name))
+    }
+
+    /// Release the object from quarantine, in every cluster it is
+    /// quarantined in, and retry it right away. If it fails again, it is
+    /// quarantined again. Returns `false` if the object was not quarantined.
+    pub fn requeue(&self, namespace: Option<&str>, name: &str) -> bool {
+        let mut objects = self.lock();
+        let keys: Vec<ObjectKey> = objects
+            .keys()
+            .filter(|key| matches(key, namespace, name))
+            .cloned()
+            .collect();
+        for key in &keys {
+            if let Some(entry) = objects.remove(key) {
+                entry.release.notify_one();
+            }
+        }
+        !keys.is_empty()
+    }
+
+    /// Quarantine the object. It stays quarantined until it is requeued or
+    /// the returned guard is dropped.
+    pub(crate) fn enter(
+        &self,
+        key: &ObjectKey,
+        failures: u32,
+        error: &str,
+        metrics: Option<StateMetrics>,
+    ) -> Quarantined {
+        let release = Arc::new(Notify::new());
+        self.lock().insert(
+            key.clone(),
+            Entry {
+                failures,
+                error: error.to_string(),
+                since: Instant::now(),
+                release: Arc::clone(&release),
+            },
+        );
+        if let Some(ref metrics) = metrics {
+            metrics.quarantined();
+        }
+        Quarantined {
+            handle: self.clone(),
+            key: key.clone(),
+            release,
+            metrics,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<BTreeMap<ObjectKey, Entry>> {
+        self.objects.lock().expect("Quarantine lock poisoned.")
+    }
+}
+
+fn matches(key: &ObjectKey, namespace: Option<&str>, name: &str) -> bool {
+    key.namespace().map(String::as_str) == namespace && key.name() == name
+}
+
+/// An object's stay in quarantine, which ends when the guard is dropped.
+pub(crate) struct Quarantined {
+    handle: QuarantineHandle,
+    key: ObjectKey,
+    release: Arc<Notify>,
+    metrics: Option<StateMetrics>,
+}
+
+impl Quarantined {
+    /// Resolves once the object is requeued.
+    pub(crate) async fn released(&self) {
+        self.release.notified().await
+    }
+}
+
+impl Drop for Quarantined {
+    fn drop(&mut self) {
+        if let Some(ref metrics) = self.metrics {
+            metrics.released();
+        }
+        let mut objects = self.handle.lock();
+        // The object may have been requeued and quarantined again since.
+        if let Some(entry) = objects.get(&self.key) {
+            if Arc::ptr_eq(&entry.release, &self.release) {
+                objects.remove(&self.key);
+            }
+        }
+    }
+}
+
+/// Quarantine settings of a runtime.
+#[derive(Clone)]
+pub(crate) struct Quarantine {
+    pub(crate) handle: QuarantineHandle,
+    /// Consecutive failures after which an object is quarantined.
+    pub(crate) threshold: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(cluster: &str) -> ObjectKey {
+        ObjectKey::new(Some("default".to_string()), "moose".to_string())
+            .with_cluster(Some(cluster.to_string()))
+    }
+
+    #[test]
+    fn quarantines_same_named_objects_per_cluster() {
+        let handle = QuarantineHandle::new();
+        let east = handle.enter(&key("east"), 3, "failed", None);
+        let west = handle.enter(&key("west"), 5, "failed", None);
+
+        let quarantined = handle.list();
+        assert_eq!(quarantined.len(), 2);
+        assert_eq!(quarantined[0].cluster.as_deref(), Some("east"));
+        assert_eq!(quarantined[0].failures, 3);
+        assert_eq!(quarantined[1].cluster.as_deref(), Some("west"));
+        assert_eq!(quarantined[1].failures, 5);
+
+        // Leaving the quarantine in one cluster keeps the other one parked.
+        drop(east);
+        let quarantined = handle.list();
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(quarantined[0].cluster.as_deref(), Some("west"));
+        assert!(handle.contains(Some("default"), "moose"));
+
+        assert!(handle.requeue(Some("default"), "moose"));
+        assert!(handle.list().is_empty());
+        drop(west);
+    }
+}
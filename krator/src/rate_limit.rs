@@ -0,0 +1,375 @@
+//! Client-side limiting of the requests made to the API server.
+
+use std::convert::TryFrom;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use http::{Request, Response, StatusCode};
+use hyper::Body;
+use kube::Client;
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::util::Backoff;
+
+/// Limits the rate of requests made through a client, like the QPS and burst
+/// settings of client-go, and retries requests the API server rejected with
+/// `429 Too Many Requests` after the delay it asked for.
+///
+/// Pass the client to [OperatorRuntime::from_client](crate::OperatorRuntime::from_client)
+/// or [Manager::from_client](crate::Manager::from_client) to limit every
+/// request krator makes, including watches, status patches, and those made
+/// through [Manifest::client](crate::Manifest::client).
+///
+/// ```no_run
+/// # use krator::RateLimit;
+/// # async fn example() -> anyhow::Result<()> {
+/// let config = kube::Config::infer().await?;
+/// let client = RateLimit::new(20.0, 40).client(&config)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RateLimit {
+    /// Sustained number of requests per second. Not limited if zero or less.
+    pub qps: f64,
+    /// Number of requests which may be made at once before being limited to
+    /// `qps`.
+    pub burst: u32,
+    /// Number of times a request rejected with `429 Too Many Requests` is
+    /// retried before the response is returned.
+    pub max_retries: u32,
+    /// Delay before retrying a rejected request which does not carry a
+    /// `Retry-After` header, given the number of retries so far.
+    pub backoff: Backoff,
+    /// Upper bound on the delay requested with `Retry-After`.
+    pub max_retry_after: Duration,
+}
+
+impl Default for RateLimit {
+    /// The defaults of client-go: 5 requests per second with bursts of 10.
+    fn default() -> Self {
+        RateLimit::new(5.0, 10)
+    }
+}
+
+impl RateLimit {
+    /// Limit requests to `qps` per second, with bursts of up to `burst`.
+    pub fn new(qps: f64, burst: u32) -> Self {
+        RateLimit {
+            qps,
+            burst,
+            max_retries: 5,
+            backoff: Default::default(),
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+
+    /// Create a client from `kubeconfig` whose requests are limited. Clones
+    /// of the client share the limit.
+    pub fn client(&self, kubeconfig: &kube::Config) -> kube::Result<Client> {
+        let inner = Client::try_from(kubeconfig.clone())?;
+        let service = Limited::new(inner, self);
+        Ok(Client::new(service, kubeconfig.default_namespace.clone()))
+    }
+}
+
+/// Token bucket holding the requests which may be made right away.
+struct Bucket {
+    /// Negative when requests are waiting for tokens.
+    tokens: f64,
+    last: Instant,
+}
+
+#[derive(Clone)]
+struct Limited {
+    inner: Client,
+    limit: Arc<RateLimit>,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+impl Limited {
+    fn new(inner: Client, limit: &RateLimit) -> Self {
+        Limited {
+            inner,
+            limit: Arc::new(limit.clone()),
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: limit.burst.max(1) as f64,
+                last: Instant::now(),
+            })),
+        }
+    }
+
+    /// Take a token, waiting until one is available.
+    async fn acquire(&self) {
+        if self.limit.qps <= 0.0 {
+            return;
+        }
+        let wait = {
+            let mut bucket = self.bucket.lock().expect("Rate limit lock poisoned.");
+            let now = Instant::now();
+            let refill = now.duration_since(bucket.last).as_secs_f64() * self.limit.qps;
+            bucket.tokens = (bucket.tokens + refill).min(self.limit.burst.max(1) as f64);
+            bucket.last = now;
+            // Reserve the token now so that waiting requests are served in order.
+            bucket.tokens -= 1.0;
+            if bucket.tokens >= 0.0 {
+                None
+            } else {
+                Some(Duration::from_secs_f64(-bucket.tokens / self.limit.qps))
+            }
+        };
+        if let Some(wait) = wait {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    async fn send(self, request: Request<Body>) -> Result<Response<Body>, kube::Error> {
+        let (parts, body) = request.into_parts();
+        // Buffered so that the request can be sent again.
+        let body = hyper::body::to_bytes(body)
+            .await
+            .map_err(kube::Error::HyperError)?;
+        let mut retries = 0;
+        loop {
+            self.acquire().await;
+            let mut request = Request::new(Body::from(body.clone()));
+            *request.method_mut() = parts.method.clone();
+            *request.uri_mut() = parts.uri.clone();
+            *request.version_mut() = parts.version;
+            *request.headers_mut() = parts.headers.clone();
+            let response = self.inner.send(request).await?;
+            if response.status() != StatusCode::TOO_MANY_REQUESTS
+                || retries >= self.limit.max_retries
+            {
+                return Ok(response);
+            }
+            retries += 1;
+            let delay = match retry_after(&response) {
+                Some(delay) => delay.min(self.limit.max_retry_after),
+                None => self.limit.backoff.delay(retries),
+            };
+            warn!(
+                method = %parts.method,
+                uri = %parts.uri,
+                retries,
+                ?delay,
+                "Request throttled by the API server, retrying."
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// The delay requested by the `Retry-After` header of `response`, in seconds.
+fn retry_after(response: &Response<Body>) -> Option<Duration> {
+    let seconds = response
+        .headers()
+        .get(http::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+impl tower::Service<Request<Body>> for Limited {
+    type Response = Response<Body>;
+    type Error = kube::Error;
+    type Future = BoxFuture<'static, Result<Response<Body>, kube::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        self.clone().send(request).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Answers requests with the queued responses, or `200 OK` once they run
+    /// out, recording when each request arrived.
+    #[derive(Clone, Default)]
+    struct Server {
+        responses: Arc<Mutex<VecDeque<Response<Body>>>>,
+        received: Arc<Mutex<Vec<Instant>>>,
+    }
+
+    impl Server {
+        fn respond(self, status: StatusCode, retry_after: Option<&str>) -> Self {
+            let mut response = Response::builder().status(status);
+            if let Some(retry_after) = retry_after {
+                response = response.header(http::header::RETRY_AFTER, retry_after);
+            }
+            self.responses
+                .lock()
+                .unwrap()
+                .push_back(response.body(Body::empty()).unwrap());
+            self
+        }
+
+        /// When each request arrived, relative to `start`.
+        fn received(&self, start: Instant) -> Vec<Duration> {
+            self.received
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|at| at.duration_since(start))
+                .collect()
+        }
+    }
+
+    impl tower::Service<Request<Body>> for Server {
+        type Response = Response<Body>;
+        type Error = std::convert::Infallible;
+        type Future = futures::future::Ready<Result<Response<Body>, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _request: Request<Body>) -> Self::Future {
+            self.received.lock().unwrap().push(Instant::now());
+            let response = self.responses.lock().unwrap().pop_front();
+            futures::future::ready(Ok(response.unwrap_or_else(|| Response::new(Body::empty()))))
+        }
+    }
+
+    fn request() -> Request<Body> {
+        Request::get("/api/v1/namespaces/default/pods")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    fn limited(limit: RateLimit, server: &Server) -> Limited {
+        Limited::new(Client::new(server.clone(), "default"), &limit)
+    }
+
+    #[tokio::test]
+    async fn throttles_after_burst() {
+        tokio::time::pause();
+        let server = Server::default();
+        let start = Instant::now();
+        let limited = limited(RateLimit::new(2.0, 2), &server);
+        for _ in 0..4 {
+            let response = limited.clone().send(request()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        assert_eq!(
+            server.received(start),
+            vec![
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::from_millis(500),
+                Duration::from_millis(1000),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn refills_while_idle() {
+        tokio::time::pause();
+        let server = Server::default();
+        let start = Instant::now();
+        let limited = limited(RateLimit::new(1.0, 2), &server);
+        limited.clone().send(request()).await.unwrap();
+        limited.clone().send(request()).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        limited.clone().send(request()).await.unwrap();
+        limited.clone().send(request()).await.unwrap();
+        limited.clone().send(request()).await.unwrap();
+        assert_eq!(
+            server.received(start),
+            vec![
+                Duration::ZERO,
+                Duration::ZERO,
+                // The bucket holds at most `burst` tokens.
+                Duration::from_secs(10),
+                Duration::from_secs(10),
+                Duration::from_secs(11),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn retries_after_requested_delay() {
+        tokio::time::pause();
+        let server = Server::default().respond(StatusCode::TOO_MANY_REQUESTS, Some("3"));
+        let start = Instant::now();
+        let limited = limited(RateLimit::new(0.0, 0), &server);
+        let response = limited.send(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            server.received(start),
+            vec![Duration::ZERO, Duration::from_secs(3)]
+        );
+    }
+
+    #[tokio::test]
+    async fn caps_requested_delay() {
+        tokio::time::pause();
+        let server = Server::default().respond(StatusCode::TOO_MANY_REQUESTS, Some("3600"));
+        let start = Instant::now();
+        let limit = RateLimit {
+            max_retry_after: Duration::from_secs(60),
+            ..RateLimit::new(0.0, 0)
+        };
+        limited(limit, &server).send(request()).await.unwrap();
+        assert_eq!(
+            server.received(start),
+            vec![Duration::ZERO, Duration::from_secs(60)]
+        );
+    }
+
+    #[tokio::test]
+    async fn backs_off_without_retry_after() {
+        tokio::time::pause();
+        let server = Server::default()
+            .respond(StatusCode::TOO_MANY_REQUESTS, None)
+            .respond(StatusCode::TOO_MANY_REQUESTS, Some("soon"));
+        let start = Instant::now();
+        let limit = RateLimit {
+            backoff: Backoff {
+                initial: Duration::from_secs(1),
+                max: Duration::from_secs(30),
+                multiplier: 2.0,
+                jitter: 0.0,
+            },
+            ..RateLimit::new(0.0, 0)
+        };
+        limited(limit, &server).send(request()).await.unwrap();
+        assert_eq!(
+            server.received(start),
+            vec![
+                Duration::ZERO,
+                Duration::from_secs(1),
+                Duration::from_secs(3)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn returns_response_after_max_retries() {
+        tokio::time::pause();
+        let server = Server::default()
+            .respond(StatusCode::TOO_MANY_REQUESTS, Some("1"))
+            .respond(StatusCode::TOO_MANY_REQUESTS, Some("1"))
+            .respond(StatusCode::TOO_MANY_REQUESTS, Some("1"));
+        let limit = RateLimit {
+            max_retries: 2,
+            ..RateLimit::new(0.0, 0)
+        };
+        let response = limited(limit, &server).send(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(server.received.lock().unwrap().len(), 3);
+    }
+}
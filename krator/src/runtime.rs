@@ -1,35 +1,65 @@
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
+use std::future::Future;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
-use futures::{StreamExt, TryStreamExt};
+use anyhow::Context;
+
+use futures::{FutureExt, StreamExt};
+use tokio::sync::mpsc::error::TrySendError;
 use tokio::sync::mpsc::Sender;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock, Semaphore};
 use tracing::{debug, error, info, trace, warn};
 
 use kube::{
-    api::{Api, ListParams, Resource, ResourceExt},
+    api::{
+        Api, ApiResource, DynamicObject, GroupVersionKind, ListParams, Patch, PatchParams,
+        Resource, ResourceExt,
+    },
     Client,
 };
+use kube_runtime::events::Reporter;
 use kube_runtime::watcher;
 use kube_runtime::watcher::Event;
 
+use crate::background::{task_context, BackgroundTask, TaskContext};
+use crate::error_policy::{ErrorAction, ErrorSource};
+use crate::graph::{Graph, Transitions};
+use crate::leader::{wait_for_leadership, LeaderElection, LeaderTask};
+use crate::manager::watch::Watch;
 use crate::manifest::Manifest;
+use crate::metrics::StateMetrics;
 use crate::object::ObjectKey;
 use crate::object::ObjectState;
-use crate::operator::Operator;
-use crate::state::{run_to_completion, SharedState};
+use crate::operator::{DeregistrationPolicy, Operator, StatusMode, Watchable};
+use crate::quarantine::{Quarantine, QuarantineHandle};
+use crate::state::{run_with_policy, ErrorHook, RunContext, SharedState, State, StateMiddleware};
+use crate::status::StatusOptions;
 use crate::store::Store;
-use crate::util::PrettyEvent;
+use crate::tracker::StateTracker;
+use crate::util::{dynamic_object, Backoff, DynamicEvent, PrettyEvent};
+use crate::write::{has_status, retry_conflicts};
 
 #[derive(Debug)]
 enum ObjectEvent<R> {
     Applied(R),
+    /// A newer manifest is waiting in the object's coalescing slot. Only sent
+    /// with `OverflowPolicy::Coalesce`.
+    Coalesced {
+        name: String,
+        namespace: Option<String>,
+    },
     Deleted {
         name: String,
         namespace: Option<String>,
     },
+    /// An object owned by the object changed.
+    DependentChanged {
+        name: String,
+        namespace: Option<String>,
+    },
 }
 
 impl<R: Resource> From<&ObjectEvent<R>> for PrettyEvent {
@@ -39,6 +69,11 @@ impl<R: Resource> From<&ObjectEvent<R>> for PrettyEvent {
                 name: object.name(),
                 namespace: object.namespace(),
             },
+            ObjectEvent::Coalesced { name, namespace }
+            | ObjectEvent::DependentChanged { name, namespace } => PrettyEvent::Applied {
+                name: name.to_string(),
+                namespace: namespace.clone(),
+            },
             ObjectEvent::Deleted { name, namespace } => PrettyEvent::Deleted {
                 name: name.to_string(),
                 namespace: namespace.clone(),
@@ -47,55 +82,792 @@ impl<R: Resource> From<&ObjectEvent<R>> for PrettyEvent {
     }
 }
 
+/// Determines what happens when an object's event queue is full.
+///
+/// `Applied` events are always coalesced: while an object's task has not yet
+/// picked up a manifest, newer manifests replace it, so at most one `Applied`
+/// event per object is queued at a time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait for the object's task to make room in the queue. This applies
+    /// backpressure to the watcher.
+    #[default]
+    Block,
+    /// Never wait on `Applied` events. Since they are coalesced, the queue
+    /// can only be full once the object's deletion is queued, in which case
+    /// the event is dropped. `Deleted` events always block.
+    Coalesce,
+}
+
+/// Callback invoked on every consecutive watch error, with the number of
+/// consecutive failures so far.
+type WatchErrorHook = Arc<dyn Fn(u32, &watcher::Error) + Send + Sync>;
+
+/// Channels used to forward events to a single object's task.
+struct ObjectHandler<R> {
+    sender: Sender<ObjectEvent<R>>,
+    /// Latest manifest which has not been picked up by the object's task.
+    latest: Arc<std::sync::Mutex<Option<R>>>,
+    /// The resourceVersion of the last dispatched manifest, used to skip
+    /// unchanged objects when resyncing.
+    resource_version: Option<String>,
+    /// The generation of the last manifest delivered to the object's task.
+    generation: Option<i64>,
+}
+
+/// Handle for requesting a graceful shutdown of a running
+/// [OperatorRuntime](crate::OperatorRuntime) from another task.
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    tx: Arc<watch::Sender<bool>>,
+}
+
+impl ShutdownHandle {
+    pub(crate) fn new(tx: Arc<watch::Sender<bool>>) -> Self {
+        ShutdownHandle { tx }
+    }
+
+    /// Request shutdown. The runtime stops its watcher, stops accepting new
+    /// `Applied` events and waits for running state machines to finish their
+    /// current state (or their `DeletedState`) before `start` returns.
+    pub fn shutdown(&self) {
+        // This only fails if the runtime has already been dropped, in which
+        // case there is nothing left to shut down.
+        let _ = self.tx.send(true);
+    }
+}
+
+/// Pauses and resumes dispatching of `Applied` events to an
+/// [OperatorRuntime](crate::OperatorRuntime) or [Manager](crate::Manager).
+#[derive(Clone)]
+pub struct PauseHandle {
+    tx: Arc<watch::Sender<bool>>,
+    rx: watch::Receiver<bool>,
+}
+
+impl PauseHandle {
+    pub(crate) fn new() -> Self {
+        let (tx, rx) = watch::channel(false);
+        PauseHandle {
+            tx: Arc::new(tx),
+            rx,
+        }
+    }
+
+    /// Stop dispatching `Applied` events. Watches and per-object tasks keep
+    /// running, and `Deleted` events are still dispatched.
+    pub fn pause(&self) {
+        let _ = self.tx.send(true);
+    }
+
+    /// Resume dispatching `Applied` events. If any were dropped while paused,
+    /// the runtime re-lists its objects to catch up.
+    pub fn resume(&self) {
+        let _ = self.tx.send(false);
+    }
+
+    /// Whether dispatching is currently paused.
+    pub fn is_paused(&self) -> bool {
+        *self.rx.borrow()
+    }
+
+    pub(crate) fn subscribe(&self) -> watch::Receiver<bool> {
+        self.rx.clone()
+    }
+}
+
+/// Create a client from `kubeconfig`, panicking on failure.
+pub(crate) fn client_from_config(kubeconfig: &kube::Config) -> Client {
+    Client::try_from(kubeconfig.clone()).expect("Unable to create kube::Client from kubeconfig.")
+}
+
+/// Resolves once shutdown has been requested on the channel.
+pub(crate) async fn wait_shutdown(mut shutdown: watch::Receiver<bool>) {
+    while !*shutdown.borrow() {
+        if shutdown.changed().await.is_err() {
+            // Runtime was dropped without requesting shutdown.
+            futures::future::pending::<()>().await;
+        }
+    }
+}
+
 /// Accepts a type implementing the `Operator` trait and watches
 /// for resources of the associated `Manifest` type, running the
 /// associated state machine for each. Optionally filter by
 /// `kube::api::ListParams`.
 pub struct OperatorRuntime<O: Operator> {
     client: Client,
-    handlers: HashMap<ObjectKey, Sender<ObjectEvent<O::Manifest>>>,
+    /// Identifies the watched API resource, e.g. an `ApiResource` for
+    /// `DynamicObject` manifests.
+    dyntype: Arc<<O::Manifest as Resource>::DynamicType>,
+    /// Name of the cluster when running as part of a
+    /// [MultiClusterRuntime](crate::MultiClusterRuntime).
+    cluster: Option<String>,
+    handlers: HashMap<ObjectKey, ObjectHandler<O::Manifest>>,
     operator: Arc<O>,
     list_params: ListParams,
+    /// Restrict the runtime to objects in specific namespaces. Watches all
+    /// namespaces if empty.
+    namespaces: Vec<String>,
+    /// How often to re-list all objects and resync the queue.
+    resync_period: Option<Duration>,
+    watch_backoff: Backoff,
+    on_watch_error: Option<WatchErrorHook>,
     signal: Option<Arc<AtomicBool>>,
+    shutdown_on: Option<watch::Receiver<bool>>,
+    list_params_updates: Option<watch::Receiver<ListParams>>,
     store: Store,
+    /// Other objects cached in the store while the runtime is running.
+    watches: Vec<Watch>,
+    shutdown_tx: Arc<watch::Sender<bool>>,
+    shutdown_rx: watch::Receiver<bool>,
+    /// Each object task holds a clone of this sender, so `drain_rx` resolves
+    /// once the runtime's copy is dropped and every object task has exited.
+    drain_tx: Option<Sender<()>>,
+    drain_rx: tokio::sync::mpsc::Receiver<()>,
+    drain_timeout: Option<Duration>,
+    cancel_timeout: Option<Duration>,
+    init_backoff: Backoff,
+    /// Only dispatch objects whose key hashes to `(index, count)`.
+    shard: Option<(u64, u64)>,
+    pause: PauseHandle,
+    quarantine: QuarantineHandle,
+    /// Consecutive failures after which objects are quarantined.
+    quarantine_threshold: Option<u32>,
+    /// Whether an `Applied` event was dropped while paused.
+    missed_while_paused: bool,
+    /// The buffer length of the channel used to forward events to each
+    /// object's task.
+    buffer: usize,
+    overflow_policy: OverflowPolicy,
+    concurrency: Option<Arc<Semaphore>>,
+    leader_election: Option<LeaderElection>,
+    background_tasks: Vec<BackgroundTask<O>>,
+    middleware: Vec<Arc<dyn StateMiddleware<O::ObjectState>>>,
+    /// Identifies the runtime in Events recorded by states.
+    reporter: Reporter,
+    /// State graph checked before starting.
+    graph: Option<Graph>,
+    /// Number of transitions to record in each object's status.
+    state_history: Option<usize>,
+    state_metrics: Option<StateMetrics>,
+    state_tracker: Option<StateTracker>,
+    /// Metrics and audit sinks of the admission webhook.
+    #[cfg(feature = "admission-webhook")]
+    admission: crate::admission::AdmissionObserver,
+    /// Serves the admission webhook instead of the built-in server.
+    #[cfg(feature = "admission-webhook")]
+    admission_server: Option<Box<dyn crate::admission::AdmissionServer>>,
+    /// How status updates are sent. Whether they are sent inline is
+    /// resolved from the operator's [StatusMode] when the runtime starts.
+    status: StatusOptions,
+    /// Whether this replica is leading, once leader election has started.
+    leader: Option<watch::Receiver<bool>>,
+    /// Releases the Lease once the runtime has drained.
+    leader_task: Option<LeaderTask>,
 }
 
 impl<O: Operator> OperatorRuntime<O> {
     /// Create new runtime with optional ListParams.
-    pub fn new(kubeconfig: &kube::Config, operator: O, params: Option<ListParams>) -> Self {
-        let client = Client::try_from(kubeconfig.clone())
-            .expect("Unable to create kube::Client from kubeconfig.");
-        let list_params = params.unwrap_or_default();
-        OperatorRuntime {
+    ///
+    /// # Panics
+    ///
+    /// Panics if a client cannot be created from `kubeconfig`. Use
+    /// [from_client](Self::from_client) to handle this.
+    pub fn new(kubeconfig: &kube::Config, operator: O, params: Option<ListParams>) -> Self
+    where
+        <O::Manifest as Resource>::DynamicType: Default,
+    {
+        Self::from_client(client_from_config(kubeconfig), operator, params)
+    }
+
+    /// Create new runtime with optional ListParams, using an existing
+    /// client, for example one shared with the rest of the binary, with
+    /// custom middleware, or limited with a [RateLimit](crate::RateLimit).
+    pub fn from_client(client: Client, operator: O, params: Option<ListParams>) -> Self
+    where
+        <O::Manifest as Resource>::DynamicType: Default,
+    {
+        Self::from_parts(
             client,
-            handlers: HashMap::new(),
-            operator: Arc::new(operator),
-            list_params,
-            signal: None,
-            store: Store::new(),
-        }
+            Arc::new(operator),
+            Arc::new(Default::default()),
+            params,
+            Store::new(),
+        )
     }
 
-    #[cfg(not(feature = "admission-webhook"))]
-    pub(crate) fn new_with_store(
+    /// Create new runtime with optional ListParams for a manifest type whose
+    /// API resource is only known at runtime, such as `DynamicObject` with an
+    /// `ApiResource` built from configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a client cannot be created from `kubeconfig`.
+    pub fn new_dynamic(
         kubeconfig: &kube::Config,
         operator: O,
+        dyntype: <O::Manifest as Resource>::DynamicType,
+        params: Option<ListParams>,
+    ) -> Self {
+        Self::from_parts(
+            client_from_config(kubeconfig),
+            Arc::new(operator),
+            Arc::new(dyntype),
+            params,
+            Store::new(),
+        )
+    }
+
+    pub(crate) fn from_parts(
+        client: Client,
+        operator: Arc<O>,
+        dyntype: Arc<<O::Manifest as Resource>::DynamicType>,
         params: Option<ListParams>,
         store: Store,
     ) -> Self {
-        let client = Client::try_from(kubeconfig.clone())
-            .expect("Unable to create kube::Client from kubeconfig.");
         let list_params = params.unwrap_or_default();
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let (drain_tx, drain_rx) = tokio::sync::mpsc::channel(1);
         OperatorRuntime {
             client,
+            dyntype,
+            cluster: None,
             handlers: HashMap::new(),
-            operator: Arc::new(operator),
+            operator,
             list_params,
+            namespaces: vec![],
+            resync_period: None,
+            watch_backoff: Default::default(),
+            on_watch_error: None,
             signal: None,
+            shutdown_on: None,
+            list_params_updates: None,
             store,
+            watches: vec![],
+            shutdown_tx: Arc::new(shutdown_tx),
+            shutdown_rx,
+            drain_tx: Some(drain_tx),
+            drain_rx,
+            drain_timeout: None,
+            cancel_timeout: None,
+            init_backoff: Default::default(),
+            shard: None,
+            pause: PauseHandle::new(),
+            quarantine: QuarantineHandle::new(),
+            quarantine_threshold: None,
+            missed_while_paused: false,
+            buffer: 128,
+            overflow_policy: Default::default(),
+            concurrency: None,
+            leader_election: None,
+            background_tasks: vec![],
+            middleware: vec![],
+            reporter: Reporter {
+                controller: "krator".to_string(),
+                instance: None,
+            },
+            graph: None,
+            state_history: None,
+            state_metrics: None,
+            state_tracker: None,
+            #[cfg(feature = "admission-webhook")]
+            admission: Default::default(),
+            #[cfg(feature = "admission-webhook")]
+            admission_server: None,
+            status: Default::default(),
+            leader: None,
+            leader_task: None,
+        }
+    }
+
+    /// Identify the cluster this runtime watches. Exposed to states through
+    /// [Manifest::cluster](crate::Manifest::cluster) and part of the key of
+    /// every object. Runtimes of a cluster do not serve the admission
+    /// webhook, since each cluster's runtime would bind the same port.
+    pub(crate) fn with_cluster(mut self, cluster: &str) -> Self {
+        self.cluster = Some(cluster.to_string());
+        self
+    }
+
+    /// Key of `object` in the cluster this runtime watches.
+    fn key_of(&self, object: &O::Manifest) -> ObjectKey {
+        ObjectKey::from(object).with_cluster(self.cluster.clone())
+    }
+
+    /// Obtain a handle which can be used to gracefully shut down the runtime
+    /// while `start` is running.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle {
+            tx: Arc::clone(&self.shutdown_tx),
+        }
+    }
+
+    /// Obtain a handle which can be used to pause and resume dispatching
+    /// while `start` is running.
+    pub fn pause_handle(&self) -> PauseHandle {
+        self.pause.clone()
+    }
+
+    /// Obtain a handle which can be used to list and requeue quarantined
+    /// objects while `start` is running. See
+    /// [with_quarantine](Self::with_quarantine).
+    pub fn quarantine_handle(&self) -> QuarantineHandle {
+        self.quarantine.clone()
+    }
+
+    /// Quarantine an object once the [ErrorPolicy](crate::ErrorPolicy)
+    /// retried its state machine or hooks `threshold` times in a row,
+    /// instead of retrying it again. A Warning Event is recorded for the
+    /// object and it is counted in the
+    /// [state metrics](Self::with_state_metrics). Quarantined objects stay
+    /// put until they are requeued through the
+    /// [quarantine_handle](Self::quarantine_handle), their spec changes, they
+    /// are deleted, or the runtime restarts. Objects which succeed after
+    /// leaving the quarantine start counting failures from zero again.
+    pub fn with_quarantine(mut self, threshold: u32) -> Self {
+        self.quarantine_threshold = Some(threshold);
+        self
+    }
+
+    /// Replace the runtime's `ListParams` whenever a new value is sent on
+    /// `updates`, for example when a ConfigMap holding the selectors changes.
+    /// The watchers are restarted with the new params and the queue is
+    /// resynced; objects which no longer match are handled as if they had
+    /// been deleted, just like objects whose labels change.
+    pub fn with_list_params_updates(mut self, updates: watch::Receiver<ListParams>) -> Self {
+        self.list_params_updates = Some(updates);
+        self
+    }
+
+    /// Stop dispatching `Applied` events without tearing down watches or
+    /// per-object tasks.
+    pub fn pause(&self) {
+        self.pause.pause();
+    }
+
+    /// Resume dispatching `Applied` events.
+    pub fn resume(&self) {
+        self.pause.resume();
+    }
+
+    pub(crate) fn with_pause_handle(mut self, pause: PauseHandle) -> Self {
+        self.pause = pause;
+        self
+    }
+
+    /// Run `f` alongside the state machines until shutdown, restarting it
+    /// with exponential backoff if it fails, panics or returns early. The
+    /// task should return once
+    /// [shutdown_requested](crate::TaskContext::shutdown_requested) resolves.
+    /// With leader election, it only runs on the leader.
+    pub fn with_background_task<F, Fut>(mut self, name: &str, f: F) -> Self
+    where
+        F: Fn(TaskContext<O>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.background_tasks
+            .push(BackgroundTask::new(name, None, f));
+        self
+    }
+
+    /// Run `f` every `period` alongside the state machines until shutdown,
+    /// for example to garbage collect external resources. A run which is in
+    /// progress at shutdown is waited for. With leader election, it only runs
+    /// on the leader.
+    pub fn with_periodic_task<F, Fut>(mut self, name: &str, period: Duration, f: F) -> Self
+    where
+        F: Fn(TaskContext<O>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.background_tasks
+            .push(BackgroundTask::new(name, Some(period), f));
+        self
+    }
+
+    pub(crate) fn with_background_tasks(mut self, tasks: Vec<BackgroundTask<O>>) -> Self {
+        self.background_tasks.extend(tasks);
+        self
+    }
+
+    /// Run `middleware` before and after every state executed by this
+    /// runtime. Middleware runs in the order it was registered.
+    pub fn with_state_middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: StateMiddleware<O::ObjectState>,
+    {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    pub(crate) fn with_state_middlewares(
+        mut self,
+        middleware: Vec<Arc<dyn StateMiddleware<O::ObjectState>>>,
+    ) -> Self {
+        self.middleware.extend(middleware);
+        self
+    }
+
+    /// Check the operator's state graph with
+    /// [Graph::validate](crate::graph::Graph::validate) when the runtime
+    /// starts, so that a state machine which can get stuck fails startup.
+    pub fn with_graph_validation(mut self) -> Self
+    where
+        O::InitialState: Transitions,
+        O::DeletedState: Transitions,
+    {
+        self.graph = Some(Graph::of::<O>());
+        self
+    }
+
+    /// Record the last `entries` transitions of each object in its
+    /// `status.stateHistory`, with the state, a timestamp and the outcome, so
+    /// that users can see what the operator has been doing with
+    /// `kubectl describe`. The history starts over whenever the object's
+    /// state machine is restarted. Requires the status schema to accept the
+    /// field.
+    pub fn with_state_history(mut self, entries: usize) -> Self {
+        self.state_history = Some(entries);
+        self
+    }
+
+    /// Record the number of entries, failures and the duration of every
+    /// state executed by this runtime into `metrics`.
+    pub fn with_state_metrics(mut self, metrics: StateMetrics) -> Self {
+        self.state_metrics = Some(metrics);
+        self
+    }
+
+    /// Record the number of requests, decisions and the latency of the
+    /// admission webhook into `metrics`.
+    #[cfg(feature = "admission-webhook")]
+    pub fn with_admission_metrics(self, metrics: crate::metrics::AdmissionMetrics) -> Self {
+        self.admission.set_metrics(metrics);
+        self
+    }
+
+    /// Record every decision of the admission webhook, along with the
+    /// requesting user, in `sink`. Multiple sinks can be added.
+    #[cfg(feature = "admission-webhook")]
+    pub fn with_admission_audit(self, sink: impl crate::admission::AdmissionAudit) -> Self {
+        self.admission.add_audit(Arc::new(sink));
+        self
+    }
+
+    /// Serve the admission webhook with `server` instead of the built-in
+    /// server, for example to embed it into an existing HTTP server.
+    /// [admission_hook_tls](Operator::admission_hook_tls) is not called.
+    #[cfg(feature = "admission-webhook")]
+    pub fn with_admission_server(mut self, server: impl crate::admission::AdmissionServer) -> Self {
+        self.admission_server = Some(Box::new(server));
+        self
+    }
+
+    /// Track the current state, time in state and last error of every
+    /// object in `tracker`.
+    pub fn with_state_tracker(mut self, tracker: StateTracker) -> Self {
+        self.state_tracker = Some(tracker);
+        self
+    }
+
+    /// Hold status patches for up to `window`, so that a burst of
+    /// transitions results in a single patch with the final status. Pending
+    /// patches are sent before a state machine waits to re-enter a state or
+    /// stops, and may be lost if a running state is interrupted by deletion
+    /// or cancellation.
+    pub fn with_status_debounce(mut self, window: Duration) -> Self {
+        self.status.debounce = Some(window);
+        self
+    }
+
+    /// Update status with server-side apply as `field_manager`, instead of
+    /// merge patches, so that fields owned by other controllers, such as
+    /// their conditions, are left alone. Each request carries every status
+    /// field the runtime has set for the object so far, since server-side
+    /// apply removes owned fields which are left out. Conflicting fields are
+    /// taken over, as recommended for controllers.
+    pub fn with_status_field_manager(mut self, field_manager: &str) -> Self {
+        self.status.field_manager = Some(field_manager.to_string());
+        self
+    }
+
+    /// Only send status updates which change a field from what the runtime
+    /// last sent for the object. Fields changed by anyone else in the
+    /// meantime are not restored.
+    pub fn with_status_skip_unchanged(mut self) -> Self {
+        self.status.skip_unchanged = true;
+        self
+    }
+
+    /// Send at most one status update per object every `min_interval`.
+    /// Patches issued in between are merged and sent once the interval has
+    /// passed, or when the state machine stops.
+    pub fn with_status_rate_limit(mut self, min_interval: Duration) -> Self {
+        self.status.min_interval = Some(min_interval);
+        self
+    }
+
+    /// Identify the runtime in Kubernetes Events recorded through
+    /// [Manifest::recorder](crate::Manifest::recorder). Defaults to the
+    /// controller name `krator`.
+    pub fn with_event_reporter(mut self, reporter: Reporter) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    /// Spawn the registered background tasks. Each holds a drain sender, so
+    /// shutdown waits for them to exit.
+    pub(crate) async fn spawn_background_tasks(&mut self) {
+        for task in std::mem::take(&mut self.background_tasks) {
+            let drain = match self.drain_tx {
+                Some(ref drain) => drain.clone(),
+                None => return,
+            };
+            let context = task_context(
+                self.client.clone(),
+                self.store.clone(),
+                &*self.operator,
+                self.shutdown_rx.clone(),
+            )
+            .await;
+            tokio::spawn(task.supervise(context, drain));
+        }
+    }
+
+    /// Re-list objects if any `Applied` events were dropped while paused.
+    pub(crate) async fn catch_up_after_pause(&mut self) {
+        if self.missed_while_paused && !self.pause.is_paused() {
+            self.missed_while_paused = false;
+            info!("Resumed, resyncing objects missed while paused.");
+            self.periodic_resync().await;
         }
     }
 
+    /// Obtain a handle to the [Store], which caches the watched objects as
+    /// well as those of the kinds registered with
+    /// [watches](Self::watches).
+    pub fn store(&self) -> Store {
+        self.store.clone()
+    }
+
+    /// Cache all objects of kind R in the [Store] while the runtime is
+    /// running. No object is dispatched before they have been listed.
+    pub fn watches<R: Watchable>(mut self) -> Self {
+        self.watches.push(Watch::new::<R>(None, Default::default()));
+        self
+    }
+
+    /// Cache the objects of kind R in `namespace` in the [Store] while the
+    /// runtime is running. No object is dispatched before they have been
+    /// listed.
+    pub fn watches_namespaced<R: Watchable>(mut self, namespace: &str) -> Self {
+        self.watches.push(Watch::new::<R>(
+            Some(namespace.to_string()),
+            Default::default(),
+        ));
+        self
+    }
+
+    /// Spawn a task caching the objects of each of the runtime's watches,
+    /// and wait until all of them have been listed. Returns `false` if
+    /// shutdown is requested first.
+    async fn sync_watches(&mut self) -> bool {
+        let mut gvks = vec![];
+        for watch in std::mem::take(&mut self.watches) {
+            let drain = match self.drain_tx {
+                Some(ref drain) => drain.clone(),
+                None => return false,
+            };
+            gvks.push(watch.gvk.clone());
+            tokio::spawn(reflect_watch(
+                self.client.clone(),
+                watch,
+                self.store.clone(),
+                self.watch_backoff.clone(),
+                self.shutdown_rx.clone(),
+                drain,
+            ));
+        }
+        if gvks.is_empty() {
+            return true;
+        }
+        debug!(?gvks, "Waiting for watched objects to be cached.");
+        tokio::select! {
+            _ = self.store.wait_synced(&gvks) => true,
+            _ = wait_shutdown(self.shutdown_rx.clone()) => false,
+        }
+    }
+
+    /// Restrict the runtime to watch objects in a specific namespace. This
+    /// only requires namespaced list/watch permissions. Must not be used
+    /// with cluster-scoped resources.
+    pub fn namespaced(mut self, namespace: &str) -> Self {
+        self.namespaces = vec![namespace.to_string()];
+        self
+    }
+
+    /// Restrict the runtime to watch objects in a set of namespaces. One
+    /// watcher is started per namespace, and all of them feed the same
+    /// dispatch loop.
+    pub fn with_namespaces(mut self, namespaces: &[&str]) -> Self {
+        self.namespaces = namespaces.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    /// Periodically re-list all objects and resync the queue, so that an
+    /// object is not left stale if a watch event is missed.
+    pub fn with_resync_period(mut self, period: Duration) -> Self {
+        self.resync_period = Some(period);
+        self
+    }
+
+    /// Change the backoff applied when the watcher returns errors.
+    pub fn with_watch_backoff(mut self, backoff: Backoff) -> Self {
+        self.watch_backoff = backoff;
+        self
+    }
+
+    /// Register a callback which is invoked on every watcher error with the
+    /// number of consecutive failures, e.g. to export a metric or alert on
+    /// an unhealthy apiserver.
+    pub fn on_watch_error<F>(mut self, f: F) -> Self
+    where
+        F: Fn(u32, &watcher::Error) + Send + Sync + 'static,
+    {
+        self.on_watch_error = Some(Arc::new(f));
+        self
+    }
+
+    /// Drop incoming `Applied` and `Deleted` events while `signal` is set.
+    /// Unlike [shutdown_on](Self::shutdown_on) this does not stop the runtime.
+    pub fn with_signal(mut self, signal: Arc<AtomicBool>) -> Self {
+        self.signal = Some(signal);
+        self
+    }
+
+    /// Gracefully shut down the runtime once `true` is sent on `signal`. This
+    /// has the same effect as calling
+    /// [ShutdownHandle::shutdown](crate::ShutdownHandle::shutdown).
+    pub fn shutdown_on(mut self, signal: watch::Receiver<bool>) -> Self {
+        self.shutdown_on = Some(signal);
+        self
+    }
+
+    /// Change the length of the buffer used to forward events to each
+    /// object's task.
+    pub fn with_buffer(mut self, buffer: usize) -> Self {
+        self.buffer = buffer;
+        self
+    }
+
+    /// Change how `Applied` events are handled when an object's buffer is
+    /// full.
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// Limit the number of object state machines which execute a state at
+    /// the same time. Others wait for a free slot before entering their next
+    /// state and are woken in the order they started waiting. Note that a
+    /// state which waits for a long time also holds its slot.
+    pub fn with_max_concurrent_reconciles(mut self, max: usize) -> Self {
+        self.concurrency = Some(Arc::new(Semaphore::new(max)));
+        self
+    }
+
+    /// Limit how long shutdown waits for running state machines to reach a
+    /// safe point. By default shutdown waits indefinitely.
+    pub fn with_drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = Some(timeout);
+        self
+    }
+
+    /// Only watch and dispatch objects while this replica holds the given
+    /// Lease. When leadership is lost the runtime stops watching and drains
+    /// running state machines, as if shutdown had been requested.
+    pub fn with_leader_election(mut self, election: LeaderElection) -> Self {
+        self.leader_election = Some(election);
+        self
+    }
+
+    /// Start leader election if configured and wait until this replica is
+    /// leading. Returns `false` if shutdown is requested first.
+    async fn acquire_leadership(&mut self) -> bool {
+        if let Some(election) = self.leader_election.take() {
+            let (leader, task) = election.spawn(self.client.clone());
+            self.leader = Some(leader);
+            self.leader_task = Some(task);
+        }
+        let mut leader = match self.leader.clone() {
+            Some(leader) => leader,
+            None => return true,
+        };
+        info!("Waiting for leadership.");
+        let external_shutdown = match self.shutdown_on.clone() {
+            Some(signal) => wait_shutdown(signal).boxed(),
+            None => futures::future::pending::<()>().boxed(),
+        };
+        tokio::select! {
+            _ = wait_for_leadership(&mut leader, true) => true,
+            _ = wait_shutdown(self.shutdown_rx.clone()) => false,
+            _ = external_shutdown => {
+                let _ = self.shutdown_tx.send(true);
+                false
+            }
+        }
+    }
+
+    /// Change the backoff between attempts to initialize an object's state
+    /// when [initialize_object_state](Operator::initialize_object_state)
+    /// fails. Every failure is also passed to
+    /// [error_hook](Operator::error_hook). Attempts continue until they
+    /// succeed, the object is deleted, or the runtime shuts down.
+    pub fn with_init_backoff(mut self, backoff: Backoff) -> Self {
+        self.init_backoff = backoff;
+        self
+    }
+
+    /// Cancel states which are still executing this long after shutdown is
+    /// requested or leadership is lost. By default executing states run to
+    /// completion and only the next transition is skipped.
+    pub fn with_cancel_timeout(mut self, timeout: Duration) -> Self {
+        self.cancel_timeout = Some(timeout);
+        self
+    }
+
+    /// Only dispatch objects which hash into shard `index` of `count`, so that
+    /// the objects are split between `count` replicas configured with
+    /// indexes `0..count`. Every replica still watches all objects; see
+    /// [with_shard_label](Self::with_shard_label) to filter on the server.
+    ///
+    /// Panics if `index` is not less than `count`.
+    pub fn with_shard(mut self, index: u64, count: u64) -> Self {
+        assert!(index < count, "Shard index must be less than shard count.");
+        self.shard = Some((index, count));
+        self
+    }
+
+    /// Only watch objects labelled with `key=value`, for example when objects
+    /// are assigned to a shard by a label.
+    pub fn with_shard_label(mut self, key: &str, value: &str) -> Self {
+        let selector = format!("{}={}", key, value);
+        self.list_params.label_selector = Some(match self.list_params.label_selector.take() {
+            Some(existing) => format!("{},{}", existing, selector),
+            None => selector,
+        });
+        self
+    }
+
+    fn is_shutting_down(&self) -> bool {
+        if let Some(ref signal) = self.signal {
+            if signal.load(Ordering::Relaxed) {
+                return true;
+            }
+        }
+        *self.shutdown_rx.borrow()
+    }
+
     /// Dispatch event to the matching resource's task.
     /// If no task is found, `self.start_object` is called to start a task for
     /// the new object.
@@ -107,52 +879,157 @@ impl<O: Operator> OperatorRuntime<O> {
     async fn dispatch(&mut self, event: ObjectEvent<O::Manifest>) -> anyhow::Result<()> {
         match event {
             ObjectEvent::Applied(object) => {
-                let key: ObjectKey = (&object).into();
+                let key = self.key_of(&object);
+                if self.pause.is_paused() {
+                    trace!("Runtime is paused, dropping event.");
+                    self.missed_while_paused = true;
+                    return Ok(());
+                }
+                if let Some((index, count)) = self.shard {
+                    if key.shard(count) != index {
+                        trace!("Object belongs to another shard, ignoring.");
+                        return Ok(());
+                    }
+                }
                 // We are explicitly not using the entry api here to insert to avoid the need for a
                 // mutex
                 match self.handlers.get_mut(&key) {
-                    Some(sender) => {
+                    Some(handler) => {
                         trace!("Found existing event handler for object.");
-                        match sender.send(ObjectEvent::Applied(object)).await {
-                            Ok(_) => trace!("Successfully sent event to handler for object."),
-                            Err(error) => error!(
+                        handler.resource_version = object.resource_version();
+                        let generation = object.meta().generation;
+                        if self.operator.generation_changes_only()
+                            && generation.is_some()
+                            && generation == handler.generation
+                        {
+                            trace!("Generation of object is unchanged, ignoring.");
+                            return Ok(());
+                        }
+                        handler.generation = generation;
+                        // Only the newest manifest matters, so replace any
+                        // manifest the object's task has not picked up yet.
+                        let pending = handler.latest.lock().unwrap().replace(object).is_some();
+                        if pending {
+                            trace!("Coalesced with pending event for object.");
+                            return Ok(());
+                        }
+                        let event = ObjectEvent::Coalesced {
+                            name: key.name().to_string(),
+                            namespace: key.namespace().cloned(),
+                        };
+                        let result = match self.overflow_policy {
+                            OverflowPolicy::Block => {
+                                handler.sender.send(event).await.map_err(|_| ())
+                            }
+                            OverflowPolicy::Coalesce => {
+                                match handler.sender.try_send(event) {
+                                    Err(TrySendError::Full(_)) => {
+                                        trace!("Event queue for object is full, dropping notification.");
+                                        Ok(())
+                                    }
+                                    result => result.map_err(|_| ()),
+                                }
+                            }
+                        };
+                        match result {
+                            Ok(()) => trace!("Successfully sent event to handler for object."),
+                            Err(()) => error!(
                                 name=key.name(),
                                 namespace=?key.namespace(),
-                                ?error,
                                 "Error while sending event. Will retry on next event.",
                             ),
                         }
                     }
                     None => {
+                        if !self.operator.should_reconcile(&object) {
+                            trace!("Operator declined to reconcile object, ignoring.");
+                            return Ok(());
+                        }
                         debug!(
                             name=key.name(),
                             namespace=?key.namespace(),
                             "Creating event handler for object.",
                         );
-                        self.handlers.insert(
-                            key.clone(),
-                            // TODO Do we want to capture join handles? Worker wasnt using them.
-                            // TODO How do we drop this sender / handler?
-                            self.start_object(object).await?,
-                        );
+                        // The object's tasks exit once the handler is removed
+                        // when the object is deleted, closing its channel.
+                        self.handlers
+                            .insert(key.clone(), self.start_object(object).await?);
                     }
                 }
                 Ok(())
             }
             ObjectEvent::Deleted { name, namespace } => {
-                let key = ObjectKey::new(namespace.clone(), name.clone());
-                if let Some(sender) = self.handlers.remove(&key) {
+                let key = ObjectKey::new(namespace.clone(), name.clone())
+                    .with_cluster(self.cluster.clone());
+                if let Some(handler) = self.handlers.remove(&key) {
                     debug!(
                         "Removed event handler for object {} in namespace {:?}.",
                         key.name(),
                         key.namespace()
                     );
-                    sender
+                    handler
+                        .sender
                         .send(ObjectEvent::Deleted { name, namespace })
                         .await?;
                 }
                 Ok(())
             }
+            ObjectEvent::DependentChanged { name, namespace } => {
+                if self.pause.is_paused() {
+                    trace!("Runtime is paused, dropping dependent notification.");
+                    return Ok(());
+                }
+                let key = ObjectKey::new(namespace.clone(), name.clone())
+                    .with_cluster(self.cluster.clone());
+                let handler = match self.handlers.get(&key) {
+                    Some(handler) => handler,
+                    None => {
+                        trace!("No event handler for owner, ignoring dependent notification.");
+                        return Ok(());
+                    }
+                };
+                let event = ObjectEvent::DependentChanged { name, namespace };
+                let result = match self.overflow_policy {
+                    OverflowPolicy::Block => handler.sender.send(event).await.map_err(|_| ()),
+                    OverflowPolicy::Coalesce => {
+                        match handler.sender.try_send(event) {
+                            Err(TrySendError::Full(_)) => {
+                                trace!("Event queue for object is full, dropping dependent notification.");
+                                Ok(())
+                            }
+                            result => result.map_err(|_| ()),
+                        }
+                    }
+                };
+                if result.is_err() {
+                    error!(
+                        name = key.name(),
+                        namespace = ?key.namespace(),
+                        "Error while sending dependent notification.",
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Notify the object `owner` that an object it owns changed, which
+    /// re-delivers its latest manifest and resolves
+    /// [Manifest::dependent_changed](crate::Manifest::dependent_changed).
+    /// Owners which are not namespaced are looked up by name if no object
+    /// matches `owner`.
+    pub(crate) async fn notify_owner(&mut self, owner: ObjectKey) {
+        let owner = owner.with_cluster(self.cluster.clone());
+        let namespace = match owner.namespace() {
+            Some(namespace) if self.handlers.contains_key(&owner) => Some(namespace.clone()),
+            _ => None,
+        };
+        let event = ObjectEvent::DependentChanged {
+            name: owner.name().to_string(),
+            namespace,
+        };
+        if let Err(error) = self.dispatch(event).await {
+            warn!(?error, "Error notifying owner of dependent change.");
         }
     }
 
@@ -162,17 +1039,35 @@ impl<O: Operator> OperatorRuntime<O> {
     async fn start_object(
         &self,
         manifest: O::Manifest,
-    ) -> anyhow::Result<Sender<ObjectEvent<O::Manifest>>> {
-        let (sender, mut receiver) = tokio::sync::mpsc::channel::<ObjectEvent<O::Manifest>>(128);
+    ) -> anyhow::Result<ObjectHandler<O::Manifest>> {
+        let drain = match self.drain_tx {
+            Some(ref drain) => drain.clone(),
+            None => anyhow::bail!("Runtime is shutting down, not starting new object task."),
+        };
+
+        let (sender, mut receiver) =
+            tokio::sync::mpsc::channel::<ObjectEvent<O::Manifest>>(self.buffer);
+        let latest = Arc::new(std::sync::Mutex::new(None));
+        let reflector_latest = Arc::clone(&latest);
 
         let deleted = Arc::new(RwLock::new(false));
         let deleted_event = Arc::new(RwLock::new(false));
 
-        let object_state = self.operator.initialize_object_state(&manifest).await?;
+        let resource_version = manifest.resource_version();
+        let generation = manifest.meta().generation;
+        let reference = manifest.object_ref(&*self.dyntype);
+        let mut current = manifest.clone();
 
         let (manifest_tx, manifest_rx) = Manifest::new(manifest, self.store.clone());
+        let (dependents_tx, dependents_rx) = tokio::sync::watch::channel(());
+        let manifest_rx = manifest_rx
+            .with_client(self.client.clone(), self.cluster.clone())
+            .with_events(self.reporter.clone(), reference)
+            .with_dependents(dependents_rx);
         let reflector_deleted = Arc::clone(&deleted);
         let reflector_deleted_event = Arc::clone(&deleted_event);
+        let reflector_previous = Arc::clone(&manifest_rx.previous);
+        let debounce = self.operator.manifest_debounce();
 
         // Two tasks are spawned for each resource. The first updates shared state (manifest and
         // deleted flag) while the second awaits on the actual state machine, interrupts it on
@@ -180,6 +1075,21 @@ impl<O: Operator> OperatorRuntime<O> {
 
         tokio::spawn(async move {
             while let Some(event) = receiver.recv().await {
+                let event = match event {
+                    ObjectEvent::Coalesced { .. } => {
+                        if let Some(window) = debounce {
+                            // Later updates replace the pending manifest meanwhile.
+                            tokio::time::sleep(window).await;
+                        }
+                        let latest = reflector_latest.lock().unwrap().take();
+                        match latest {
+                            Some(manifest) => ObjectEvent::Applied(manifest),
+                            // Already delivered along with an earlier event.
+                            None => continue,
+                        }
+                    }
+                    event => event,
+                };
                 // Watch errors are handled before an event ever gets here, so it should always have
                 // an object
                 match event {
@@ -196,6 +1106,13 @@ impl<O: Operator> OperatorRuntime<O> {
                                 *event = true;
                             }
                         }
+                        // Re-deliveries of the same version keep the previous one.
+                        if manifest.resource_version() != current.resource_version() {
+                            let previous = std::mem::replace(&mut current, manifest.clone());
+                            *reflector_previous
+                                .lock()
+                                .expect("Previous manifest lock poisoned.") = Some(previous);
+                        }
                         match manifest_tx.send(manifest) {
                             Ok(()) => (),
                             Err(_) => {
@@ -204,6 +1121,19 @@ impl<O: Operator> OperatorRuntime<O> {
                             }
                         }
                     }
+                    ObjectEvent::DependentChanged { .. } => {
+                        trace!(
+                            name=%current.name(),
+                            namespace=?current.namespace(),
+                            "Dependent changed.",
+                        );
+                        dependents_tx.send(()).ok();
+                        // Wake states waiting on the next manifest.
+                        if manifest_tx.send(current.clone()).is_err() {
+                            debug!("Manifest receiver hung up, exiting.");
+                            return;
+                        }
+                    }
                     ObjectEvent::Deleted { name, namespace } => {
                         // I'm not sure if this matters, we get notified of pod deletion with a
                         // Modified event, and I think we only get this after *we* delete the pod.
@@ -224,35 +1154,80 @@ impl<O: Operator> OperatorRuntime<O> {
                         }
                         break;
                     }
+                    ObjectEvent::Coalesced { .. } => unreachable!(),
                 }
             }
         });
 
-        tokio::spawn(run_object_task::<O>(
-            self.client.clone(),
+        let context = ObjectTaskContext {
+            client: self.client.clone(),
+            dyntype: Arc::clone(&self.dyntype),
+            operator: Arc::clone(&self.operator),
+            shutdown: self.shutdown_rx.clone(),
+            concurrency: self.concurrency.clone(),
+            cancel_timeout: self.cancel_timeout,
+            init_backoff: self.init_backoff.clone(),
+            middleware: self.middleware.clone(),
+            state_history: self.state_history,
+            state_metrics: self.state_metrics.clone(),
+            state_tracker: self.state_tracker.clone(),
+            status: self.status.clone(),
+            quarantine: self.quarantine_threshold.map(|threshold| Quarantine {
+                handle: self.quarantine.clone(),
+                threshold,
+            }),
+            _drain: drain,
+        };
+
+        tokio::spawn(supervise_object_task::<O>(
+            context,
             manifest_rx,
-            self.operator.shared_state().await,
-            object_state,
             deleted,
             deleted_event,
-            Arc::clone(&self.operator),
         ));
 
-        Ok(sender)
+        Ok(ObjectHandler {
+            sender,
+            latest,
+            resource_version,
+            generation,
+        })
     }
 
     /// Resyncs the queue given the list of objects. Objects that exist in
-    /// the queue but no longer exist in the list will be deleted
+    /// the queue but no longer exist in the list will be deleted. If
+    /// `namespace` is set, only objects in that namespace are considered. If
+    /// `skip_unchanged` is set, objects whose resourceVersion matches the last
+    /// dispatched manifest are not requeued.
     #[tracing::instrument(
       level="trace",
       skip(self, objects),
       fields(count=objects.len())
     )]
-    async fn resync(&mut self, objects: Vec<O::Manifest>) -> anyhow::Result<()> {
+    async fn resync(
+        &mut self,
+        namespace: Option<&str>,
+        objects: Vec<O::Manifest>,
+        skip_unchanged: bool,
+    ) -> anyhow::Result<()> {
+        match objects.iter().map(dynamic_object).collect() {
+            Ok(listed) => self.reflect(namespace, Event::Restarted(listed)).await,
+            Err(error) => warn!(?error, "Unable to cache listed objects."),
+        }
+
         // First reconcile any deleted items we might have missed (if it exists
         // in our map, but not in the list)
-        let current_objects: HashSet<ObjectKey> = objects.iter().map(|obj| obj.into()).collect();
-        let objects_in_state: HashSet<ObjectKey> = self.handlers.keys().cloned().collect();
+        let current_objects: HashSet<ObjectKey> =
+            objects.iter().map(|obj| self.key_of(obj)).collect();
+        let objects_in_state: HashSet<ObjectKey> = self
+            .handlers
+            .keys()
+            .filter(|key| match namespace {
+                Some(namespace) => key.namespace().map(String::as_str) == Some(namespace),
+                None => true,
+            })
+            .cloned()
+            .collect();
         for key in objects_in_state.difference(&current_objects) {
             trace!(
                 name=key.name(),
@@ -267,7 +1242,21 @@ impl<O: Operator> OperatorRuntime<O> {
         }
 
         // Now that we've sent off deletes, queue an apply event for all pods
+        // which changed since they were last dispatched.
         for object in objects.into_iter() {
+            let key = self.key_of(&object);
+            if let Some(handler) = self.handlers.get(&key).filter(|_| skip_unchanged) {
+                if handler.resource_version.is_some()
+                    && handler.resource_version == object.resource_version()
+                {
+                    trace!(
+                        name=%object.name(),
+                        namespace=?object.namespace(),
+                        "object_unchanged"
+                    );
+                    continue;
+                }
+            }
             trace!(
                 name=%object.name(),
                 namespace=?object.namespace(),
@@ -278,25 +1267,77 @@ impl<O: Operator> OperatorRuntime<O> {
         Ok(())
     }
 
+    /// Re-list objects in all watched namespaces and resync the queue.
+    pub(crate) async fn periodic_resync(&mut self) {
+        info!("Starting periodic resync.");
+        let scopes: Vec<Option<String>> = if self.namespaces.is_empty() {
+            vec![None]
+        } else {
+            self.namespaces.iter().cloned().map(Some).collect()
+        };
+        for namespace in scopes {
+            let api: Api<O::Manifest> = match namespace {
+                Some(ref namespace) => {
+                    Api::namespaced_with(self.client.clone(), namespace, &*self.dyntype)
+                }
+                None => Api::all_with(self.client.clone(), &*self.dyntype),
+            };
+            match api.list(&self.list_params).await {
+                Ok(list) => match self.resync(namespace.as_deref(), list.items, false).await {
+                    Ok(()) => debug!(?namespace, "Finished periodic resync of objects."),
+                    Err(error) => warn!(?namespace, ?error, "Error resyncing objects."),
+                },
+                Err(error) => warn!(?namespace, ?error, "Unable to list objects for resync."),
+            }
+        }
+    }
+
+    pub(crate) async fn handle_event(&mut self, event: Event<O::Manifest>) {
+        self.handle_namespaced_event(None, event).await
+    }
+
+    /// Handle an event from a watcher which is restricted to `namespace`, so
+    /// that restarts only resync objects in that namespace.
     #[tracing::instrument(
         level="trace",
         skip(self, event),
         fields(event=?PrettyEvent::from(&event))
     )]
-    pub(crate) async fn handle_event(&mut self, event: Event<O::Manifest>) {
-        if let Some(ref signal) = self.signal {
-            if matches!(event, kube_runtime::watcher::Event::Applied(_))
-                && signal.load(Ordering::Relaxed)
-            {
-                warn!("Controller is shutting down (got signal). Dropping Add event.");
-                return;
+    async fn handle_namespaced_event(
+        &mut self,
+        namespace: Option<&str>,
+        event: Event<O::Manifest>,
+    ) {
+        self.operator.on_event(&event).await;
+        let changed = match event {
+            Event::Applied(ref object) => Some(dynamic_object(object).map(Event::Applied)),
+            Event::Deleted(ref object) => Some(dynamic_object(object).map(Event::Deleted)),
+            // Restarts are cached by `resync`.
+            Event::Restarted(_) => None,
+        };
+        match changed {
+            Some(Ok(changed)) => self.reflect(namespace, changed).await,
+            Some(Err(error)) => warn!(?error, "Unable to cache object."),
+            None => (),
+        }
+        if self.is_shutting_down() {
+            match event {
+                Event::Applied(_) => {
+                    warn!("Controller is shutting down (got signal). Dropping Add event.");
+                    return;
+                }
+                Event::Deleted(_) => {
+                    warn!("Controller is shutting down (got signal). Dropping Delete event.");
+                    return;
+                }
+                Event::Restarted(_) => (),
             }
         }
         match event {
             Event::Restarted(objects) => {
                 info!("Got a watch restart. Resyncing queue...");
                 // If we got a restart, we need to requeue an applied event for all objects
-                match self.resync(objects).await {
+                match self.resync(namespace, objects, true).await {
                     Ok(()) => info!("Finished resync of objects."),
                     Err(error) => warn!(?error, "Error resyncing objects."),
                 };
@@ -308,7 +1349,7 @@ impl<O: Operator> OperatorRuntime<O> {
                 };
             }
             Event::Deleted(object) => {
-                let key: ObjectKey = (&object).into();
+                let key = self.key_of(&object);
                 let event = ObjectEvent::<O::Manifest>::Deleted {
                     name: key.name().to_string(),
                     namespace: key.namespace().cloned(),
@@ -321,34 +1362,374 @@ impl<O: Operator> OperatorRuntime<O> {
         }
     }
 
-    /// Listens for updates to objects and forwards them to queue.
+    /// Apply an event of the watcher restricted to `namespace`, or of the
+    /// only configured namespace, to the store.
+    async fn reflect(&self, namespace: Option<&str>, event: DynamicEvent) {
+        let gvk = GroupVersionKind::gvk(
+            &O::Manifest::group(&*self.dyntype),
+            &O::Manifest::version(&*self.dyntype),
+            &O::Manifest::kind(&*self.dyntype),
+        );
+        let namespace = match (namespace, self.namespaces.as_slice()) {
+            (Some(namespace), _) => Some(namespace),
+            (None, [namespace]) => Some(namespace.as_str()),
+            (None, _) => None,
+        };
+        self.store.apply(&gvk, namespace, event).await;
+    }
+
+    /// Watch objects in each configured namespace, or in all namespaces,
+    /// tagging each event with the namespace of the watcher it came from.
+    fn informer(
+        &self,
+    ) -> futures::stream::BoxStream<
+        'static,
+        (Option<String>, Result<Event<O::Manifest>, watcher::Error>),
+    > {
+        // Bookmarks keep the watcher's resourceVersion current, so that a
+        // dropped watch resumes where it left off rather than re-listing.
+        let list_params = ListParams {
+            bookmarks: true,
+            ..self.list_params.clone()
+        };
+        if self.namespaces.is_empty() {
+            let api = Api::<O::Manifest>::all_with(self.client.clone(), &*self.dyntype);
+            watcher(api, list_params)
+                .map(|event| (None::<String>, event))
+                .boxed()
+        } else {
+            futures::stream::select_all(self.namespaces.iter().map(|namespace| {
+                let api = Api::<O::Manifest>::namespaced_with(
+                    self.client.clone(),
+                    namespace,
+                    &*self.dyntype,
+                );
+                let namespace = namespace.clone();
+                watcher(api, list_params.clone())
+                    .map(move |event| (Some(namespace.clone()), event))
+                    .boxed()
+            }))
+            .boxed()
+        }
+    }
+
+    /// Listens for updates to objects and forwards them to queue. Returns
+    /// once shutdown is requested.
     pub async fn main_loop(&mut self) {
-        let api = Api::<O::Manifest>::all(self.client.clone());
-        let mut informer = watcher(api, self.list_params.clone()).boxed();
+        let mut informer = self.informer();
+        let mut list_params_updates = self.list_params_updates.clone();
+        let shutdown = wait_shutdown(self.shutdown_rx.clone());
+        tokio::pin!(shutdown);
+        let mut external_shutdown = match self.shutdown_on.clone() {
+            Some(signal) => wait_shutdown(signal).boxed(),
+            None => futures::future::pending::<()>().boxed(),
+        };
+        let mut resync_interval = self.resync_period.map(|period| {
+            let mut interval =
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            interval
+        });
+        let mut leader = self.leader.clone();
+        let lost_leadership = async move {
+            match leader {
+                Some(ref mut leader) => wait_for_leadership(leader, false).await,
+                None => futures::future::pending::<()>().await,
+            }
+        };
+        tokio::pin!(lost_leadership);
+        let mut paused = self.pause.subscribe();
+        let mut failures: u32 = 0;
         loop {
-            match informer.try_next().await {
-                Ok(Some(event)) => self.handle_event(event).await,
-                Ok(None) => break,
-                Err(error) => warn!(?error, "Error streaming object events."),
+            tokio::select! {
+                event = informer.next() => match event {
+                    Some((namespace, Ok(event))) => {
+                        failures = 0;
+                        self.handle_namespaced_event(namespace.as_deref(), event).await
+                    }
+                    Some((namespace, Err(error))) => {
+                        failures = failures.saturating_add(1);
+                        let delay = self.watch_backoff.delay(failures);
+                        warn!(
+                            ?namespace,
+                            ?error,
+                            failures,
+                            ?delay,
+                            "Error streaming object events."
+                        );
+                        if let Some(ref hook) = self.on_watch_error {
+                            hook(failures, &error);
+                        }
+                        tokio::select! {
+                            _ = tokio::time::sleep(delay) => (),
+                            _ = wait_shutdown(self.shutdown_rx.clone()) => (),
+                        }
+                    }
+                    None => break,
+                },
+                _ = tick(&mut resync_interval) => self.periodic_resync().await,
+                Some(list_params) = next_list_params(&mut list_params_updates) => {
+                    info!(
+                        label_selector = ?list_params.label_selector,
+                        field_selector = ?list_params.field_selector,
+                        "List params changed, restarting watcher."
+                    );
+                    self.list_params = list_params;
+                    // The new watchers start with a `Restarted` event, which
+                    // resyncs the queue.
+                    informer = self.informer();
+                    failures = 0;
+                }
+                Ok(()) = paused.changed() => self.catch_up_after_pause().await,
+                _ = &mut shutdown => {
+                    info!("Shutdown requested, stopping watcher.");
+                    break;
+                }
+                _ = &mut external_shutdown => {
+                    info!("Got shutdown signal, stopping watcher.");
+                    let _ = self.shutdown_tx.send(true);
+                    break;
+                }
+                _ = &mut lost_leadership => {
+                    warn!("Lost leadership, stopping watcher.");
+                    let _ = self.shutdown_tx.send(true);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Stop all object tasks and wait for them to reach a safe point, or for
+    /// the drain timeout to elapse.
+    pub(crate) async fn drain(&mut self) {
+        let _ = self.shutdown_tx.send(true);
+        // Dropping the senders closes each object's event channel.
+        self.handlers.clear();
+        self.drain_tx.take();
+        info!("Waiting for object state machines to exit.");
+        match self.drain_timeout {
+            Some(timeout) => {
+                if tokio::time::timeout(timeout, self.drain_rx.recv())
+                    .await
+                    .is_err()
+                {
+                    warn!(
+                        ?timeout,
+                        "Timed out waiting for object state machines to exit."
+                    );
+                    return;
+                }
+            }
+            None => {
+                self.drain_rx.recv().await;
             }
         }
+        info!("All object state machines exited.");
+    }
+
+    /// Run the operator's [on_start](Operator::on_start) hook.
+    async fn run_on_start(&self) -> anyhow::Result<()> {
+        if let Some(ref graph) = self.graph {
+            graph.validate()?;
+        }
+        info!("Running operator startup hook.");
+        self.operator
+            .on_start(self.client.clone())
+            .await
+            .context("Operator startup hook failed")
+    }
+
+    /// Decide where and how status is written according to the operator's
+    /// [status_mode](Operator::status_mode) and
+    /// [status_patch_strategy](Operator::status_patch_strategy).
+    pub(crate) async fn resolve_status_options(&mut self) {
+        self.status.inline = match self.operator.status_mode() {
+            StatusMode::Subresource => false,
+            StatusMode::Inline => true,
+            StatusMode::Detect => !self.detect_status_subresource().await,
+        };
+        self.status.strategy = self.operator.status_patch_strategy();
+        debug!(
+            inline_status = self.status.inline,
+            strategy = ?self.status.strategy,
+            "Resolved status options."
+        );
     }
 
-    /// Start Operator (blocks forever).
+    /// Whether the CustomResourceDefinition of the watched resource enables
+    /// the status subresource. Assumes it does if there is no definition.
+    async fn detect_status_subresource(&self) -> bool {
+        use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+        let group = O::Manifest::group(&*self.dyntype);
+        if group.is_empty() {
+            return true;
+        }
+        let name = format!("{}.{}", O::Manifest::plural(&*self.dyntype), group);
+        let version = O::Manifest::version(&*self.dyntype);
+        let api: Api<CustomResourceDefinition> = Api::all(self.client.clone());
+        match api.get(&name).await {
+            Ok(crd) => crd
+                .spec
+                .versions
+                .iter()
+                .find(|v| v.name == version)
+                .map(|v| {
+                    v.subresources
+                        .as_ref()
+                        .and_then(|subresources| subresources.status.as_ref())
+                        .is_some()
+                })
+                .unwrap_or(true),
+            Err(kube::Error::Api(kube::error::ErrorResponse { code, .. })) if code == 404 => {
+                debug!(%name, "No CustomResourceDefinition, assuming status subresource.");
+                true
+            }
+            Err(error) => {
+                warn!(
+                    %name,
+                    ?error,
+                    "Unable to read CustomResourceDefinition, assuming status subresource."
+                );
+                true
+            }
+        }
+    }
+
+    /// Start Operator. Blocks until shutdown is requested through a
+    /// [ShutdownHandle](crate::ShutdownHandle) and running state machines
+    /// have been drained.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error without watching anything if the operator's
+    /// [on_start](Operator::on_start) hook fails, or if the state graph is
+    /// invalid when [with_graph_validation](Self::with_graph_validation) is
+    /// used.
     #[cfg(not(feature = "admission-webhook"))]
-    pub async fn start(&mut self) {
-        self.main_loop().await;
+    pub async fn start(&mut self) -> anyhow::Result<()> {
+        self.run_on_start().await?;
+        self.resolve_status_options().await;
+        if self.sync_watches().await && self.acquire_leadership().await {
+            self.spawn_background_tasks().await;
+            self.main_loop().await;
+        }
+        self.drain().await;
+        if let Some(task) = self.leader_task.take() {
+            task.release().await;
+        }
+        Ok(())
     }
 
-    /// Start Operator (blocks forever).
+    /// Start Operator. Blocks until shutdown is requested through a
+    /// [ShutdownHandle](crate::ShutdownHandle) and running state machines
+    /// have been drained.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error without watching anything if the operator's
+    /// [on_start](Operator::on_start) hook fails, or if the state graph is
+    /// invalid when [with_graph_validation](Self::with_graph_validation) is
+    /// used. Returns an error after draining if the admission webhook server
+    /// fails, for example because it cannot bind its port.
     #[cfg(feature = "admission-webhook")]
-    pub async fn start(&mut self) {
-        let hook = crate::admission::endpoint(Arc::clone(&self.operator));
-        let main = self.main_loop();
-        tokio::select!(
-            _ = main => warn!("Main loop exited"),
-            _ = hook => warn!("Admission hook exited."),
-        )
+    pub async fn start(&mut self) -> anyhow::Result<()> {
+        self.run_on_start().await?;
+        self.resolve_status_options().await;
+        let hook = match self.cluster {
+            Some(_) => futures::future::pending().right_future(),
+            None => crate::admission::endpoint(
+                Arc::clone(&self.operator),
+                self.admission.clone(),
+                self.admission_server.take(),
+            )
+            .left_future(),
+        };
+        // The webhook is served by every replica, regardless of leadership.
+        let main = async {
+            if self.sync_watches().await && self.acquire_leadership().await {
+                self.spawn_background_tasks().await;
+                self.main_loop().await;
+            }
+        };
+        let result = tokio::select!(
+            _ = main => {
+                info!("Main loop exited");
+                Ok(())
+            }
+            result = hook => {
+                warn!("Admission hook exited.");
+                result.context("Admission webhook server failed")
+            }
+        );
+        self.drain().await;
+        if let Some(task) = self.leader_task.take() {
+            task.release().await;
+        }
+        result
+    }
+}
+
+/// Cache the objects of `watched` in `store` until shutdown is requested,
+/// retrying errors according to `backoff`. Holds `_drain` so that draining
+/// waits for it to exit.
+async fn reflect_watch(
+    client: Client,
+    watched: Watch,
+    store: Store,
+    backoff: Backoff,
+    shutdown: watch::Receiver<bool>,
+    _drain: Sender<()>,
+) {
+    let resource = ApiResource::from_gvk(&watched.gvk);
+    let api: Api<DynamicObject> = match watched.namespace {
+        Some(ref namespace) => Api::namespaced_with(client, namespace, &resource),
+        None => Api::all_with(client, &resource),
+    };
+    let list_params = ListParams {
+        bookmarks: true,
+        ..watched.list_params.clone()
+    };
+    let mut events = watcher(api, list_params).boxed();
+    let mut failures: u32 = 0;
+    loop {
+        let next = tokio::select! {
+            next = events.next() => next,
+            _ = wait_shutdown(shutdown.clone()) => break,
+        };
+        match next {
+            Some(Ok(event)) => {
+                failures = 0;
+                store
+                    .apply(&watched.gvk, watched.namespace.as_deref(), event)
+                    .await;
+            }
+            Some(Err(error)) => {
+                failures = failures.saturating_add(1);
+                let delay = backoff.delay(failures);
+                warn!(
+                    gvk = ?watched.gvk,
+                    ?error,
+                    failures,
+                    ?delay,
+                    "Error streaming watched object events."
+                );
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => (),
+                    _ = wait_shutdown(shutdown.clone()) => break,
+                }
+            }
+            None => break,
+        }
+    }
+}
+
+/// Resolves on the next tick of `interval`, or never if there is none.
+async fn tick(interval: &mut Option<tokio::time::Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => futures::future::pending().await,
     }
 }
 
@@ -364,95 +1745,556 @@ async fn wait_event(event: Arc<RwLock<bool>>) {
     }
 }
 
-async fn run_object_task<O: Operator>(
+/// Resolves with the next value sent on `updates`, or never if there is no
+/// channel or its sender was dropped.
+async fn next_list_params(updates: &mut Option<watch::Receiver<ListParams>>) -> Option<ListParams> {
+    match updates {
+        Some(updates) => match updates.changed().await {
+            Ok(()) => Some(updates.borrow().clone()),
+            Err(_) => futures::future::pending().await,
+        },
+        None => futures::future::pending().await,
+    }
+}
+
+/// Resolves `timeout` after shutdown is requested, or never if there is no
+/// timeout.
+async fn wait_cancel(shutdown: watch::Receiver<bool>, timeout: Option<Duration>) {
+    match timeout {
+        Some(timeout) => {
+            wait_shutdown(shutdown).await;
+            tokio::time::sleep(timeout).await;
+        }
+        None => futures::future::pending().await,
+    }
+}
+
+/// Delay before the first restart of a failed object task.
+const RESTART_BACKOFF_MIN: Duration = Duration::from_secs(1);
+/// Upper bound on the delay between restarts of a failed object task.
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(300);
+/// Time after which a restarted object task is considered healthy again, so
+/// that its next restart starts over from `RESTART_BACKOFF_MIN`.
+const RESTART_BACKOFF_RESET: Duration = Duration::from_secs(600);
+
+/// Settings and handles shared by every object task of a runtime.
+struct ObjectTaskContext<O: Operator> {
     client: Client,
+    dyntype: Arc<<O::Manifest as Resource>::DynamicType>,
+    operator: Arc<O>,
+    shutdown: watch::Receiver<bool>,
+    /// Limits how many state machines execute a state at the same time.
+    concurrency: Option<Arc<Semaphore>>,
+    /// How long an executing state may continue after shutdown is requested.
+    cancel_timeout: Option<Duration>,
+    /// Delay between attempts to initialize the object state.
+    init_backoff: Backoff,
+    middleware: Vec<Arc<dyn StateMiddleware<O::ObjectState>>>,
+    /// Number of transitions to record in each object's status.
+    state_history: Option<usize>,
+    state_metrics: Option<StateMetrics>,
+    state_tracker: Option<StateTracker>,
+    status: StatusOptions,
+    quarantine: Option<Quarantine>,
+    // Held until the task exits so that the runtime can wait for it to drain.
+    _drain: Sender<()>,
+}
+
+impl<O: Operator> Clone for ObjectTaskContext<O> {
+    fn clone(&self) -> Self {
+        ObjectTaskContext {
+            client: self.client.clone(),
+            dyntype: Arc::clone(&self.dyntype),
+            operator: Arc::clone(&self.operator),
+            shutdown: self.shutdown.clone(),
+            concurrency: self.concurrency.clone(),
+            cancel_timeout: self.cancel_timeout,
+            init_backoff: self.init_backoff.clone(),
+            middleware: self.middleware.clone(),
+            state_history: self.state_history,
+            state_metrics: self.state_metrics.clone(),
+            state_tracker: self.state_tracker.clone(),
+            status: self.status.clone(),
+            quarantine: self.quarantine.clone(),
+            _drain: self._drain.clone(),
+        }
+    }
+}
+
+/// Removes an object from the state tracker once its task exits.
+struct TrackedObject {
+    tracker: StateTracker,
+    namespace: Option<String>,
+    name: String,
+}
+
+impl Drop for TrackedObject {
+    fn drop(&mut self) {
+        self.tracker.remove(&self.namespace, &self.name);
+    }
+}
+
+/// Initializes the object's state and runs `run_object_task`, restarting the
+/// state machine from `InitialState`, with exponential backoff, if it panics
+/// or exits before the object is deleted. Failures to initialize the object
+/// state are retried according to the runtime's initialization backoff.
+async fn supervise_object_task<O: Operator>(
+    context: ObjectTaskContext<O>,
+    manifest: Manifest<O::Manifest>,
+    deleted: Arc<RwLock<bool>>,
+    deleted_event: Arc<RwLock<bool>>,
+) {
+    let operator = Arc::clone(&context.operator);
+    let _tracked = context.state_tracker.clone().map(|tracker| {
+        let latest = manifest.latest();
+        TrackedObject {
+            tracker,
+            namespace: latest.namespace(),
+            name: latest.name(),
+        }
+    });
+    let mut backoff = RESTART_BACKOFF_MIN;
+    let mut init_failures: u32 = 0;
+    loop {
+        let object_state = match operator.initialize_object_state(&manifest.latest()).await {
+            Ok(object_state) => object_state,
+            Err(error) => {
+                init_failures = init_failures.saturating_add(1);
+                let delay = context.init_backoff.delay(init_failures);
+                warn!(
+                    ?error,
+                    failures = init_failures,
+                    ?delay,
+                    "Unable to initialize object state."
+                );
+                operator.error_hook(manifest.clone(), &error).await;
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => (),
+                    _ = wait_shutdown(context.shutdown.clone()) => return,
+                }
+                if *deleted_event.read().await {
+                    debug!("Object deleted before its state could be initialized.");
+                    return;
+                }
+                continue;
+            }
+        };
+        init_failures = 0;
+
+        let started = tokio::time::Instant::now();
+        let task = tokio::spawn(run_object_task::<O>(
+            context.clone(),
+            manifest.clone(),
+            operator.shared_state().await,
+            object_state,
+            Arc::clone(&deleted),
+            Arc::clone(&deleted_event),
+        ));
+        match task.await {
+            // The task finishes on its own once the object was finalized.
+            Ok(()) if *deleted.read().await => return,
+            Ok(()) => warn!("Object task exited before the object was deleted."),
+            Err(error) if error.is_panic() => error!(?error, "Object task panicked."),
+            Err(error) => {
+                warn!(?error, "Object task was cancelled.");
+                return;
+            }
+        }
+        if *context.shutdown.borrow() {
+            return;
+        }
+        if *deleted_event.read().await {
+            debug!("Object deleted, not restarting its task.");
+            return;
+        }
+
+        if started.elapsed() >= RESTART_BACKOFF_RESET {
+            backoff = RESTART_BACKOFF_MIN;
+        }
+        debug!(?backoff, "Restarting object task from initial state.");
+        tokio::select! {
+            _ = tokio::time::sleep(backoff) => (),
+            _ = wait_shutdown(context.shutdown.clone()) => return,
+        }
+        if *deleted_event.read().await {
+            debug!("Object deleted before its task could be restarted.");
+            return;
+        }
+        backoff = std::cmp::min(backoff * 2, RESTART_BACKOFF_MAX);
+    }
+}
+
+async fn run_object_task<O: Operator>(
+    context: ObjectTaskContext<O>,
     manifest: Manifest<O::Manifest>,
     shared: SharedState<<O::ObjectState as ObjectState>::SharedState>,
     mut object_state: O::ObjectState,
     deleted: Arc<RwLock<bool>>,
     deleted_event: Arc<RwLock<bool>>,
-    operator: Arc<O>,
 ) {
-    debug!("Running registration hook.");
-    let state: O::InitialState = Default::default();
+    let client = context.client.clone();
+    let operator = Arc::clone(&context.operator);
+    let on_error: ErrorHook = {
+        let operator = Arc::clone(&operator);
+        let manifest = manifest.clone();
+        Arc::new(move |error: anyhow::Error| {
+            let operator = Arc::clone(&operator);
+            let manifest = manifest.clone();
+            async move { operator.error_hook(manifest, &error).await }.boxed()
+        })
+    };
+    let run_context = RunContext {
+        shutdown: Some(context.shutdown.clone()),
+        concurrency: context.concurrency.clone(),
+        on_error: Some(Arc::clone(&on_error)),
+        middleware: context.middleware.clone(),
+        history: context.state_history,
+        metrics: context.state_metrics.clone(),
+        tracker: context.state_tracker.clone(),
+        status: context.status.clone(),
+        error_policy: Some(operator.error_policy()),
+        quarantine: context.quarantine.clone(),
+    };
+    // The deleted state always runs to completion.
+    let deleted_run_context = RunContext {
+        shutdown: None,
+        ..run_context.clone()
+    };
     let (namespace, name) = {
         let m = manifest.latest();
-        match operator.registration_hook(manifest.clone()).await {
-            Ok(()) => debug!("Running hook complete."),
-            Err(e) => {
-                error!(
-                    "Operator registration hook for object {} in namespace {:?} failed: {:?}",
-                    m.name(),
-                    m.namespace(),
-                    e
-                );
-                return;
+        (m.namespace(), m.name())
+    };
+
+    debug!("Running registration hook.");
+    let mut failures: u32 = 0;
+    let state: anyhow::Result<Box<dyn State<O::ObjectState>>> = loop {
+        let generation = manifest.meta().generation;
+        let error = match with_timeout(
+            "Registration",
+            operator.registration_hook_timeout(),
+            operator.registration_hook(manifest.clone()),
+        )
+        .await
+        {
+            Ok(()) => {
+                debug!("Running hook complete.");
+                let initial: Box<dyn State<O::ObjectState>> = Box::new(O::InitialState::default());
+                break Ok(initial);
             }
+            Err(error) => error,
+        };
+        error!(?namespace, %name, ?error, "Operator registration hook failed.");
+        failures = failures.saturating_add(1);
+        let action = run_context.decide(ErrorSource::RegistrationHook, &error, failures);
+        let delay = match action {
+            ErrorAction::Enter(state) => {
+                on_error(error).await;
+                let state: Box<dyn State<O::ObjectState>> = state.into();
+                break Ok(state);
+            }
+            action => match action.delay(failures) {
+                Some(delay) => delay,
+                // Reported along with other permanent failures.
+                None => break Err(error),
+            },
+        };
+        let message = format!("{:#}", error);
+        on_error(error).await;
+        if !run_context
+            .wait_retry(&manifest, generation, delay, failures, &message)
+            .await
+        {
+            return;
         }
-        (m.namespace(), m.name())
     };
 
-    tokio::select! {
-        _ = run_to_completion(&client, state, shared.clone(), &mut object_state, manifest.clone()) => (),
+    let run = match state {
+        Ok(state) => run_with_policy::<_, O::InitialState>(
+            &client,
+            &*context.dyntype,
+            state,
+            shared.clone(),
+            &mut object_state,
+            manifest.clone(),
+            &run_context,
+        )
+        .left_future(),
+        Err(error) => {
+            warn!(?namespace, %name, "Not running state machine after registration failed.");
+            futures::future::ready(Err(error)).right_future()
+        }
+    };
+    let result = tokio::select! {
+        result = run => result,
+        _ = wait_cancel(context.shutdown.clone(), context.cancel_timeout) => {
+            warn!(?namespace, %name, "Cancelled executing state after shutdown.");
+            Ok(())
+        }
         _ = wait_event(Arc::clone(&deleted)) => {
             let state: O::DeletedState = Default::default();
             debug!("Object {} in namespace {:?} terminated. Jumping to state {:?}.", name, &namespace, state);
-            run_to_completion(&client, state, shared.clone(), &mut object_state, manifest.clone()).await;
+            run_with_policy::<_, O::DeletedState>(&client, &*context.dyntype, Box::new(state), shared.clone(), &mut object_state, manifest.clone(), &deleted_run_context).await
+        }
+    };
+    if let Err(error) = result {
+        warn!(?namespace, %name, ?error, "Object reconciliation failed permanently.");
+        if let Some(ref metrics) = context.state_metrics {
+            metrics.failed_permanently();
         }
+        operator.failure_hook(manifest.clone(), &error).await;
+        on_error(error).await;
     }
 
     debug!(
         "Resource {} in namespace {:?} waiting for deregistration.",
         name, namespace
     );
-    wait_event(Arc::clone(&deleted)).await;
+    tokio::select! {
+        biased;
+        _ = wait_event(Arc::clone(&deleted)) => (),
+        _ = wait_shutdown(context.shutdown.clone()) => {
+            debug!(?namespace, %name, "Runtime shutting down, handing off object.");
+            let mut failures: u32 = 0;
+            while let Err(error) = operator.handoff_hook(manifest.clone(), &mut object_state).await {
+                warn!(?namespace, %name, ?error, "Operator handoff hook failed.");
+                failures = failures.saturating_add(1);
+                let action = run_context.decide(ErrorSource::HandoffHook, &error, failures);
+                on_error(error).await;
+                match action.delay(failures) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => break,
+                }
+            }
+            return;
+        }
+    }
+
+    let mut failures: u32 = 0;
+    loop {
+        let generation = manifest.meta().generation;
+        let error = match operator
+            .finalize_hook(manifest.clone(), &mut object_state)
+            .await
+        {
+            Ok(()) => break,
+            Err(error) => error,
+        };
+        warn!(?namespace, %name, ?error, "Operator finalize hook failed.");
+        failures = failures.saturating_add(1);
+        match retry_failed(
+            &run_context,
+            &on_error,
+            &manifest,
+            generation,
+            ErrorSource::FinalizeHook,
+            error,
+            failures,
+        )
+        .await
+        {
+            Retry::Again => debug!(?namespace, %name, "Retrying finalize hook."),
+            Retry::GiveUp => break,
+            Retry::Shutdown => {
+                debug!(?namespace, %name, "Runtime shutting down, finalization incomplete.");
+                return;
+            }
+        }
+    }
+
     {
         let mut state_writer = shared.write().await;
         object_state.async_drop(&mut state_writer).await;
     }
 
-    match operator.deregistration_hook(manifest.clone()).await {
-        Ok(()) => (),
-        Err(e) => warn!(
-            "Operator deregistration hook for object {} in namespace {:?} failed: {:?}",
-            name, namespace, e
-        ),
+    let mut failures: u32 = 0;
+    loop {
+        let generation = manifest.meta().generation;
+        let error = match with_timeout(
+            "Deregistration",
+            operator.deregistration_hook_timeout(),
+            operator.deregistration_hook(manifest.clone()),
+        )
+        .await
+        {
+            Ok(()) => break,
+            Err(error) => error,
+        };
+        warn!(?namespace, %name, ?error, "Operator deregistration hook failed.");
+        failures = failures.saturating_add(1);
+        match retry_failed(
+            &run_context,
+            &on_error,
+            &manifest,
+            generation,
+            ErrorSource::DeregistrationHook,
+            error,
+            failures,
+        )
+        .await
+        {
+            Retry::Again => (),
+            Retry::GiveUp => break,
+            Retry::Shutdown => return,
+        }
     }
 
     let api_client: Api<O::Manifest> = match namespace {
-        Some(ref namespace) => kube::Api::namespaced(client, namespace),
-        None => kube::Api::all(client),
-    };
-
-    let dp = kube::api::DeleteParams {
-        grace_period_seconds: Some(0),
-        ..Default::default()
+        Some(ref namespace) => kube::Api::namespaced_with(client, namespace, &*context.dyntype),
+        None => kube::Api::all_with(client, &*context.dyntype),
     };
 
-    match api_client.delete(&name, &dp).await {
-        Ok(_) => {
-            debug!(
-                ?namespace,
-                %name,
-                "Object deregistered"
-            );
-        }
-        Err(e) => match e {
-            // Ignore not found, already deleted. This could happen if resource was force deleted.
-            kube::error::Error::Api(kube::error::ErrorResponse { code, .. }) if code == 404 => {
-                debug!(?namespace, %name, "Object already deleted")
+    let mut failures: u32 = 0;
+    loop {
+        let generation = manifest.meta().generation;
+        let error = match deregister(&*operator, &api_client, &name, &manifest).await {
+            Ok(()) => {
+                debug!(?namespace, %name, "Object deregistered");
+                break;
             }
-            error => {
-                warn!(
-                    ?namespace,
-                    %name,
-                    ?error,
-                    "Unable to deregister object with Kubernetes API"
-                );
+            // Ignore not found, already deleted. This could happen if resource was force deleted.
+            Err(error) if has_status(&error, 404) => {
+                debug!(?namespace, %name, "Object already deleted");
+                break;
             }
-        },
+            Err(error) => error,
+        };
+        warn!(
+            ?namespace,
+            %name,
+            ?error,
+            "Unable to deregister object with Kubernetes API"
+        );
+        failures = failures.saturating_add(1);
+        match retry_failed(
+            &run_context,
+            &on_error,
+            &manifest,
+            generation,
+            ErrorSource::Deregistration,
+            error,
+            failures,
+        )
+        .await
+        {
+            Retry::Again => (),
+            Retry::GiveUp => break,
+            Retry::Shutdown => return,
+        }
+    }
+
+    // Unless krator deleted the object, something else may keep it around for
+    // a while, so don't hold up shutdown.
+    tokio::select! {
+        _ = wait_event(deleted_event) => debug!(?namespace, %name, "Object deleted"),
+        _ = wait_shutdown(context.shutdown.clone()) => (),
+    }
+}
+
+/// Run `hook`, cancelling it and failing if it does not complete within
+/// `timeout`.
+async fn with_timeout(
+    name: &str,
+    timeout: Option<Duration>,
+    hook: impl Future<Output = anyhow::Result<()>>,
+) -> anyhow::Result<()> {
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, hook)
+            .await
+            .unwrap_or_else(|_| {
+                Err(anyhow::anyhow!(
+                    "{} hook timed out after {:?}",
+                    name,
+                    timeout
+                ))
+            }),
+        None => hook.await,
+    }
+}
+
+/// Apply the operator's deregistration policy to the object, retrying
+/// conflicts.
+async fn deregister<O: Operator>(
+    operator: &O,
+    api: &Api<O::Manifest>,
+    name: &str,
+    manifest: &Manifest<O::Manifest>,
+) -> anyhow::Result<()> {
+    match operator.deregistration_policy() {
+        DeregistrationPolicy::Delete => {
+            retry_conflicts(api, name, |_| async {
+                let dp = kube::api::DeleteParams {
+                    grace_period_seconds: Some(0),
+                    ..Default::default()
+                };
+                api.delete(name, &dp).await?;
+                Ok(())
+            })
+            .await
+        }
+        DeregistrationPolicy::RemoveFinalizerOnly(finalizer) => {
+            retry_conflicts(api, name, |latest: Option<O::Manifest>| {
+                let latest = latest.unwrap_or_else(|| manifest.latest());
+                let finalizers: Vec<String> = latest
+                    .finalizers()
+                    .iter()
+                    .filter(|f| **f != finalizer)
+                    .cloned()
+                    .collect();
+                // Fail on concurrent changes rather than dropping them.
+                let patch = serde_json::json!({
+                    "metadata": {
+                        "finalizers": finalizers,
+                        "resourceVersion": latest.resource_version(),
+                    }
+                });
+                async move {
+                    api.patch(name, &PatchParams::default(), &Patch::Merge(&patch))
+                        .await?;
+                    Ok(())
+                }
+            })
+            .await
+        }
+        DeregistrationPolicy::None => Ok(()),
     }
+}
+
+/// What to do after a hook or write failed.
+enum Retry {
+    /// Try again now.
+    Again,
+    /// Move on without it.
+    GiveUp,
+    /// Stop, since the runtime is shutting down.
+    Shutdown,
+}
 
-    wait_event(deleted_event).await;
-    debug!(?namespace, %name, "Object deleted");
+/// Report a failed hook or write of the object at `generation` and, if the
+/// error policy retries it, wait before the next attempt.
+async fn retry_failed<S: ObjectState>(
+    context: &RunContext<S>,
+    on_error: &ErrorHook,
+    manifest: &Manifest<S::Manifest>,
+    generation: Option<i64>,
+    source: ErrorSource,
+    error: anyhow::Error,
+    failures: u32,
+) -> Retry
+where
+    S::Manifest: Resource,
+{
+    let delay = context.decide(source, &error, failures).delay(failures);
+    let message = format!("{:#}", error);
+    on_error(error).await;
+    match delay {
+        Some(delay)
+            if context
+                .wait_retry(manifest, generation, delay, failures, &message)
+                .await =>
+        {
+            Retry::Again
+        }
+        Some(_) => Retry::Shutdown,
+        None => Retry::GiveUp,
+    }
 }
@@ -2,11 +2,23 @@
 
 use kube::api::{PatchParams, Resource, ResourceExt};
 use kube::Api;
+use kube_runtime::events::{Event, EventType};
 use serde::de::DeserializeOwned;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
 use tracing::Instrument;
 use tracing::{debug, error, trace, warn};
 
-use crate::object::ObjectStatus;
+use crate::error_policy::{DefaultErrorPolicy, ErrorAction, ErrorPolicy, ErrorSource};
+use crate::graph::short_name;
+use crate::metrics::StateMetrics;
+use crate::object::{ObjectKey, ObjectStatus, StateError};
+use crate::quarantine::Quarantine;
+use crate::status::{StatusOptions, StatusPatcher};
+use crate::tracker::StateTracker;
+use crate::util::Backoff;
 use crate::Manifest;
 // Re-export for compatibility.
 pub use crate::object::ObjectState as ResourceState;
@@ -23,9 +35,17 @@ impl<S: ResourceState> From<StateHolder<S>> for Box<dyn State<S>> {
 }
 
 /// Represents result of state execution and which state to transition to next.
+/// New kinds of transitions may be added, so matches on it need a wildcard
+/// arm.
+#[non_exhaustive]
 pub enum Transition<S: ResourceState> {
     /// Transition to new state.
     Next(StateHolder<S>),
+    /// Re-enter a state once the duration has elapsed.
+    Requeue(StateHolder<S>, Duration),
+    /// Re-enter a state after a failed attempt, according to its
+    /// [retry_backoff](State::retry_backoff).
+    Retry(StateHolder<S>, anyhow::Error),
     /// Stop executing the state machine and report the result of the execution.
     Complete(anyhow::Result<()>),
 }
@@ -55,11 +75,62 @@ impl<S: ResourceState> Transition<S> {
     pub fn next_unchecked<I: State<S>, O: State<S>>(_i: Box<I>, o: O) -> Transition<S> {
         Transition::Next(StateHolder { state: Box::new(o) })
     }
+
+    /// Transition to state `O`, built from `payload`, so that data computed by
+    /// the current state is handed to the next one explicitly instead of
+    /// through `ObjectState` fields. The next state declares what it accepts
+    /// by implementing `From<P>`:
+    ///
+    /// ```ignore
+    /// struct Eat { food: Food }
+    ///
+    /// impl From<Food> for Eat {
+    ///     fn from(food: Food) -> Self {
+    ///         Eat { food }
+    ///     }
+    /// }
+    ///
+    /// // In `Roam::next`:
+    /// Transition::next_with::<Eat, _, _>(self, food)
+    /// ```
+    #[allow(clippy::boxed_local)]
+    pub fn next_with<O, I, P>(_i: Box<I>, payload: P) -> Transition<S>
+    where
+        I: State<S> + TransitionTo<O>,
+        O: State<S> + From<P>,
+    {
+        Transition::Next(StateHolder {
+            state: Box::new(O::from(payload)),
+        })
+    }
+
+    /// Re-enter the current state after `after` has elapsed, instead of
+    /// sleeping or polling inside `next`. The state's status is patched again
+    /// on re-entry. The state machine does not hold a concurrency slot while
+    /// waiting, and stops waiting if the runtime shuts down.
+    pub fn requeue<I: State<S>>(state: Box<I>, after: Duration) -> Transition<S> {
+        Transition::Requeue(StateHolder { state }, after)
+    }
+
+    /// Re-enter the current state after a delay which grows with each
+    /// consecutive retry, according to the state's
+    /// [retry_backoff](State::retry_backoff). Once the state's
+    /// [max_attempts](State::max_attempts) is reached,
+    /// [retries_exhausted](State::retries_exhausted) decides where to go
+    /// instead. The count is kept per object and reset whenever the state
+    /// machine moves to another state.
+    pub fn retry<I: State<S>>(state: Box<I>, error: anyhow::Error) -> Transition<S> {
+        Transition::Retry(StateHolder { state }, error)
+    }
 }
 
 /// Convenience redefinition of Arc<RwLock<T>>
 pub type SharedState<T> = std::sync::Arc<tokio::sync::RwLock<T>>;
 
+/// Builds the transition to take once a state times out, see
+/// [State::on_timeout].
+pub type OnTimeout<S> = Box<dyn FnOnce() -> Transition<S> + Send>;
+
 #[async_trait::async_trait]
 /// A trait representing a node in the state graph.
 pub trait State<S: ResourceState>: Sync + Send + 'static + std::fmt::Debug {
@@ -72,7 +143,120 @@ pub trait State<S: ResourceState>: Sync + Send + 'static + std::fmt::Debug {
     ) -> Transition<S>;
 
     /// Provider supplies JSON status patch to apply when entering this state.
-    async fn status(&self, state: &mut S, manifest: &S::Manifest) -> anyhow::Result<S::Status>;
+    /// Return `None` if the status does not change, to skip the patch.
+    async fn status(
+        &self,
+        state: &mut S,
+        manifest: &S::Manifest,
+    ) -> anyhow::Result<Option<S::Status>>;
+
+    /// Delay between attempts of this state after
+    /// [Transition::retry](Transition::retry).
+    fn retry_backoff(&self) -> Backoff {
+        Backoff::default()
+    }
+
+    /// Number of consecutive attempts of this state, including the first,
+    /// after which [retries_exhausted](State::retries_exhausted) is called
+    /// instead of retrying. Retries forever if `None`.
+    fn max_attempts(&self) -> Option<u32> {
+        None
+    }
+
+    /// Transition to take once [max_attempts](State::max_attempts) is
+    /// reached, with the error passed to the last retry. By default the state
+    /// machine completes with that error.
+    fn retries_exhausted(self: Box<Self>, error: anyhow::Error) -> Transition<S> {
+        Transition::Complete(Err(error))
+    }
+
+    /// Name reported to [StateMiddleware]. Defaults to the type name.
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Maximum time `next` may run. Once exceeded, `next` is cancelled and
+    /// the transition returned by [on_timeout](State::on_timeout) is taken.
+    fn timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Transition to take when `next` exceeds [timeout](State::timeout).
+    /// Called before `next` is entered, so it cannot depend on anything
+    /// `next` does, but the returned closure only runs once the deadline has
+    /// passed. By default the state machine completes with an error.
+    ///
+    /// ```ignore
+    /// fn on_timeout(&self) -> OnTimeout<PodState> {
+    ///     Box::new(|| Transition::next(Box::new(Pull), ImagePullBackoff))
+    /// }
+    /// ```
+    fn on_timeout(&self) -> OnTimeout<S> {
+        let name = self.name();
+        let timeout = self.timeout();
+        Box::new(move || {
+            Transition::Complete(Err(anyhow::anyhow!(
+                "State {} timed out after {:?}.",
+                short_name(name),
+                timeout
+            )))
+        })
+    }
+}
+
+/// The transition produced by a call to [State::next], as reported to
+/// [StateMiddleware::on_exit].
+#[derive(Debug)]
+pub enum StateOutcome<'a> {
+    /// Transitioning to the named state.
+    Next(&'static str),
+    /// Re-entering the state after the delay.
+    Requeue(Duration),
+    /// Retrying the state after the error.
+    Retry(&'a anyhow::Error),
+    /// The state machine completed.
+    Complete(Result<(), &'a anyhow::Error>),
+}
+
+impl StateOutcome<'_> {
+    /// Short description recorded in the state history.
+    fn describe(&self) -> String {
+        match self {
+            StateOutcome::Next(state) => format!("Next: {}", short_name(state)),
+            StateOutcome::Requeue(after) => format!("Requeue: {:?}", after),
+            StateOutcome::Retry(error) => format!("Retry: {}", error),
+            StateOutcome::Complete(Ok(())) => "Complete".to_string(),
+            StateOutcome::Complete(Err(error)) => format!("Error: {}", error),
+        }
+    }
+}
+
+impl<S: ResourceState> Transition<S> {
+    fn outcome(&self) -> StateOutcome<'_> {
+        match self {
+            Transition::Next(s) => StateOutcome::Next(s.state.name()),
+            Transition::Requeue(_, after) => StateOutcome::Requeue(*after),
+            Transition::Retry(_, error) => StateOutcome::Retry(error),
+            Transition::Complete(result) => StateOutcome::Complete(result.as_ref().map(|_| ())),
+        }
+    }
+}
+
+/// Hooks run before and after every call to [State::next], for concerns
+/// such as logging, metrics or locking which apply to every state.
+#[async_trait::async_trait]
+pub trait StateMiddleware<S: ResourceState>: Send + Sync + 'static {
+    /// Called before the state named `state` is entered.
+    async fn on_enter(&self, _state: &'static str, _manifest: &S::Manifest) {}
+
+    /// Called once the state named `state` has returned or timed out.
+    async fn on_exit(
+        &self,
+        _state: &'static str,
+        _manifest: &S::Manifest,
+        _outcome: &StateOutcome<'_>,
+    ) {
+    }
 }
 
 /// Iteratively evaluate state machine until it returns Complete.
@@ -86,6 +270,335 @@ pub async fn run_to_completion<S: ResourceState>(
     S::Manifest: Resource + DeserializeOwned,
     <S::Manifest as kube::Resource>::DynamicType: std::default::Default,
     S::Status: ObjectStatus,
+{
+    // Errors are logged and set as the status already.
+    run_with_context(
+        client,
+        &Default::default(),
+        Box::new(state),
+        shared,
+        object_state,
+        manifest,
+        &RunContext::<S>::default(),
+    )
+    .await
+    .ok();
+}
+
+/// Controls applied by the runtime while evaluating a state machine.
+pub(crate) struct RunContext<S: ResourceState> {
+    /// Once set, stop before entering the next state. A running state is
+    /// never interrupted.
+    pub(crate) shutdown: Option<tokio::sync::watch::Receiver<bool>>,
+    /// Limits how many state machines execute a state at the same time.
+    pub(crate) concurrency: Option<Arc<Semaphore>>,
+    /// Reports errors to the operator.
+    pub(crate) on_error: Option<ErrorHook>,
+    /// Run around every call to `State::next`.
+    pub(crate) middleware: Vec<Arc<dyn StateMiddleware<S>>>,
+    /// Number of transitions to record in `status.stateHistory`, if any.
+    pub(crate) history: Option<usize>,
+    /// Collects per-state counters and durations.
+    pub(crate) metrics: Option<StateMetrics>,
+    /// Tracks the current state of each object.
+    pub(crate) tracker: Option<StateTracker>,
+    /// How status updates are sent.
+    pub(crate) status: StatusOptions,
+    /// Decides how to recover from errors, [DefaultErrorPolicy] if unset.
+    pub(crate) error_policy: Option<Arc<dyn ErrorPolicy<S>>>,
+    /// Where objects which keep failing are parked, if anywhere.
+    pub(crate) quarantine: Option<Quarantine>,
+}
+
+impl<S: ResourceState> Clone for RunContext<S> {
+    fn clone(&self) -> Self {
+        RunContext {
+            shutdown: self.shutdown.clone(),
+            concurrency: self.concurrency.clone(),
+            on_error: self.on_error.clone(),
+            middleware: self.middleware.clone(),
+            history: self.history,
+            metrics: self.metrics.clone(),
+            tracker: self.tracker.clone(),
+            status: self.status.clone(),
+            error_policy: self.error_policy.clone(),
+            quarantine: self.quarantine.clone(),
+        }
+    }
+}
+
+impl<S: ResourceState> Default for RunContext<S> {
+    fn default() -> Self {
+        RunContext {
+            shutdown: None,
+            concurrency: None,
+            on_error: None,
+            middleware: vec![],
+            history: None,
+            metrics: None,
+            tracker: None,
+            status: Default::default(),
+            error_policy: None,
+            quarantine: None,
+        }
+    }
+}
+
+/// Bookkeeping carried from one state to the next within a run.
+#[derive(Default)]
+struct Progress {
+    /// Consecutive retries of the current state.
+    attempts: u32,
+    /// Most recent transitions, oldest first.
+    history: VecDeque<serde_json::Value>,
+}
+
+/// Callback invoked with errors encountered while running a state machine.
+pub(crate) type ErrorHook =
+    Arc<dyn Fn(anyhow::Error) -> futures::future::BoxFuture<'static, ()> + Send + Sync>;
+
+impl<S: ResourceState> RunContext<S> {
+    fn is_shutting_down(&self) -> bool {
+        match self.shutdown {
+            Some(ref shutdown) => *shutdown.borrow(),
+            None => false,
+        }
+    }
+
+    /// Resolves once shutdown is signalled. Never resolves without a
+    /// shutdown channel.
+    async fn shutdown_requested(&self) {
+        let mut shutdown = match self.shutdown {
+            Some(ref shutdown) => shutdown.clone(),
+            None => return futures::future::pending().await,
+        };
+        while !*shutdown.borrow() {
+            if shutdown.changed().await.is_err() {
+                futures::future::pending::<()>().await;
+            }
+        }
+    }
+
+    async fn report_error(&self, error: anyhow::Error) {
+        if let Some(ref on_error) = self.on_error {
+            on_error(error).await;
+        }
+    }
+
+    /// Consult the error policy about `error`.
+    pub(crate) fn decide(
+        &self,
+        source: ErrorSource,
+        error: &anyhow::Error,
+        failures: u32,
+    ) -> ErrorAction<S> {
+        match self.error_policy {
+            Some(ref policy) => policy.on_error(source, error, failures),
+            None => <DefaultErrorPolicy as ErrorPolicy<S>>::on_error(
+                &DefaultErrorPolicy::default(),
+                source,
+                error,
+                failures,
+            ),
+        }
+    }
+
+    /// Wait `delay` before retrying after `failures` consecutive failures of
+    /// the object at `generation`, or, once the quarantine threshold is
+    /// reached, until the object is requeued or its spec moves on from
+    /// `generation`. Returns `false` if shutdown was requested first.
+    pub(crate) async fn wait_retry(
+        &self,
+        manifest: &Manifest<S::Manifest>,
+        generation: Option<i64>,
+        delay: Duration,
+        failures: u32,
+        error: &str,
+    ) -> bool
+    where
+        S::Manifest: Resource,
+    {
+        let meta = manifest.meta();
+        let quarantine = match self.quarantine {
+            // A spec changed since the failure may well fix it.
+            Some(ref quarantine)
+                if failures >= quarantine.threshold && meta.generation == generation =>
+            {
+                quarantine
+            }
+            _ => {
+                return tokio::select! {
+                    _ = tokio::time::sleep(delay) => true,
+                    _ = self.shutdown_requested() => false,
+                }
+            }
+        };
+        let name = meta.name.unwrap_or_default();
+        warn!(
+            namespace = ?meta.namespace,
+            %name,
+            failures,
+            %error,
+            "Quarantining object after repeated failures."
+        );
+        let key = ObjectKey::new(meta.namespace.clone(), name.clone())
+            .with_cluster(manifest.cluster().map(str::to_string));
+        let quarantined = quarantine
+            .handle
+            .enter(&key, failures, error, self.metrics.clone());
+        if let Some(recorder) = manifest.recorder() {
+            let event = Event {
+                type_: EventType::Warning,
+                reason: "Quarantined".to_string(),
+                note: Some(format!(
+                    "Not retrying after {} consecutive failures until requeued: {}",
+                    failures, error
+                )),
+                action: "Reconcile".to_string(),
+                secondary: None,
+            };
+            if let Err(error) = recorder.publish(event).await {
+                warn!(?error, "Unable to publish quarantine event.");
+            }
+        }
+        tokio::select! {
+            _ = quarantined.released() => {
+                debug!(namespace = ?meta.namespace, %name, "Object requeued from quarantine.");
+                true
+            }
+            Some(_) = manifest.changed_where(|_, latest| latest.meta().generation != generation) => {
+                debug!(namespace = ?meta.namespace, %name, "Object spec changed, releasing it from quarantine.");
+                true
+            }
+            _ = self.shutdown_requested() => false,
+        }
+    }
+
+    /// Report a failed status update, and queue it again if the error policy
+    /// retries it.
+    async fn report_patch(
+        &self,
+        result: anyhow::Result<()>,
+        patcher: &mut StatusPatcher<S::Manifest>,
+    ) where
+        S::Manifest: Resource,
+    {
+        if let Err(error) = result {
+            let failures = patcher.failures();
+            if let Some(delay) = self
+                .decide(ErrorSource::StatusPatch, &error, failures)
+                .delay(failures)
+            {
+                debug!(?delay, failures, "Retrying status patch.");
+                patcher.retry_after(delay);
+            }
+            self.report_error(error).await;
+        }
+    }
+
+    /// Send pending status updates, waiting for retries of failed ones unless
+    /// shutdown is requested.
+    async fn finish_patches(&self, patcher: &mut StatusPatcher<S::Manifest>)
+    where
+        S::Manifest: Resource + Clone + DeserializeOwned,
+    {
+        loop {
+            let result = patcher.finish().await;
+            self.report_patch(result, patcher).await;
+            let due = match patcher.due() {
+                Some(due) => due,
+                None => return,
+            };
+            tokio::select! {
+                _ = tokio::time::sleep_until(due) => (),
+                _ = self.shutdown_requested() => return,
+            }
+        }
+    }
+}
+
+/// A state machine which stopped with an error.
+pub(crate) struct Failure {
+    /// Name of the state which returned the error.
+    pub(crate) state: &'static str,
+    pub(crate) error: anyhow::Error,
+}
+
+/// Run the state machine from `state`, consulting the context's error policy
+/// whenever it stops with an error. Retries restart it from `I`. Returns the
+/// error the policy gave up on, which is left for the caller to report.
+pub(crate) async fn run_with_policy<S: ResourceState, I: State<S> + Default>(
+    client: &kube::Client,
+    dyntype: &<S::Manifest as Resource>::DynamicType,
+    mut state: Box<dyn State<S>>,
+    shared: SharedState<S::SharedState>,
+    object_state: &mut S,
+    manifest: Manifest<S::Manifest>,
+    context: &RunContext<S>,
+) -> anyhow::Result<()>
+where
+    S::Manifest: Resource + DeserializeOwned,
+    S::Status: ObjectStatus,
+{
+    let mut failures: u32 = 0;
+    loop {
+        let generation = manifest.meta().generation;
+        let failure = match run_with_context(
+            client,
+            dyntype,
+            state,
+            shared.clone(),
+            object_state,
+            manifest.clone(),
+            context,
+        )
+        .await
+        {
+            Ok(()) => return Ok(()),
+            Err(failure) => failure,
+        };
+        failures = failures.saturating_add(1);
+        let action = context.decide(ErrorSource::State(failure.state), &failure.error, failures);
+        if action.delay(failures).is_none() && !matches!(action, ErrorAction::Enter(_)) {
+            return Err(failure.error);
+        }
+        let message = format!("{:#}", failure.error);
+        context.report_error(failure.error).await;
+        state = match action {
+            ErrorAction::Enter(next_state) => {
+                let next_state: Box<dyn State<S>> = next_state.into();
+                debug!(?next_state, "Error policy entering state.");
+                next_state
+            }
+            action => {
+                let delay = action.delay(failures).unwrap_or_default();
+                debug!(?delay, failures, "Error policy restarting state machine.");
+                if !context
+                    .wait_retry(&manifest, generation, delay, failures, &message)
+                    .await
+                {
+                    return Ok(());
+                }
+                Box::new(I::default())
+            }
+        };
+    }
+}
+
+/// Iteratively evaluate state machine until it returns Complete or the
+/// context signals shutdown.
+pub(crate) async fn run_with_context<S: ResourceState>(
+    client: &kube::Client,
+    dyntype: &<S::Manifest as Resource>::DynamicType,
+    mut state: Box<dyn State<S>>,
+    shared: SharedState<S::SharedState>,
+    object_state: &mut S,
+    manifest: Manifest<S::Manifest>,
+    context: &RunContext<S>,
+) -> Result<(), Failure>
+where
+    S::Manifest: Resource + DeserializeOwned,
+    S::Status: ObjectStatus,
 {
     let (name, namespace, api) = {
         let initial_manifest = manifest.latest();
@@ -93,46 +606,110 @@ pub async fn run_to_completion<S: ResourceState>(
         let name = initial_manifest.name();
 
         let api: Api<S::Manifest> = match namespace {
-            Some(ref namespace) => Api::namespaced(client.clone(), namespace),
-            None => Api::all(client.clone()),
+            Some(ref namespace) => Api::namespaced_with(client.clone(), namespace, dyntype),
+            None => Api::all_with(client.clone(), dyntype),
         };
         (name, namespace, api)
     };
+    let mut patcher = StatusPatcher::new(
+        api,
+        name.clone(),
+        dyntype,
+        &context.status,
+        manifest.last_status.clone(),
+    );
 
-    let mut state: Box<dyn State<S>> = Box::new(state);
+    let mut progress = Progress::default();
 
-    loop {
-        state = match execute_object_state(
+    let result = loop {
+        let permit = match context.concurrency {
+            Some(ref concurrency) => Some(
+                concurrency
+                    .acquire()
+                    .await
+                    .expect("Concurrency semaphore is never closed."),
+            ),
+            None => None,
+        };
+        let state_name = state.name();
+        let (next_state, requeue_after) = match execute_object_state(
             &name,
             &namespace,
             state,
-            &api,
+            &mut patcher,
             &shared,
             object_state,
             &manifest,
+            context,
+            &mut progress,
         )
         .await
         {
-            Some(state) => state,
-            None => break,
+            Step::Enter(next_state, requeue_after) => (next_state, requeue_after),
+            Step::Complete(result) => {
+                break result.map_err(|error| Failure {
+                    state: state_name,
+                    error,
+                })
+            }
+        };
+        state = next_state;
+        drop(permit);
+        if let Some(after) = requeue_after {
+            context
+                .report_patch(patcher.flush().await, &mut patcher)
+                .await;
+            trace!(?state, ?after, "Waiting to re-enter state.");
+            let wait = tokio::time::sleep(after);
+            tokio::pin!(wait);
+            // Send patches held back by the rate limit while waiting.
+            loop {
+                let flush_at = patcher.due();
+                let due =
+                    tokio::time::sleep_until(flush_at.unwrap_or_else(tokio::time::Instant::now));
+                tokio::select! {
+                    _ = &mut wait => break,
+                    _ = context.shutdown_requested() => break,
+                    _ = due, if flush_at.is_some() => {
+                        context.report_patch(patcher.flush().await, &mut patcher).await;
+                    }
+                }
+            }
         }
-    }
+        if context.is_shutting_down() {
+            debug!(?state, "Shutdown requested, not entering next state.");
+            break Ok(());
+        }
+    };
+    context.finish_patches(&mut patcher).await;
+    result
 }
 
-#[tracing::instrument(level = "trace", skip(object_state, manifest, api, shared))]
+#[tracing::instrument(
+    level = "trace",
+    skip(object_state, manifest, patcher, shared, context, progress)
+)]
+#[allow(clippy::too_many_arguments)]
 async fn execute_object_state<S: ResourceState>(
     name: &str,
     namespace: &Option<String>,
     state: Box<dyn State<S>>,
-    api: &Api<S::Manifest>,
+    patcher: &mut StatusPatcher<S::Manifest>,
     shared: &SharedState<S::SharedState>,
     object_state: &mut S,
     manifest: &Manifest<S::Manifest>,
-) -> Option<Box<dyn State<S>>>
+    context: &RunContext<S>,
+    progress: &mut Progress,
+) -> Step<S>
 where
     S::Manifest: Resource + DeserializeOwned,
     S::Status: ObjectStatus,
 {
+    let state_name = state.name();
+    if let Some(ref tracker) = context.tracker {
+        tracker.enter(namespace, name, state_name);
+    }
+
     let latest_manifest = manifest.latest();
     let span = tracing::debug_span!("State::status");
     match state
@@ -140,40 +717,177 @@ where
         .instrument(span)
         .await
     {
-        Ok(status) => {
-            patch_status(api, name, status).await;
+        Ok(Some(status)) => {
+            context
+                .report_patch(patcher.queue(status.json_patch()).await, patcher)
+                .await;
         }
+        Ok(None) => trace!("State did not change status, skipping patch."),
         Err(error) => {
             warn!(?error, "Object status patch returned error.",);
+            context.report_error(error).await;
         }
     }
 
+    for middleware in &context.middleware {
+        middleware.on_enter(state_name, &latest_manifest).await;
+    }
+
+    let started = std::time::Instant::now();
     let transition = {
-        let span = tracing::trace_span!("State::next",);
-        state
-            .next(shared.clone(), object_state, manifest.clone())
-            .instrument(span)
-            .await
+        let next = next_with_timeout(state, shared, object_state, manifest);
+        tokio::pin!(next);
+        // Send held back status patches which fall due while the state runs.
+        loop {
+            let flush_at = patcher.due();
+            let due = tokio::time::sleep_until(flush_at.unwrap_or_else(tokio::time::Instant::now));
+            tokio::select! {
+                transition = &mut next => break transition,
+                _ = due, if flush_at.is_some() => {
+                    context.report_patch(patcher.flush().await, patcher).await;
+                }
+            }
+        }
     };
+    if let Some(ref metrics) = context.metrics {
+        let failed = matches!(
+            transition,
+            Transition::Retry(..) | Transition::Complete(Err(_))
+        );
+        metrics.record(state_name, started.elapsed(), failed);
+    }
+    if let Some(ref tracker) = context.tracker {
+        if let Transition::Retry(_, ref error) | Transition::Complete(Err(ref error)) = transition {
+            tracker.error(namespace, name, error);
+        }
+    }
 
-    match transition {
-        Transition::Next(s) => {
-            let next_state = s.into();
-            trace!(?next_state, "Object transitioning to new state",);
-            Some(next_state)
+    if !context.middleware.is_empty() {
+        let outcome = transition.outcome();
+        let latest_manifest = manifest.latest();
+        for middleware in &context.middleware {
+            middleware
+                .on_exit(state_name, &latest_manifest, &outcome)
+                .await;
         }
-        Transition::Complete(result) => match result {
-            Ok(()) => {
-                debug!("Object state machine exited without error.",);
-                None
-            }
-            Err(error) => {
-                error!(?error, "Object state machine exited with error.",);
-                let status = S::Status::failed(&format!("{:?}", error));
-                patch_status(api, name, status).await;
-                None
+    }
+
+    if let Some(retain) = context.history {
+        progress.history.push_back(serde_json::json!({
+            "state": short_name(state_name),
+            "timestamp": k8s_openapi::chrono::Utc::now().to_rfc3339(),
+            "outcome": transition.outcome().describe(),
+        }));
+        while progress.history.len() > retain {
+            progress.history.pop_front();
+        }
+        let patch = serde_json::json!({ "status": { "stateHistory": progress.history } });
+        context
+            .report_patch(patcher.queue(patch).await, patcher)
+            .await;
+    }
+
+    let attempts = progress.attempts.saturating_add(1);
+    let step = resolve_transition(transition, &mut progress.attempts);
+    match step {
+        Step::Enter(..) => (),
+        Step::Complete(Ok(())) => {
+            debug!("Object state machine exited without error.",);
+        }
+        Step::Complete(Err(ref error)) => {
+            error!(?error, "Object state machine exited with error.",);
+            let status = S::Status::failed_with(&StateError {
+                error,
+                state: state_name,
+                attempts,
+            });
+            context
+                .report_patch(patcher.queue(status.json_patch()).await, patcher)
+                .await;
+        }
+    }
+    step
+}
+
+/// Call `State::next`, applying the state's timeout.
+pub(crate) async fn next_with_timeout<S: ResourceState>(
+    state: Box<dyn State<S>>,
+    shared: &SharedState<S::SharedState>,
+    object_state: &mut S,
+    manifest: &Manifest<S::Manifest>,
+) -> Transition<S> {
+    let timeout = state.timeout().map(|timeout| (timeout, state.on_timeout()));
+    let span = tracing::trace_span!("State::next",);
+    let next = state
+        .next(shared.clone(), object_state, manifest.clone())
+        .instrument(span);
+    match timeout {
+        Some((timeout, on_timeout)) => match tokio::time::timeout(timeout, next).await {
+            Ok(transition) => transition,
+            Err(_) => {
+                warn!(?timeout, "Object state timed out.");
+                on_timeout()
             }
         },
+        None => next.await,
+    }
+}
+
+/// What a state machine does after a transition.
+pub(crate) enum Step<S: ResourceState> {
+    /// Enter the state, after the delay if any.
+    Enter(Box<dyn State<S>>, Option<Duration>),
+    /// Stop with the result.
+    Complete(anyhow::Result<()>),
+}
+
+/// Decide the next step for `transition`, tracking consecutive retries of the
+/// current state in `attempts`.
+pub(crate) fn resolve_transition<S: ResourceState>(
+    mut transition: Transition<S>,
+    attempts: &mut u32,
+) -> Step<S> {
+    loop {
+        return match transition {
+            Transition::Next(s) => {
+                *attempts = 0;
+                let next_state = s.into();
+                trace!(?next_state, "Object transitioning to new state",);
+                Step::Enter(next_state, None)
+            }
+            Transition::Requeue(s, after) => {
+                let state = s.into();
+                trace!(?state, ?after, "Object requeued in current state",);
+                Step::Enter(state, Some(after))
+            }
+            Transition::Retry(s, error) => {
+                let state: Box<dyn State<S>> = s.into();
+                *attempts = attempts.saturating_add(1);
+                if let Some(max_attempts) = state.max_attempts() {
+                    if *attempts >= max_attempts {
+                        warn!(
+                            ?state,
+                            ?error,
+                            attempts = *attempts,
+                            "Object state retries exhausted.",
+                        );
+                        *attempts = 0;
+                        transition = state.retries_exhausted(error);
+                        continue;
+                    }
+                }
+                let delay = state.retry_backoff().delay(*attempts);
+                warn!(
+                    ?state,
+                    ?error,
+                    attempts = *attempts,
+                    ?delay,
+                    "Object state will be retried.",
+                );
+                Step::Enter(state, Some(delay))
+            }
+            Transition::Complete(result) => Step::Complete(result),
+        };
     }
 }
 
@@ -184,7 +898,25 @@ pub async fn patch_status<R: Resource + Clone + DeserializeOwned, S: ObjectStatu
     name: &str,
     status: S,
 ) {
-    let patch = status.json_patch();
+    // Errors are logged by `try_patch_status`.
+    let _ = try_patch_status(api, name, status).await;
+}
+
+/// Patch object status with Kubernetes API, returning any error after
+/// logging it.
+async fn try_patch_status<R: Resource + Clone + DeserializeOwned, S: ObjectStatus>(
+    api: &Api<R>,
+    name: &str,
+    status: S,
+) -> kube::Result<()> {
+    try_patch_status_json(api, name, status.json_patch()).await
+}
+
+async fn try_patch_status_json<R: Resource + Clone + DeserializeOwned>(
+    api: &Api<R>,
+    name: &str,
+    patch: serde_json::Value,
+) -> kube::Result<()> {
     debug!(
         %name,
         %patch,
@@ -198,13 +930,14 @@ pub async fn patch_status<R: Resource + Clone + DeserializeOwned, S: ObjectStatu
         )
         .await
     {
-        Ok(_) => (),
+        Ok(_) => Ok(()),
         Err(error) => {
             warn!(
                 %name,
                 ?error,
                 "Object error patching status."
             );
+            Err(error)
         }
     }
 }
@@ -238,8 +971,362 @@ pub mod test {
             &self,
             _state: &mut ResourceState,
             _pod: &Resource,
-        ) -> anyhow::Result<Status> {
-            Ok(Default::default())
+        ) -> anyhow::Result<Option<Status>> {
+            Ok(Some(Default::default()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quarantine::QuarantineHandle;
+    use crate::{ObjectState, Store};
+    use k8s_openapi::api::core::v1::ConfigMap;
+    use kube::api::ObjectMeta;
+
+    /// Succeeds or fails each time it grazes, in order.
+    #[derive(Default)]
+    struct Moose {
+        outcomes: VecDeque<bool>,
+    }
+
+    #[async_trait::async_trait]
+    impl ObjectState for Moose {
+        type Manifest = ConfigMap;
+        type Status = MooseStatus;
+        type SharedState = ();
+        async fn async_drop(self, _shared: &mut ()) {}
+    }
+
+    struct MooseStatus;
+
+    impl ObjectStatus for MooseStatus {
+        fn json_patch(&self) -> serde_json::Value {
+            serde_json::json!({})
+        }
+
+        fn failed(_error: &str) -> Self {
+            MooseStatus
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct Graze;
+
+    #[async_trait::async_trait]
+    impl State<Moose> for Graze {
+        async fn next(
+            self: Box<Self>,
+            _shared: SharedState<()>,
+            moose: &mut Moose,
+            _manifest: Manifest<ConfigMap>,
+        ) -> Transition<Moose> {
+            match moose.outcomes.pop_front() {
+                Some(true) => Transition::Complete(Ok(())),
+                _ => Transition::Complete(Err(anyhow::anyhow!("failed"))),
+            }
+        }
+
+        async fn status(
+            &self,
+            _moose: &mut Moose,
+            _manifest: &ConfigMap,
+        ) -> anyhow::Result<Option<MooseStatus>> {
+            Ok(None)
+        }
+    }
+
+    /// Chews for a second too long, unless `done`.
+    #[derive(Debug)]
+    struct Ruminate {
+        done: bool,
+        timed_out: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    #[async_trait::async_trait]
+    impl State<Moose> for Ruminate {
+        async fn next(
+            self: Box<Self>,
+            _shared: SharedState<()>,
+            _moose: &mut Moose,
+            _manifest: Manifest<ConfigMap>,
+        ) -> Transition<Moose> {
+            if !self.done {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+            }
+            Transition::Complete(Ok(()))
+        }
+
+        async fn status(
+            &self,
+            _moose: &mut Moose,
+            _manifest: &ConfigMap,
+        ) -> anyhow::Result<Option<MooseStatus>> {
+            Ok(None)
+        }
+
+        fn timeout(&self) -> Option<Duration> {
+            Some(Duration::from_secs(1))
+        }
+
+        fn on_timeout(&self) -> OnTimeout<Moose> {
+            let timed_out = Arc::clone(&self.timed_out);
+            Box::new(move || {
+                timed_out.store(true, std::sync::atomic::Ordering::SeqCst);
+                Transition::Complete(Err(anyhow::anyhow!("timed out")))
+            })
+        }
+    }
+
+    /// Retries failed states after a second.
+    struct RequeuePolicy;
+
+    impl ErrorPolicy<Moose> for RequeuePolicy {
+        fn on_error(
+            &self,
+            _source: ErrorSource,
+            _error: &anyhow::Error,
+            _failures: u32,
+        ) -> ErrorAction<Moose> {
+            ErrorAction::RequeueAfter(Duration::from_secs(1))
+        }
+    }
+
+    /// Answers every request with an error. The tests leave the status
+    /// alone, so none should be sent.
+    struct NoApiServer;
+
+    impl tower::Service<http::Request<hyper::Body>> for NoApiServer {
+        type Response = http::Response<hyper::Body>;
+        type Error = std::convert::Infallible;
+        type Future = futures::future::Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(
+            &mut self,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _request: http::Request<hyper::Body>) -> Self::Future {
+            let mut response = http::Response::new(hyper::Body::empty());
+            *response.status_mut() = http::StatusCode::INTERNAL_SERVER_ERROR;
+            futures::future::ready(Ok(response))
+        }
+    }
+
+    /// Run the state machine of a moose with `outcomes` through the error
+    /// policy in the background, returning whether it succeeded.
+    fn graze(
+        context: &RunContext<Moose>,
+        manifest: &Manifest<ConfigMap>,
+        outcomes: &[bool],
+    ) -> tokio::task::JoinHandle<bool> {
+        let context = RunContext {
+            error_policy: Some(Arc::new(RequeuePolicy)),
+            status: StatusOptions {
+                skip_unchanged: true,
+                ..Default::default()
+            },
+            ..context.clone()
+        };
+        let manifest = manifest.clone();
+        let mut moose = Moose {
+            outcomes: outcomes.iter().copied().collect(),
+        };
+        tokio::spawn(async move {
+            let client = kube::Client::new(NoApiServer, "default");
+            run_with_policy::<_, Graze>(
+                &client,
+                &(),
+                Box::new(Graze),
+                Arc::new(tokio::sync::RwLock::new(())),
+                &mut moose,
+                manifest,
+                &context,
+            )
+            .await
+            .is_ok()
+        })
+    }
+
+    fn config_map(generation: i64, data: &str) -> ConfigMap {
+        ConfigMap {
+            metadata: ObjectMeta {
+                name: Some("moose".to_string()),
+                namespace: Some("herd".to_string()),
+                generation: Some(generation),
+                ..Default::default()
+            },
+            data: Some(std::iter::once(("data".to_string(), data.to_string())).collect()),
+            ..Default::default()
+        }
+    }
+
+    fn context(handle: &QuarantineHandle) -> RunContext<Moose> {
+        RunContext {
+            quarantine: Some(Quarantine {
+                handle: handle.clone(),
+                threshold: 3,
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Wait for the object's retry after `failures` consecutive failures at
+    /// generation 1 in the background.
+    fn wait_retry(
+        context: &RunContext<Moose>,
+        manifest: &Manifest<ConfigMap>,
+        failures: u32,
+    ) -> tokio::task::JoinHandle<bool> {
+        let context = context.clone();
+        let manifest = manifest.clone();
+        tokio::spawn(async move {
+            context
+                .wait_retry(
+                    &manifest,
+                    Some(1),
+                    Duration::from_secs(1),
+                    failures,
+                    "failed",
+                )
+                .await
+        })
+    }
+
+    /// Let the background task run until it waits, without moving the clock
+    /// past its delay.
+    async fn settle() {
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_below_threshold() {
+        tokio::time::pause();
+        let handle = QuarantineHandle::new();
+        let context = context(&handle);
+        let (_tx, manifest) = Manifest::new(config_map(1, "a"), Store::new());
+
+        let retry = wait_retry(&context, &manifest, 2);
+        settle().await;
+        assert!(!handle.contains(Some("herd"), "moose"));
+        assert!(retry.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn quarantines_at_threshold_until_requeued() {
+        tokio::time::pause();
+        let handle = QuarantineHandle::new();
+        let context = context(&handle);
+        let (_tx, manifest) = Manifest::new(config_map(1, "a"), Store::new());
+
+        let retry = wait_retry(&context, &manifest, 3);
+        settle().await;
+        let quarantined = handle.list();
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(quarantined[0].name, "moose");
+        assert_eq!(quarantined[0].failures, 3);
+        assert_eq!(quarantined[0].error, "failed");
+
+        // Not retried after the delay.
+        tokio::time::sleep(Duration::from_secs(3600)).await;
+        assert!(handle.contains(Some("herd"), "moose"));
+
+        assert!(handle.requeue(Some("herd"), "moose"));
+        assert!(retry.await.unwrap());
+        assert!(handle.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn leaves_quarantine_when_spec_changes() {
+        tokio::time::pause();
+        let handle = QuarantineHandle::new();
+        let context = context(&handle);
+        let (tx, manifest) = Manifest::new(config_map(1, "a"), Store::new());
+
+        let retry = wait_retry(&context, &manifest, 3);
+        settle().await;
+        assert!(handle.contains(Some("herd"), "moose"));
+
+        // Changes which leave the generation alone, such as to the status,
+        // do not release the object.
+        tx.send(config_map(1, "b")).unwrap();
+        settle().await;
+        assert!(handle.contains(Some("herd"), "moose"));
+
+        tx.send(config_map(2, "b")).unwrap();
+        assert!(retry.await.unwrap());
+        assert!(!handle.contains(Some("herd"), "moose"));
+    }
+
+    #[tokio::test]
+    async fn skips_quarantine_when_spec_changed_since_failure() {
+        tokio::time::pause();
+        let handle = QuarantineHandle::new();
+        let context = context(&handle);
+        let (tx, manifest) = Manifest::new(config_map(1, "a"), Store::new());
+
+        // The spec changes while the failing attempt runs.
+        tx.send(config_map(2, "b")).unwrap();
+        let retry = wait_retry(&context, &manifest, 3);
+        settle().await;
+        assert!(!handle.contains(Some("herd"), "moose"));
+        assert!(retry.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn counts_failures_from_zero_after_success() {
+        tokio::time::pause();
+        let handle = QuarantineHandle::new();
+        let context = context(&handle);
+        let (_tx, manifest) = Manifest::new(config_map(1, "a"), Store::new());
+
+        // Two failures stay below the threshold of three.
+        let grazing = graze(&context, &manifest, &[false, false, true]);
+        assert!(grazing.await.unwrap());
+        assert!(handle.list().is_empty());
+
+        // Counting on from the earlier failures would quarantine the object
+        // at the first failure here, and leave it there.
+        let grazing = graze(&context, &manifest, &[false, true]);
+        let grazed = tokio::time::timeout(Duration::from_secs(3600), grazing)
+            .await
+            .expect("Object was quarantined.");
+        assert!(grazed.unwrap());
+        assert!(handle.list().is_empty());
+
+        // Three failures in a row still quarantine it.
+        let grazing = graze(&context, &manifest, &[false, false, false, true]);
+        for _ in 0..10 {
+            if handle.contains(Some("herd"), "moose") {
+                break;
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        assert_eq!(handle.list()[0].failures, 3);
+        assert!(handle.requeue(Some("herd"), "moose"));
+        assert!(grazing.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn takes_timeout_transition_only_once_deadline_passes() {
+        tokio::time::pause();
+        let (_tx, manifest) = Manifest::new(config_map(1, "a"), Store::new());
+        let shared = Arc::new(tokio::sync::RwLock::new(()));
+        let mut moose = Moose::default();
+        for done in [true, false] {
+            let timed_out = Arc::new(std::sync::atomic::AtomicBool::new(false));
+            let state: Box<dyn State<Moose>> = Box::new(Ruminate {
+                done,
+                timed_out: Arc::clone(&timed_out),
+            });
+            let transition = next_with_timeout(state, &shared, &mut moose, &manifest).await;
+            assert_eq!(matches!(transition, Transition::Complete(Ok(()))), done);
+            assert_eq!(timed_out.load(std::sync::atomic::Ordering::SeqCst), !done);
         }
     }
 }
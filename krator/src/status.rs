@@ -0,0 +1,330 @@
+//! Sending status updates to the Kubernetes API.
+
+use anyhow::Context;
+use kube::api::{Patch, PatchParams, Resource, ResourceExt};
+use kube::Api;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{debug, trace, warn};
+
+use crate::manifest::LastStatus;
+use crate::operator::PatchStrategy;
+use crate::write::retry_conflicts;
+
+/// How status updates are sent, configured on the runtime.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StatusOptions {
+    /// Hold patches for this long, merging those issued in between.
+    pub(crate) debounce: Option<Duration>,
+    /// Send at most one update per object in this interval, merging those
+    /// issued in between.
+    pub(crate) min_interval: Option<Duration>,
+    /// Update status with server-side apply as this field manager, instead
+    /// of merge patches.
+    pub(crate) field_manager: Option<String>,
+    /// Patch the object itself rather than its status subresource.
+    pub(crate) inline: bool,
+    /// Encoding of patches, unless they are applied.
+    pub(crate) strategy: PatchStrategy,
+    /// Leave out updates which would not change the status last sent.
+    pub(crate) skip_unchanged: bool,
+}
+
+/// Sends the status updates of a single object.
+///
+/// Patches are merged while debounced or rate limited. Conflicts are retried
+/// against the latest `resourceVersion` of the object, and an error is only
+/// returned once the update has failed for good.
+pub(crate) struct StatusPatcher<R: Resource> {
+    api: Api<R>,
+    name: String,
+    options: StatusOptions,
+    apply: Option<StatusApply>,
+    /// Patches not yet sent, merged into one.
+    pending: Option<serde_json::Value>,
+    /// When the pending patch is due.
+    flush_at: Option<Instant>,
+    /// When the last update was sent.
+    last_sent: Option<Instant>,
+    /// Every patch sent successfully so far, merged.
+    last_applied: serde_json::Value,
+    /// Shared with the object's manifest, to expose the status of
+    /// `last_applied` to states.
+    last_status: LastStatus,
+    /// The patch which failed most recently, if it has not been sent since.
+    failed: Option<serde_json::Value>,
+    /// Consecutive failed updates.
+    failures: u32,
+}
+
+impl<R: Resource> StatusPatcher<R> {
+    /// Consecutive failed updates.
+    pub(crate) fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Queue the patch which failed most recently again, to be sent after
+    /// `delay` unless another patch falls due first. Patches queued since
+    /// take precedence.
+    pub(crate) fn retry_after(&mut self, delay: Duration) {
+        let mut patch = match self.failed.take() {
+            Some(patch) => patch,
+            None => return,
+        };
+        if let Some(pending) = self.pending.take() {
+            merge_patches(&mut patch, pending);
+        }
+        self.pending = Some(patch);
+        let retry_at = Instant::now() + delay;
+        self.flush_at = Some(match self.flush_at {
+            Some(flush_at) => std::cmp::min(flush_at, retry_at),
+            None => retry_at,
+        });
+    }
+}
+
+/// Server-side apply settings for status updates.
+struct StatusApply {
+    field_manager: String,
+    api_version: String,
+    kind: String,
+    /// Every patch applied so far, merged. Server-side apply removes fields
+    /// which the field manager owns but leaves out, so each request carries
+    /// all of them.
+    applied: serde_json::Value,
+}
+
+impl<R: Resource + Clone + DeserializeOwned> StatusPatcher<R> {
+    pub(crate) fn new(
+        api: Api<R>,
+        name: String,
+        dyntype: &R::DynamicType,
+        options: &StatusOptions,
+        last_status: LastStatus,
+    ) -> Self {
+        StatusPatcher {
+            api,
+            name,
+            options: options.clone(),
+            apply: options
+                .field_manager
+                .as_ref()
+                .map(|field_manager| StatusApply {
+                    field_manager: field_manager.clone(),
+                    api_version: R::api_version(dyntype).to_string(),
+                    kind: R::kind(dyntype).to_string(),
+                    applied: serde_json::json!({}),
+                }),
+            pending: None,
+            flush_at: None,
+            last_sent: None,
+            last_applied: serde_json::json!({}),
+            last_status,
+            failed: None,
+            failures: 0,
+        }
+    }
+
+    /// When the pending patch should be flushed, if there is one.
+    pub(crate) fn due(&self) -> Option<Instant> {
+        self.flush_at
+    }
+
+    /// Send a patch, or merge it into the pending patch when debouncing or
+    /// rate limited.
+    pub(crate) async fn queue(&mut self, patch: serde_json::Value) -> anyhow::Result<()> {
+        match self.pending {
+            Some(ref mut pending) => merge_patches(pending, patch),
+            None => self.pending = Some(patch),
+        }
+        if self.flush_at.is_none() {
+            let now = Instant::now();
+            let ready = std::cmp::max(
+                now + self.options.debounce.unwrap_or_default(),
+                self.ready_at().unwrap_or(now),
+            );
+            if ready <= now {
+                return self.finish().await;
+            }
+            self.flush_at = Some(ready);
+        }
+        Ok(())
+    }
+
+    /// Send the pending patch, if any, as soon as the rate limit allows.
+    pub(crate) async fn flush(&mut self) -> anyhow::Result<()> {
+        if self.pending.is_none() {
+            self.flush_at = None;
+            return Ok(());
+        }
+        match self.ready_at() {
+            Some(ready) if ready > Instant::now() => {
+                self.flush_at = Some(ready);
+                Ok(())
+            }
+            _ => self.finish().await,
+        }
+    }
+
+    /// Send the pending patch, if any, regardless of the rate limit.
+    pub(crate) async fn finish(&mut self) -> anyhow::Result<()> {
+        self.flush_at = None;
+        let patch = match self.pending.take() {
+            Some(patch) => patch,
+            None => return Ok(()),
+        };
+        let result = self.send(patch.clone()).await;
+        match result {
+            Ok(()) => {
+                self.failed = None;
+                self.failures = 0;
+            }
+            Err(_) => {
+                self.failed = Some(patch);
+                self.failures = self.failures.saturating_add(1);
+            }
+        }
+        result
+    }
+
+    /// Earliest time the rate limit allows another update.
+    fn ready_at(&self) -> Option<Instant> {
+        Some(self.last_sent? + self.options.min_interval?)
+    }
+
+    async fn send(&mut self, patch: serde_json::Value) -> anyhow::Result<()> {
+        let mut applied = self.last_applied.clone();
+        merge_patches(&mut applied, patch.clone());
+        if self.options.skip_unchanged && applied == self.last_applied {
+            trace!(name = %self.name, "Status unchanged, skipping patch.");
+            return Ok(());
+        }
+        let (params, body) = match self.apply {
+            Some(ref mut apply) => {
+                merge_patches(&mut apply.applied, patch);
+                let mut body = apply.applied.clone();
+                strip_nulls(&mut body);
+                if let serde_json::Value::Object(ref mut body) = body {
+                    body.insert("apiVersion".to_string(), apply.api_version.clone().into());
+                    body.insert("kind".to_string(), apply.kind.clone().into());
+                }
+                (PatchParams::apply(&apply.field_manager).force(), body)
+            }
+            None => (PatchParams::default(), patch),
+        };
+        let inline = self.options.inline;
+        let strategy = self.options.strategy;
+        let applying = self.apply.is_some();
+        let mut body = body;
+        let result = retry_conflicts(&self.api, &self.name, |latest: Option<R>| {
+            if let (Some(latest), serde_json::Value::Object(body)) = (latest, &mut body) {
+                let metadata = body
+                    .entry("metadata")
+                    .or_insert_with(|| serde_json::json!({}));
+                metadata["resourceVersion"] = latest.resource_version().into();
+            }
+            debug!(
+                name = %self.name,
+                patch = %body,
+                inline,
+                field_manager = ?self.options.field_manager,
+                ?strategy,
+                "Applying status patch to object."
+            );
+            let api = self.api.clone();
+            let name = self.name.clone();
+            let params = params.clone();
+            let body = body.clone();
+            async move {
+                let patch = match (applying, strategy) {
+                    (true, _) => Patch::Apply(body),
+                    (false, PatchStrategy::Merge) => Patch::Merge(body),
+                    (false, PatchStrategy::Strategic) => Patch::Strategic(body),
+                    (false, PatchStrategy::Json) => Patch::Json(to_json_patch(&body)?),
+                };
+                if inline {
+                    api.patch(&name, &params, &patch).await?;
+                } else {
+                    api.patch_status(&name, &params, &patch).await?;
+                }
+                Ok(())
+            }
+        })
+        .await;
+        self.last_sent = Some(Instant::now());
+        match result {
+            Ok(()) => {
+                let mut status = applied.get("status").cloned();
+                if let Some(ref mut status) = status {
+                    strip_nulls(status);
+                }
+                *self.last_status.lock().expect("Last status lock poisoned.") = status;
+                self.last_applied = applied;
+                Ok(())
+            }
+            Err(error) => {
+                warn!(name = %self.name, ?error, "Object error patching status.");
+                Err(error).with_context(|| format!("Failed to patch status of {}", self.name))
+            }
+        }
+    }
+}
+
+/// Translate a JSON merge patch into JSON Patch operations, descending into
+/// objects, removing `null` fields and adding everything else.
+fn to_json_patch(patch: &serde_json::Value) -> anyhow::Result<json_patch::Patch> {
+    fn collect(path: &str, value: &serde_json::Value, operations: &mut Vec<serde_json::Value>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map {
+                    let path = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                    collect(&path, value, operations);
+                }
+            }
+            serde_json::Value::Null => {
+                operations.push(serde_json::json!({ "op": "remove", "path": path }))
+            }
+            value => operations.push(serde_json::json!({
+                "op": "add",
+                "path": path,
+                "value": value,
+            })),
+        }
+    }
+    let mut operations = vec![];
+    collect("", patch, &mut operations);
+    serde_json::from_value(serde_json::Value::Array(operations))
+        .context("Failed to translate status into JSON Patch")
+}
+
+/// Remove `null` values, which in a merge patch delete a field and in an
+/// applied configuration are left out instead.
+fn strip_nulls(value: &mut serde_json::Value) {
+    if let serde_json::Value::Object(map) = value {
+        map.retain(|_, value| !value.is_null());
+        for value in map.values_mut() {
+            strip_nulls(value);
+        }
+    }
+}
+
+/// Combine two JSON merge patches into one with the same effect as applying
+/// `base` and then `patch`.
+fn merge_patches(base: &mut serde_json::Value, patch: serde_json::Value) {
+    match (base, patch) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(patch)) => {
+            for (key, value) in patch {
+                match base.get_mut(&key) {
+                    Some(existing) if existing.is_object() && value.is_object() => {
+                        merge_patches(existing, value)
+                    }
+                    _ => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
@@ -1,22 +1,302 @@
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 
-#[cfg(not(feature = "admission-webhook"))]
-use kube::api::DynamicObject;
+use anyhow::Context;
+use kube::api::{Api, ApiResource, DynamicObject};
 
 use kube::api::GroupVersionKind;
+use kube_runtime::watcher::Event;
 use serde::de::DeserializeOwned;
-use tokio::sync::RwLock;
+use serde::Serialize;
+use tokio::sync::{broadcast, watch, RwLock};
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+use tracing::{debug, warn};
 
 use crate::object::ObjectKey;
+use crate::util::DynamicEvent;
 
-type ResourceMap = HashMap<GroupVersionKind, HashMap<ObjectKey, serde_json::Value>>;
+mod backend;
+
+pub use backend::{MemoryBackend, StoreBackend};
+
+/// Computes the values under which an object is indexed.
+pub(crate) type IndexFn = Arc<dyn Fn(&DynamicObject) -> Vec<String> + Send + Sync>;
+
+type IndexMap = HashMap<GroupVersionKind, HashMap<String, Index>>;
+
+/// Order in which the cached objects of a bounded kind were last accessed.
+#[derive(Default)]
+struct Recency {
+    tick: u64,
+    ticks: HashMap<ObjectKey, u64>,
+    order: BTreeMap<u64, ObjectKey>,
+}
+
+impl Recency {
+    fn touch(&mut self, object_key: &ObjectKey) {
+        self.tick += 1;
+        if let Some(tick) = self.ticks.insert(object_key.clone(), self.tick) {
+            self.order.remove(&tick);
+        }
+        self.order.insert(self.tick, object_key.clone());
+    }
+
+    fn remove(&mut self, object_key: &ObjectKey) {
+        if let Some(tick) = self.ticks.remove(object_key) {
+            self.order.remove(&tick);
+        }
+    }
+
+    fn pop_oldest(&mut self) -> Option<ObjectKey> {
+        let tick = *self.order.keys().next()?;
+        let object_key = self.order.remove(&tick)?;
+        self.ticks.remove(&object_key);
+        Some(object_key)
+    }
+}
+
+/// Limits on the number of cached objects per kind.
+#[derive(Default)]
+struct Bounds {
+    limits: HashMap<GroupVersionKind, usize>,
+    recency: HashMap<GroupVersionKind, Recency>,
+    /// Fetches objects missing from the cache of bounded kinds.
+    client: Option<kube::Client>,
+}
+
+impl Bounds {
+    /// Record an access to a cached object, returning the objects to evict
+    /// for `cached` objects of the kind to fit its limit.
+    fn touch(
+        &mut self,
+        gvk: &GroupVersionKind,
+        object_key: &ObjectKey,
+        cached: usize,
+    ) -> Vec<ObjectKey> {
+        let limit = match self.limits.get(gvk) {
+            Some(limit) => *limit,
+            None => return vec![],
+        };
+        let recency = self.recency.entry(gvk.clone()).or_default();
+        recency.touch(object_key);
+        let mut evicted = vec![];
+        while cached - evicted.len() > limit {
+            match recency.pop_oldest() {
+                Some(oldest) => evicted.push(oldest),
+                None => break,
+            }
+        }
+        evicted
+    }
+
+    fn remove(&mut self, gvk: &GroupVersionKind, object_key: &ObjectKey) {
+        if let Some(recency) = self.recency.get_mut(gvk) {
+            recency.remove(object_key);
+        }
+    }
+}
+
+/// Fetch an object which is not cached from the API server.
+async fn fetch<R: k8s_openapi::Resource + DeserializeOwned>(
+    client: kube::Client,
+    namespace: Option<&str>,
+    name: &str,
+) -> anyhow::Result<Option<R>> {
+    let gvk = GroupVersionKind::gvk(R::GROUP, R::VERSION, R::KIND);
+    let resource = ApiResource::from_gvk_with_plural(&gvk, R::URL_PATH_SEGMENT);
+    let api: Api<DynamicObject> = match namespace {
+        Some(namespace) => Api::namespaced_with(client, namespace, &resource),
+        None => Api::all_with(client, &resource),
+    };
+    match api.get(name).await {
+        Ok(object) => Ok(Some(crate::util::concrete_object::<R>(object)?)),
+        Err(kube::Error::Api(e)) if e.code == 404 => Ok(None),
+        Err(e) => Err(e).with_context(|| {
+            format!(
+                "Could not fetch uncached object {}/{} {} {}",
+                R::GROUP,
+                R::VERSION,
+                R::KIND,
+                name
+            )
+        }),
+    }
+}
+
+/// Drop evicted objects from the cache of their kind and its indexes,
+/// without notifying subscribers, since they still exist.
+async fn evict(
+    backend: &dyn StoreBackend,
+    gvk: &GroupVersionKind,
+    indexes: Option<&mut HashMap<String, Index>>,
+    evicted: Vec<ObjectKey>,
+) -> anyhow::Result<()> {
+    if evicted.is_empty() {
+        return Ok(());
+    }
+    debug!(?gvk, evicted = evicted.len(), "Evicting cached objects.");
+    let mut indexes = indexes;
+    for object_key in evicted {
+        if let Some(ref mut indexes) = indexes {
+            for index in indexes.values_mut() {
+                index.remove(&object_key);
+            }
+        }
+        backend.remove(gvk, &object_key).await?;
+    }
+    Ok(())
+}
+
+/// Changes not yet received by the slowest subscriber before it misses some.
+const CHANGES_CAPACITY: usize = 1024;
+
+/// A change to the cached objects of one kind, as delivered by
+/// [Store::subscribe].
+#[derive(Clone, Debug)]
+pub enum StoreEvent<R> {
+    /// An object was cached for the first time.
+    Added(R),
+    /// A cached object changed. Contains the new object.
+    Updated(R),
+    /// An object was removed from the cache. Contains the last cached object.
+    Deleted(R),
+}
+
+impl<R> StoreEvent<R> {
+    /// The object which changed.
+    pub fn object(&self) -> &R {
+        match self {
+            StoreEvent::Added(object)
+            | StoreEvent::Updated(object)
+            | StoreEvent::Deleted(object) => object,
+        }
+    }
+
+    fn map<T>(self, f: impl FnOnce(R) -> T) -> StoreEvent<T> {
+        match self {
+            StoreEvent::Added(object) => StoreEvent::Added(f(object)),
+            StoreEvent::Updated(object) => StoreEvent::Updated(f(object)),
+            StoreEvent::Deleted(object) => StoreEvent::Deleted(f(object)),
+        }
+    }
+
+    fn try_map<T, E>(self, f: impl FnOnce(R) -> Result<T, E>) -> Result<StoreEvent<T>, E> {
+        Ok(match self {
+            StoreEvent::Added(object) => StoreEvent::Added(f(object)?),
+            StoreEvent::Updated(object) => StoreEvent::Updated(f(object)?),
+            StoreEvent::Deleted(object) => StoreEvent::Deleted(f(object)?),
+        })
+    }
+}
+
+type Change = (GroupVersionKind, StoreEvent<Arc<serde_json::Value>>);
+
+/// The contents of a [Store] at one point in time, as returned by
+/// [Store::snapshot].
+#[derive(Clone, Debug, Serialize)]
+pub struct StoreSnapshot {
+    /// Every kind with cached objects or which has been listed, ordered by
+    /// group, version and kind.
+    pub kinds: Vec<KindSnapshot>,
+}
+
+/// The cached objects of one kind in a [StoreSnapshot].
+#[derive(Clone, Debug, Serialize)]
+pub struct KindSnapshot {
+    /// API group of the kind.
+    pub group: String,
+    /// API version of the kind.
+    pub version: String,
+    /// Name of the kind.
+    pub kind: String,
+    /// Whether the objects of the kind have been listed, so that the cache
+    /// reflects the cluster as of some point in time.
+    pub synced: bool,
+    /// Number of cached objects.
+    pub count: usize,
+    /// The cached objects, ordered by namespace and name, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub objects: Option<Vec<serde_json::Value>>,
+}
+
+/// Type erase an index function over objects of type `R`.
+pub(crate) fn index_fn<R, F>(f: F) -> IndexFn
+where
+    R: DeserializeOwned,
+    F: Fn(&R) -> Vec<String> + Send + Sync + 'static,
+{
+    Arc::new(move |dynamic_object| {
+        match crate::util::concrete_object::<R>(dynamic_object.clone()) {
+            Ok(object) => f(&object),
+            Err(error) => {
+                warn!(?error, "Unable to deserialize object to index.");
+                vec![]
+            }
+        }
+    })
+}
+
+/// Keys of the cached objects of one kind by the values `f` returns for them.
+struct Index {
+    f: IndexFn,
+    keys: HashMap<String, HashSet<ObjectKey>>,
+    /// Values each object is indexed under, to remove it without calling `f`.
+    values: HashMap<ObjectKey, Vec<String>>,
+}
+
+impl Index {
+    fn new(f: IndexFn) -> Self {
+        Index {
+            f,
+            keys: HashMap::new(),
+            values: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, object_key: &ObjectKey, dynamic_object: &DynamicObject) {
+        self.remove(object_key);
+        let values = (self.f)(dynamic_object);
+        for value in &values {
+            self.keys
+                .entry(value.clone())
+                .or_insert_with(HashSet::new)
+                .insert(object_key.clone());
+        }
+        self.values.insert(object_key.clone(), values);
+    }
+
+    fn remove(&mut self, object_key: &ObjectKey) {
+        for value in self.values.remove(object_key).unwrap_or_default() {
+            if let Some(keys) = self.keys.get_mut(&value) {
+                keys.remove(object_key);
+                if keys.is_empty() {
+                    self.keys.remove(&value);
+                }
+            }
+        }
+    }
+}
 
 /// Defines Store type for caching Kubernetes objects locally.
 ///
 /// * State is held in `Arc` so it is cheap to clone.
 /// * Collections are scoped by {group, version, kind, namespace, name}.
-/// * Objects are stored as [DynamicObject](kube::api::DynamicObject)s.
+/// * Objects are stored as [DynamicObject](kube::api::DynamicObject)s and
+///   read back as their concrete type.
+/// * Objects are kept in memory, unless another [StoreBackend] is given with
+///   [with_backend](Store::with_backend).
+///
+/// Objects of the kinds a controller
+/// [watches](crate::ControllerBuilder::watches) or
+/// [owns](crate::ControllerBuilder::owns) are cached by the
+/// [Manager](crate::Manager), which waits until each of them has been listed
+/// before dispatching the controller's first object, so states can rely on
+/// the cache being populated.
+///
+/// Objects of the kind a controller manages are cached as well, both by the
+/// Manager and by an [OperatorRuntime](crate::OperatorRuntime), which also
+/// caches the kinds given to
+/// [OperatorRuntime::watches](crate::OperatorRuntime::watches).
 ///
 /// ```
 /// # use krator::Store;
@@ -33,7 +313,24 @@ type ResourceMap = HashMap<GroupVersionKind, HashMap<ObjectKey, serde_json::Valu
 /// ```
 #[derive(Clone)]
 pub struct Store {
-    objects: Arc<RwLock<ResourceMap>>,
+    backend: Arc<dyn StoreBackend>,
+    /// Indexes by kind and index name. Locked for writing while objects are
+    /// written, so that writes are serialized.
+    indexes: Arc<RwLock<IndexMap>>,
+    /// Limits on cached objects and their accesses. Always locked after
+    /// `indexes`, and never across an await.
+    bounds: Arc<Mutex<Bounds>>,
+    /// Changes to cached objects, for subscribers.
+    changes: Arc<broadcast::Sender<Change>>,
+    /// Kinds which have been listed at least once.
+    synced: Arc<RwLock<HashSet<GroupVersionKind>>>,
+    /// Notified whenever a kind is listed.
+    synced_tx: Arc<watch::Sender<()>>,
+    synced_rx: watch::Receiver<()>,
+    /// Cluster whose objects are cached, when part of a
+    /// [MultiClusterRuntime](crate::MultiClusterRuntime). Part of the key of
+    /// every object, so that clusters can share a [StoreBackend].
+    cluster: Option<String>,
 }
 
 impl Default for Store {
@@ -45,37 +342,264 @@ impl Default for Store {
 impl Store {
     /// Initialize empty store.
     pub fn new() -> Self {
+        let (synced_tx, synced_rx) = watch::channel(());
+        let (changes, _) = broadcast::channel(CHANGES_CAPACITY);
         Store {
-            objects: Arc::new(RwLock::new(HashMap::new())),
+            backend: Arc::new(MemoryBackend::new()),
+            indexes: Default::default(),
+            bounds: Default::default(),
+            changes: Arc::new(changes),
+            synced: Default::default(),
+            synced_tx: Arc::new(synced_tx),
+            synced_rx,
+            cluster: None,
         }
     }
 
-    /// Clear cache for specified object kind.
-    #[cfg(not(feature = "admission-webhook"))]
-    pub(crate) async fn reset(&self, gvk: &GroupVersionKind) {
-        let mut objects = self.objects.write().await;
-        let key = gvk.clone();
-        let resource_objects = (*objects).entry(key).or_insert_with(HashMap::new);
-        resource_objects.clear();
+    /// Cache the objects of the cluster named `cluster`.
+    pub(crate) fn for_cluster(mut self, cluster: &str) -> Self {
+        self.cluster = Some(cluster.to_string());
+        self
+    }
+
+    /// Key of the object `name` in `namespace` of the store's cluster.
+    fn key(&self, namespace: Option<String>, name: String) -> ObjectKey {
+        ObjectKey::new(namespace, name).with_cluster(self.cluster.clone())
+    }
+
+    /// Whether `key` belongs to an object of the store's cluster, in
+    /// `namespace` if given.
+    fn holds(&self, key: &ObjectKey, namespace: Option<&str>) -> bool {
+        key.cluster() == self.cluster.as_deref()
+            && (namespace.is_none() || key.namespace().map(String::as_str) == namespace)
+    }
+
+    /// Keep the cached objects in `backend` instead of in memory. Clones of
+    /// the store made before keep the previous backend.
+    pub fn with_backend(mut self, backend: impl StoreBackend) -> Self {
+        self.backend = Arc::new(backend);
+        self
+    }
+
+    /// Cache at most `max_objects` objects of type `R`, evicting those which
+    /// were least recently inserted or read with [get](Self::get).
+    ///
+    /// With a [client](Self::with_client), `get` fetches objects of type `R`
+    /// missing from the cache from the API server instead, without caching
+    /// them. Other methods, such as [list](Self::list),
+    /// [by_index](Self::by_index) and [subscribe](Self::subscribe), only
+    /// reflect the objects which are cached, and subscribers may see evicted
+    /// objects added again when their kind is listed again.
+    pub fn with_limit<R: k8s_openapi::Resource>(self, max_objects: usize) -> Self {
+        let gvk = GroupVersionKind::gvk(R::GROUP, R::VERSION, R::KIND);
+        self.lock_bounds().limits.insert(gvk, max_objects);
+        self
+    }
+
+    /// Fetch objects of bounded kinds which are missing from the cache with
+    /// `client`. See [with_limit](Self::with_limit).
+    pub fn with_client(self, client: kube::Client) -> Self {
+        self.lock_bounds().client = Some(client);
+        self
+    }
+
+    fn lock_bounds(&self) -> std::sync::MutexGuard<'_, Bounds> {
+        self.bounds.lock().expect("Store bounds lock poisoned.")
+    }
+
+    /// Record a write to an object of kind `gvk`, returning the objects to
+    /// evict.
+    async fn touch(
+        &self,
+        gvk: &GroupVersionKind,
+        object_key: &ObjectKey,
+    ) -> anyhow::Result<Vec<ObjectKey>> {
+        if !self.lock_bounds().limits.contains_key(gvk) {
+            return Ok(vec![]);
+        }
+        let cached = self.backend.count(gvk).await?;
+        Ok(self.lock_bounds().touch(gvk, object_key, cached))
+    }
+
+    /// Replace the cache for specified object kind, or only its objects in
+    /// `namespace`, with freshly listed objects, notifying subscribers of the
+    /// differences only.
+    pub(crate) async fn replace_gvk(
+        &self,
+        gvk: &GroupVersionKind,
+        namespace: Option<&str>,
+        listed: Vec<(ObjectKey, DynamicObject)>,
+    ) {
+        if let Err(error) = self.try_replace_gvk(gvk, namespace, listed).await {
+            warn!(?gvk, ?error, "Unable to replace cached objects.");
+        }
+    }
+
+    async fn try_replace_gvk(
+        &self,
+        gvk: &GroupVersionKind,
+        namespace: Option<&str>,
+        listed: Vec<(ObjectKey, DynamicObject)>,
+    ) -> anyhow::Result<()> {
+        let mut index_map = self.indexes.write().await;
+        let mut indexes = index_map.get_mut(gvk);
+        let mut previous: HashMap<ObjectKey, serde_json::Value> = self
+            .backend
+            .list(gvk)
+            .await?
+            .into_iter()
+            .filter(|(key, _)| self.holds(key, namespace))
+            .collect();
+        {
+            let mut bounds = self.lock_bounds();
+            for object_key in previous.keys() {
+                if let Some(ref mut indexes) = indexes {
+                    for index in indexes.values_mut() {
+                        index.remove(object_key);
+                    }
+                }
+                bounds.remove(gvk, object_key);
+            }
+        }
+        for (object_key, dynamic_object) in listed {
+            if let Some(ref mut indexes) = indexes {
+                for index in indexes.values_mut() {
+                    index.insert(&object_key, &dynamic_object);
+                }
+            }
+            let value = serde_json::to_value(&dynamic_object)?;
+            match previous.remove(&object_key) {
+                None => self.notify(gvk, || StoreEvent::Added(value.clone())),
+                Some(old) if old != value => {
+                    self.notify(gvk, || StoreEvent::Updated(value.clone()))
+                }
+                Some(_) => (),
+            }
+            self.backend.insert(gvk, object_key.clone(), value).await?;
+            let evicted = self.touch(gvk, &object_key).await?;
+            evict(&*self.backend, gvk, indexes.as_deref_mut(), evicted).await?;
+        }
+        for (object_key, old) in previous {
+            self.backend.remove(gvk, &object_key).await?;
+            self.notify(gvk, || StoreEvent::Deleted(old));
+        }
+        Ok(())
+    }
+
+    /// Send a change to subscribers, if there are any.
+    fn notify(
+        &self,
+        gvk: &GroupVersionKind,
+        event: impl FnOnce() -> StoreEvent<serde_json::Value>,
+    ) {
+        if self.changes.receiver_count() > 0 {
+            let _ = self.changes.send((gvk.clone(), event().map(Arc::new)));
+        }
+    }
+
+    /// Record that the objects of the kind have been listed.
+    pub(crate) async fn mark_synced(&self, gvk: &GroupVersionKind) {
+        if self.synced.write().await.insert(gvk.clone()) {
+            let _ = self.synced_tx.send(());
+        }
+    }
+
+    /// Whether the objects of type `R` have been listed, so that the cache
+    /// reflects the cluster as of some point in time.
+    pub async fn is_synced<R: k8s_openapi::Resource>(&self) -> bool {
+        let key = GroupVersionKind::gvk(R::GROUP, R::VERSION, R::KIND);
+        self.synced.read().await.contains(&key)
+    }
+
+    /// Wait until the objects of every kind in `gvks` have been listed.
+    pub(crate) async fn wait_synced(&self, gvks: &[GroupVersionKind]) {
+        let mut synced_rx = self.synced_rx.clone();
+        loop {
+            {
+                let synced = self.synced.read().await;
+                if gvks.iter().all(|gvk| synced.contains(gvk)) {
+                    return;
+                }
+            }
+            if synced_rx.changed().await.is_err() {
+                return;
+            }
+        }
     }
 
     /// Delete a cached object.
-    #[cfg(not(feature = "admission-webhook"))]
     pub(crate) async fn delete_gvk(
         &self,
         namespace: Option<String>,
         name: String,
         gvk: &GroupVersionKind,
     ) {
-        let mut objects = self.objects.write().await;
-        let key = gvk.clone();
-        let resource_objects = (*objects).entry(key).or_insert_with(HashMap::new);
-        let object_key = ObjectKey::new(namespace, name);
-        resource_objects.remove(&object_key);
+        let mut index_map = self.indexes.write().await;
+        let object_key = self.key(namespace, name);
+        if let Some(indexes) = index_map.get_mut(gvk) {
+            for index in indexes.values_mut() {
+                index.remove(&object_key);
+            }
+        }
+        self.lock_bounds().remove(gvk, &object_key);
+        match self.backend.remove(gvk, &object_key).await {
+            Ok(Some(old)) => self.notify(gvk, || StoreEvent::Deleted(old)),
+            Ok(None) => (),
+            Err(error) => warn!(?gvk, ?error, "Unable to delete cached object."),
+        }
+    }
+
+    /// Apply an event of a watch of objects of kind `gvk`, which is
+    /// restricted to `namespace`, if any.
+    pub(crate) async fn apply(
+        &self,
+        gvk: &GroupVersionKind,
+        namespace: Option<&str>,
+        event: DynamicEvent,
+    ) {
+        match event {
+            Event::Applied(dynamic_object) => {
+                let namespace = dynamic_object.metadata.namespace.clone();
+                let name = match dynamic_object.metadata.name.clone() {
+                    Some(name) => name,
+                    None => {
+                        warn!(gvk=?gvk, "Object without name.");
+                        return;
+                    }
+                };
+                self.insert_gvk(namespace, name, gvk, dynamic_object).await;
+            }
+            Event::Deleted(dynamic_object) => {
+                let namespace = dynamic_object.metadata.namespace.clone();
+                let name = match dynamic_object.metadata.name.clone() {
+                    Some(name) => name,
+                    None => {
+                        warn!(gvk=?gvk, "Object without name.");
+                        return;
+                    }
+                };
+                self.delete_gvk(namespace, name, gvk).await;
+            }
+            Event::Restarted(dynamic_objects) => {
+                let mut listed = Vec::with_capacity(dynamic_objects.len());
+                for dynamic_object in dynamic_objects {
+                    let object_namespace = dynamic_object.metadata.namespace.clone();
+                    let name = match dynamic_object.metadata.name.clone() {
+                        Some(name) => name,
+                        None => {
+                            warn!(gvk=?gvk, "Object without name.");
+                            continue;
+                        }
+                    };
+                    listed.push((self.key(object_namespace, name), dynamic_object));
+                }
+                self.replace_gvk(gvk, namespace, listed).await;
+                self.mark_synced(gvk).await;
+            }
+        }
     }
 
     /// Insert an object that has already been type erased.
-    #[cfg(not(feature = "admission-webhook"))]
     pub(crate) async fn insert_gvk(
         &self,
         namespace: Option<String>,
@@ -83,11 +607,151 @@ impl Store {
         gvk: &GroupVersionKind,
         dynamic_object: DynamicObject,
     ) {
-        let mut objects = self.objects.write().await;
-        let key = gvk.clone();
-        let resource_objects = (*objects).entry(key).or_insert_with(HashMap::new);
-        let object_key = ObjectKey::new(namespace, name);
-        resource_objects.insert(object_key, serde_json::to_value(&dynamic_object).unwrap());
+        let object_key = self.key(namespace, name);
+        if let Err(error) = self.try_insert_gvk(object_key, gvk, dynamic_object).await {
+            warn!(?gvk, ?error, "Unable to cache object.");
+        }
+    }
+
+    async fn try_insert_gvk(
+        &self,
+        object_key: ObjectKey,
+        gvk: &GroupVersionKind,
+        dynamic_object: DynamicObject,
+    ) -> anyhow::Result<()> {
+        let mut index_map = self.indexes.write().await;
+        let mut indexes = index_map.get_mut(gvk);
+        if let Some(ref mut indexes) = indexes {
+            for index in indexes.values_mut() {
+                index.insert(&object_key, &dynamic_object);
+            }
+        }
+        let value = serde_json::to_value(&dynamic_object)?;
+        let subscribed = self.changes.receiver_count() > 0;
+        let event = if subscribed {
+            Some(value.clone())
+        } else {
+            None
+        };
+        let previous = self.backend.insert(gvk, object_key.clone(), value).await?;
+        if let Some(value) = event {
+            match previous {
+                None => self.notify(gvk, || StoreEvent::Added(value)),
+                Some(old) if old != value => self.notify(gvk, || StoreEvent::Updated(value)),
+                Some(_) => (),
+            }
+        }
+        let evicted = self.touch(gvk, &object_key).await?;
+        evict(&*self.backend, gvk, indexes, evicted).await
+    }
+
+    /// Subscribe to changes of the cached objects of type `R`, as they are
+    /// cached. Only changes made after subscribing are delivered.
+    ///
+    /// When the watch of a kind restarts, it is listed again and only the
+    /// differences to the previously cached objects are delivered. A
+    /// subscriber which falls behind by more than 1024 changes, across all
+    /// kinds, misses the oldest of them, which is logged. Objects which cannot
+    /// be deserialized as type `R`, such as those of
+    /// [metadata-only](crate::ControllerBuilder::watches_metadata_only)
+    /// watches, are skipped.
+    ///
+    /// ```
+    /// # use krator::{Store, StoreEvent};
+    /// # use k8s_openapi::api::core::v1::ConfigMap;
+    /// # use futures::StreamExt;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// # let store = Store::new();
+    /// let mut changes = Box::pin(store.subscribe::<ConfigMap>());
+    /// # drop(store);
+    /// while let Some(event) = changes.next().await {
+    ///     if let StoreEvent::Deleted(config_map) = event {
+    ///         println!("Deleted {:?}", config_map.metadata.name);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn subscribe<R: 'static + k8s_openapi::Resource + Clone + DeserializeOwned + Send>(
+        &self,
+    ) -> impl Stream<Item = StoreEvent<R>> + Send + 'static {
+        let key = GroupVersionKind::gvk(R::GROUP, R::VERSION, R::KIND);
+        BroadcastStream::new(self.changes.subscribe()).filter_map(move |change| match change {
+            Ok((gvk, event)) if gvk == key => {
+                match event.try_map(|value| serde_json::from_value::<R>((*value).clone())) {
+                    Ok(event) => Some(event),
+                    Err(error) => {
+                        warn!(?error, "Unable to deserialize changed object.");
+                        None
+                    }
+                }
+            }
+            Ok(_) => None,
+            Err(error) => {
+                warn!(%error, "Store subscriber fell behind, missed changes.");
+                None
+            }
+        })
+    }
+
+    /// Index the cached objects of type `R` by the values `f` returns for
+    /// them, so that [by_index](Self::by_index) can look them up without
+    /// scanning every cached object. An object may be indexed under any
+    /// number of values, for example Pods by `spec.nodeName` or objects by
+    /// the UIDs of their owners.
+    ///
+    /// Objects which are already cached are indexed right away. An index
+    /// registered earlier under the same `name` for `R` is replaced.
+    ///
+    /// ```
+    /// # use krator::Store;
+    /// # use k8s_openapi::api::core::v1::Pod;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// # let store = Store::new();
+    /// store
+    ///     .add_index::<Pod, _>("node", |pod| {
+    ///         pod.spec.iter().flat_map(|spec| spec.node_name.clone()).collect()
+    ///     })
+    ///     .await;
+    /// let pods = store.by_index::<Pod>("node", "node-1").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn add_index<R, F>(&self, name: &str, f: F)
+    where
+        R: 'static + k8s_openapi::Resource + Clone + DeserializeOwned,
+        F: Fn(&R) -> Vec<String> + Send + Sync + 'static,
+    {
+        let gvk = GroupVersionKind::gvk(R::GROUP, R::VERSION, R::KIND);
+        self.add_index_gvk(&gvk, name, index_fn(f)).await
+    }
+
+    /// Index the cached objects of a kind which has already been type erased.
+    pub(crate) async fn add_index_gvk(&self, gvk: &GroupVersionKind, name: &str, f: IndexFn) {
+        let mut index_map = self.indexes.write().await;
+        let mut index = Index::new(f);
+        match self.backend.list(gvk).await {
+            Ok(listed) => {
+                for (object_key, value) in listed {
+                    if !self.holds(&object_key, None) {
+                        continue;
+                    }
+                    match serde_json::from_value::<DynamicObject>(value) {
+                        Ok(dynamic_object) => index.insert(&object_key, &dynamic_object),
+                        Err(error) => warn!(?error, "Unable to index cached object."),
+                    }
+                }
+            }
+            Err(error) => warn!(?gvk, ?error, "Unable to index cached objects."),
+        }
+        index_map
+            .entry(gvk.clone())
+            .or_insert_with(HashMap::new)
+            .insert(name.to_string(), index);
     }
 
     /// Fetch an object.
@@ -109,27 +773,430 @@ impl Store {
         namespace: Option<&str>,
         name: &str,
     ) -> anyhow::Result<Option<R>> {
-        let objects = self.objects.read().await;
         let key = GroupVersionKind::gvk(R::GROUP, R::VERSION, R::KIND);
-        let object_key = ObjectKey::new(namespace.map(|s| s.to_string()), name.to_string());
-        match (*objects).get(&key) {
-            Some(resource_objects) => match resource_objects.get(&object_key) {
-                Some(value) => match serde_json::from_value::<R>(value.clone()) {
-                    Ok(object) => Ok(Some(object)),
-                    Err(e) => {
-                        anyhow::bail!(
+        let object_key = self.key(namespace.map(|s| s.to_string()), name.to_string());
+        if let Some(value) = self.backend.get(&key, &object_key).await? {
+            // Only bounded kinds are tracked, and their size is unchanged.
+            self.lock_bounds().touch(&key, &object_key, 0);
+            return match serde_json::from_value::<R>(value) {
+                Ok(object) => Ok(Some(object)),
+                Err(e) => {
+                    anyhow::bail!(
+                        "Could not interpret interred object as type {}/{} {}: {:?}",
+                        R::GROUP,
+                        R::VERSION,
+                        R::KIND,
+                        e
+                    );
+                }
+            };
+        }
+        // Objects of bounded kinds may have been evicted.
+        let client = {
+            let bounds = self.lock_bounds();
+            if bounds.limits.contains_key(&key) {
+                bounds.client.clone()
+            } else {
+                None
+            }
+        };
+        match client {
+            Some(client) => fetch::<R>(client, namespace, name).await,
+            // TODO: Should this be an error since we probably arent tracking that resource type?
+            None => Ok(None),
+        }
+    }
+
+    /// Wait until the object is cached, for example after creating it, and
+    /// fetch it. Resolves right away if it is already cached.
+    ///
+    /// ```
+    /// # use krator::Store;
+    /// # use k8s_openapi::api::core::v1::ConfigMap;
+    /// # use std::time::Duration;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// # let store = Store::new();
+    /// let timeout = Duration::from_millis(10);
+    /// match store.wait_for::<ConfigMap>(Some("namespace"), "name", timeout).await? {
+    ///     Some(config_map) => println!("{:?}", config_map.data),
+    ///     None => println!("Timed out."),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * If the serialized data cannot be deserialized as type `R`.
+    ///
+    /// # Returns
+    ///
+    /// This method will return `None` if the object is not cached within
+    /// `timeout`.
+    pub async fn wait_for<R: 'static + k8s_openapi::Resource + Clone + DeserializeOwned>(
+        &self,
+        namespace: Option<&str>,
+        name: &str,
+        timeout: std::time::Duration,
+    ) -> anyhow::Result<Option<R>> {
+        // Subscribe before looking, so that no change is missed in between.
+        let mut changes = self.changes.subscribe();
+        let wait = self.next_cached::<R>(&mut changes, namespace, name);
+        match tokio::time::timeout(timeout, wait).await {
+            Ok(result) => result,
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Fetch the object once it is cached, watching `changes` for it.
+    async fn next_cached<R: 'static + k8s_openapi::Resource + Clone + DeserializeOwned>(
+        &self,
+        changes: &mut broadcast::Receiver<Change>,
+        namespace: Option<&str>,
+        name: &str,
+    ) -> anyhow::Result<Option<R>> {
+        let key = GroupVersionKind::gvk(R::GROUP, R::VERSION, R::KIND);
+        if let Some(object) = self.get::<R>(namespace, name).await? {
+            return Ok(Some(object));
+        }
+        loop {
+            let value = match changes.recv().await {
+                Ok((gvk, StoreEvent::Added(value))) | Ok((gvk, StoreEvent::Updated(value)))
+                    if gvk == key =>
+                {
+                    value
+                }
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    match self.get::<R>(namespace, name).await? {
+                        Some(object) => return Ok(Some(object)),
+                        None => continue,
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(None),
+            };
+            let matches = value.pointer("/metadata/name").and_then(|n| n.as_str()) == Some(name)
+                && value
+                    .pointer("/metadata/namespace")
+                    .and_then(|n| n.as_str())
+                    == namespace;
+            if matches {
+                return serde_json::from_value::<R>((*value).clone())
+                    .map(Some)
+                    .map_err(|e| {
+                        anyhow::anyhow!(
                             "Could not interpret interred object as type {}/{} {}: {:?}",
                             R::GROUP,
                             R::VERSION,
                             R::KIND,
                             e
-                        );
-                    }
+                        )
+                    });
+            }
+        }
+    }
+
+    /// Fetch every cached object of type `R`, ordered by namespace and name.
+    /// With `namespace`, only objects in that namespace are returned.
+    ///
+    /// ```
+    /// # use krator::Store;
+    /// # use k8s_openapi::api::core::v1::ConfigMap;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// # let store = Store::new();
+    /// for config_map in store.list::<ConfigMap>(Some("namespace")).await? {
+    ///     println!("{:?}", config_map.metadata.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * If the serialized data cannot be deserialized as type `R`.
+    pub async fn list<R: 'static + k8s_openapi::Resource + Clone + DeserializeOwned>(
+        &self,
+        namespace: Option<&str>,
+    ) -> anyhow::Result<Vec<R>> {
+        let key = GroupVersionKind::gvk(R::GROUP, R::VERSION, R::KIND);
+        let listed = self
+            .backend
+            .list(&key)
+            .await?
+            .into_iter()
+            .filter(|(key, _)| self.holds(key, namespace))
+            .collect();
+        interpret(listed)
+    }
+
+    /// Capture every cached kind and how many objects of it are cached,
+    /// including the objects themselves with `include_objects`, for example
+    /// to find out why an object is missing from the cache.
+    ///
+    /// # Errors
+    ///
+    /// * If the objects cannot be read from the [StoreBackend].
+    pub async fn snapshot(&self, include_objects: bool) -> anyhow::Result<StoreSnapshot> {
+        let mut gvks: HashSet<GroupVersionKind> = self.backend.kinds().await?.into_iter().collect();
+        let synced = self.synced.read().await.clone();
+        gvks.extend(synced.iter().cloned());
+        let mut kinds = Vec::with_capacity(gvks.len());
+        for gvk in gvks {
+            let (count, objects) = if include_objects {
+                let mut listed = self.backend.list(&gvk).await?;
+                listed.sort_by(|(a, _), (b, _)| {
+                    (a.namespace(), a.name()).cmp(&(b.namespace(), b.name()))
+                });
+                let objects: Vec<serde_json::Value> =
+                    listed.into_iter().map(|(_, value)| value).collect();
+                (objects.len(), Some(objects))
+            } else {
+                (self.backend.count(&gvk).await?, None)
+            };
+            kinds.push(KindSnapshot {
+                synced: synced.contains(&gvk),
+                group: gvk.group,
+                version: gvk.version,
+                kind: gvk.kind,
+                count,
+                objects,
+            });
+        }
+        kinds.sort_by(|a, b| (&a.group, &a.version, &a.kind).cmp(&(&b.group, &b.version, &b.kind)));
+        Ok(StoreSnapshot { kinds })
+    }
+
+    /// Serve a [snapshot](Self::snapshot) as JSON on `GET /debug/store` at
+    /// `address`, including the cached objects with `?objects=true`, or with
+    /// `500 Internal Server Error` if it cannot be captured. Runs until the
+    /// server fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `address` cannot be bound, for example because it
+    /// is already in use.
+    #[cfg(feature = "debug-endpoint")]
+    pub async fn serve(self, address: impl Into<std::net::SocketAddr>) -> anyhow::Result<()> {
+        use warp::Filter;
+
+        #[derive(serde::Deserialize)]
+        struct Query {
+            #[serde(default)]
+            objects: bool,
+        }
+
+        let routes = warp::get()
+            .and(warp::path!("debug" / "store"))
+            .and(warp::query::<Query>())
+            .and_then(move |query: Query| {
+                let store = self.clone();
+                async move {
+                    let reply = match store.snapshot(query.objects).await {
+                        Ok(snapshot) => warp::reply::with_status(
+                            warp::reply::json(&snapshot),
+                            warp::http::StatusCode::OK,
+                        ),
+                        Err(error) => warp::reply::with_status(
+                            warp::reply::json(&format!("{:#}", error)),
+                            warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+                        ),
+                    };
+                    Ok::<_, warp::Rejection>(reply)
+                }
+            });
+        let address = address.into();
+        let (_, server) = warp::serve(routes)
+            .try_bind_ephemeral(address)
+            .with_context(|| format!("Failed to bind debug endpoint to {}", address))?;
+        server.await;
+        Ok(())
+    }
+
+    /// Fetch every cached object of type `R` indexed under `value` by the
+    /// index `name`, ordered by namespace and name.
+    ///
+    /// # Errors
+    ///
+    /// * If no index `name` was [added](Self::add_index) for type `R`.
+    /// * If the serialized data cannot be deserialized as type `R`.
+    pub async fn by_index<R: 'static + k8s_openapi::Resource + Clone + DeserializeOwned>(
+        &self,
+        name: &str,
+        value: &str,
+    ) -> anyhow::Result<Vec<R>> {
+        let indexes = self.indexes.read().await;
+        let key = GroupVersionKind::gvk(R::GROUP, R::VERSION, R::KIND);
+        let index = match indexes.get(&key).and_then(|indexes| indexes.get(name)) {
+            Some(index) => index,
+            None => anyhow::bail!(
+                "No index {} for type {}/{} {}",
+                name,
+                R::GROUP,
+                R::VERSION,
+                R::KIND
+            ),
+        };
+        let keys = match index.keys.get(value) {
+            Some(keys) => keys,
+            None => return Ok(vec![]),
+        };
+        let mut indexed = Vec::with_capacity(keys.len());
+        for object_key in keys {
+            if let Some(value) = self.backend.get(&key, object_key).await? {
+                indexed.push((object_key.clone(), value));
+            }
+        }
+        interpret(indexed)
+    }
+
+    /// Fetch every cached object of type `R` with an owner reference to the
+    /// object with `owner_uid`, ordered by namespace and name.
+    ///
+    /// # Errors
+    ///
+    /// * If the serialized data cannot be deserialized as type `R`.
+    pub async fn owned_by<R: 'static + k8s_openapi::Resource + Clone + DeserializeOwned>(
+        &self,
+        owner_uid: &str,
+    ) -> anyhow::Result<Vec<R>> {
+        let key = GroupVersionKind::gvk(R::GROUP, R::VERSION, R::KIND);
+        let owned = self
+            .backend
+            .list(&key)
+            .await?
+            .into_iter()
+            .filter(
+                |(_, value)| match value.pointer("/metadata/ownerReferences") {
+                    Some(serde_json::Value::Array(owners)) => owners.iter().any(|owner| {
+                        owner.get("uid").and_then(|uid| uid.as_str()) == Some(owner_uid)
+                    }),
+                    _ => false,
                 },
-                None => Ok(None),
+            )
+            .collect();
+        interpret(owned)
+    }
+}
+
+/// Read back objects as type `R`, ordered by namespace and name.
+fn interpret<R: k8s_openapi::Resource + DeserializeOwned>(
+    mut listed: Vec<(ObjectKey, serde_json::Value)>,
+) -> anyhow::Result<Vec<R>> {
+    listed.sort_by(|(a, _), (b, _)| (a.namespace(), a.name()).cmp(&(b.namespace(), b.name())));
+    listed
+        .into_iter()
+        .map(|(_, value)| {
+            serde_json::from_value::<R>(value).map_err(|e| {
+                anyhow::anyhow!(
+                    "Could not interpret interred object as type {}/{} {}: {:?}",
+                    R::GROUP,
+                    R::VERSION,
+                    R::KIND,
+                    e
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k8s_openapi::api::core::v1::ConfigMap;
+    use kube::api::ObjectMeta;
+
+    async fn insert(store: &Store, name: &str) {
+        let config_map = ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("default".to_string()),
+                ..Default::default()
             },
-            // TODO: Should this be an error since we probably arent tracking that resource type?
-            None => Ok(None),
+            ..Default::default()
+        };
+        let gvk = GroupVersionKind::gvk("", "v1", "ConfigMap");
+        let object = crate::util::dynamic_object(&config_map).unwrap();
+        store
+            .insert_gvk(Some("default".to_string()), name.to_string(), &gvk, object)
+            .await;
+    }
+
+    async fn insert_data(store: &Store, name: &str, value: &str) {
+        let config_map = ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("default".to_string()),
+                ..Default::default()
+            },
+            data: Some([("cluster".to_string(), value.to_string())].into()),
+            ..Default::default()
+        };
+        let gvk = GroupVersionKind::gvk("", "v1", "ConfigMap");
+        let object = crate::util::dynamic_object(&config_map).unwrap();
+        store
+            .insert_gvk(Some("default".to_string()), name.to_string(), &gvk, object)
+            .await;
+    }
+
+    async fn cached(store: &Store, name: &str) -> bool {
+        store
+            .get::<ConfigMap>(Some("default"), name)
+            .await
+            .unwrap()
+            .is_some()
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_used_object() {
+        let store = Store::new().with_limit::<ConfigMap>(3);
+        insert(&store, "a").await;
+        insert(&store, "b").await;
+        insert(&store, "c").await;
+        // Reading "a" leaves "b" as the least recently used object.
+        assert!(cached(&store, "a").await);
+        insert(&store, "d").await;
+
+        assert!(!cached(&store, "b").await);
+        for name in ["a", "c", "d"] {
+            assert!(cached(&store, name).await, "{} was evicted", name);
         }
+        assert_eq!(store.list::<ConfigMap>(None).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn does_not_evict_unbounded_kinds() {
+        let store = Store::new();
+        for name in ["a", "b", "c", "d"] {
+            insert(&store, name).await;
+        }
+        assert_eq!(store.list::<ConfigMap>(None).await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn keeps_clusters_apart_in_shared_backend() {
+        let east = Store::new().for_cluster("east");
+        let west = east.clone().for_cluster("west");
+        insert_data(&east, "moose", "east").await;
+        insert_data(&west, "moose", "west").await;
+
+        for (store, cluster) in [(&east, "east"), (&west, "west")] {
+            let config_map = store
+                .get::<ConfigMap>(Some("default"), "moose")
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(config_map.data.unwrap()["cluster"], cluster);
+            assert_eq!(store.list::<ConfigMap>(None).await.unwrap().len(), 1);
+        }
+
+        let gvk = GroupVersionKind::gvk("", "v1", "ConfigMap");
+        west.delete_gvk(Some("default".to_string()), "moose".to_string(), &gvk)
+            .await;
+        assert!(cached(&east, "moose").await);
+        assert!(!cached(&west, "moose").await);
     }
 }
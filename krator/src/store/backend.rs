@@ -0,0 +1,142 @@
+//! Where the objects of a [Store](crate::Store) are kept.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use kube::api::GroupVersionKind;
+use tokio::sync::RwLock;
+
+use crate::object::ObjectKey;
+
+/// Keeps the objects cached by a [Store](crate::Store), serialized as JSON,
+/// by kind and [ObjectKey], which includes the cluster of the object when
+/// the backend is shared by the clusters of a
+/// [MultiClusterRuntime](crate::MultiClusterRuntime).
+///
+/// The default [MemoryBackend] keeps them in memory. Other implementations
+/// can keep them elsewhere, for example in a database shared by the
+/// replicas of an operator for very large clusters. Indexes, limits and
+/// subscriptions of the Store only reflect the changes it made itself.
+///
+/// Writes to a Store are serialized before they reach the backend, but
+/// reads may happen concurrently with them.
+#[async_trait]
+pub trait StoreBackend: Send + Sync + 'static {
+    /// Fetch an object.
+    async fn get(
+        &self,
+        gvk: &GroupVersionKind,
+        key: &ObjectKey,
+    ) -> anyhow::Result<Option<serde_json::Value>>;
+
+    /// Fetch every object of a kind, in any order.
+    async fn list(
+        &self,
+        gvk: &GroupVersionKind,
+    ) -> anyhow::Result<Vec<(ObjectKey, serde_json::Value)>>;
+
+    /// Number of objects of a kind.
+    async fn count(&self, gvk: &GroupVersionKind) -> anyhow::Result<usize> {
+        Ok(self.list(gvk).await?.len())
+    }
+
+    /// Every kind with objects.
+    async fn kinds(&self) -> anyhow::Result<Vec<GroupVersionKind>>;
+
+    /// Insert or replace an object, returning the object it replaced.
+    async fn insert(
+        &self,
+        gvk: &GroupVersionKind,
+        key: ObjectKey,
+        value: serde_json::Value,
+    ) -> anyhow::Result<Option<serde_json::Value>>;
+
+    /// Remove an object, returning it.
+    async fn remove(
+        &self,
+        gvk: &GroupVersionKind,
+        key: &ObjectKey,
+    ) -> anyhow::Result<Option<serde_json::Value>>;
+}
+
+/// Keeps the objects of a [Store](crate::Store) in memory. Used by default.
+#[derive(Default)]
+pub struct MemoryBackend {
+    objects: RwLock<HashMap<GroupVersionKind, HashMap<ObjectKey, serde_json::Value>>>,
+}
+
+impl MemoryBackend {
+    /// Create an empty backend.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+#[async_trait]
+impl StoreBackend for MemoryBackend {
+    async fn get(
+        &self,
+        gvk: &GroupVersionKind,
+        key: &ObjectKey,
+    ) -> anyhow::Result<Option<serde_json::Value>> {
+        let objects = self.objects.read().await;
+        Ok(objects
+            .get(gvk)
+            .and_then(|resource_objects| resource_objects.get(key))
+            .cloned())
+    }
+
+    async fn list(
+        &self,
+        gvk: &GroupVersionKind,
+    ) -> anyhow::Result<Vec<(ObjectKey, serde_json::Value)>> {
+        let objects = self.objects.read().await;
+        Ok(objects
+            .get(gvk)
+            .map(|resource_objects| {
+                resource_objects
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    async fn count(&self, gvk: &GroupVersionKind) -> anyhow::Result<usize> {
+        let objects = self.objects.read().await;
+        Ok(objects.get(gvk).map(HashMap::len).unwrap_or_default())
+    }
+
+    async fn kinds(&self) -> anyhow::Result<Vec<GroupVersionKind>> {
+        let objects = self.objects.read().await;
+        Ok(objects
+            .iter()
+            .filter(|(_, resource_objects)| !resource_objects.is_empty())
+            .map(|(gvk, _)| gvk.clone())
+            .collect())
+    }
+
+    async fn insert(
+        &self,
+        gvk: &GroupVersionKind,
+        key: ObjectKey,
+        value: serde_json::Value,
+    ) -> anyhow::Result<Option<serde_json::Value>> {
+        let mut objects = self.objects.write().await;
+        Ok(objects
+            .entry(gvk.clone())
+            .or_insert_with(HashMap::new)
+            .insert(key, value))
+    }
+
+    async fn remove(
+        &self,
+        gvk: &GroupVersionKind,
+        key: &ObjectKey,
+    ) -> anyhow::Result<Option<serde_json::Value>> {
+        let mut objects = self.objects.write().await;
+        Ok(objects
+            .get_mut(gvk)
+            .and_then(|resource_objects| resource_objects.remove(key)))
+    }
+}
@@ -0,0 +1,110 @@
+//! Introspection of the state machines run by a runtime.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Tracks which state each object's state machine is in. Cloning returns a
+/// handle to the same tracker.
+///
+/// With the `debug-endpoint` feature, the positions can be served over HTTP
+/// with [serve](StateTracker::serve).
+#[derive(Clone, Default)]
+pub struct StateTracker {
+    objects: Arc<Mutex<BTreeMap<(Option<String>, String), Entry>>>,
+}
+
+struct Entry {
+    state: &'static str,
+    entered: Instant,
+    last_error: Option<String>,
+}
+
+/// The current position of an object's state machine.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectPosition {
+    /// Namespace of the object, if it is namespaced.
+    pub namespace: Option<String>,
+    /// Name of the object.
+    pub name: String,
+    /// Type name of the current state.
+    pub state: &'static str,
+    /// Time since the current state was entered.
+    #[serde(serialize_with = "serialize_seconds", rename = "secondsInState")]
+    pub time_in_state: Duration,
+    /// The error of the last transition which failed, if any.
+    pub last_error: Option<String>,
+}
+
+fn serialize_seconds<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+impl StateTracker {
+    /// Create an empty tracker.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Positions of every tracked object, ordered by namespace and name.
+    pub fn snapshot(&self) -> Vec<ObjectPosition> {
+        self.objects
+            .lock()
+            .expect("State tracker lock poisoned.")
+            .iter()
+            .map(|((namespace, name), entry)| ObjectPosition {
+                namespace: namespace.clone(),
+                name: name.clone(),
+                state: entry.state,
+                time_in_state: entry.entered.elapsed(),
+                last_error: entry.last_error.clone(),
+            })
+            .collect()
+    }
+
+    pub(crate) fn enter(&self, namespace: &Option<String>, name: &str, state: &'static str) {
+        let mut objects = self.objects.lock().expect("State tracker lock poisoned.");
+        let entry = objects
+            .entry((namespace.clone(), name.to_string()))
+            .or_insert(Entry {
+                state,
+                entered: Instant::now(),
+                last_error: None,
+            });
+        if entry.state != state {
+            entry.state = state;
+            entry.entered = Instant::now();
+        }
+    }
+
+    pub(crate) fn error(&self, namespace: &Option<String>, name: &str, error: &anyhow::Error) {
+        let mut objects = self.objects.lock().expect("State tracker lock poisoned.");
+        if let Some(entry) = objects.get_mut(&(namespace.clone(), name.to_string())) {
+            entry.last_error = Some(format!("{:#}", error));
+        }
+    }
+
+    pub(crate) fn remove(&self, namespace: &Option<String>, name: &str) {
+        self.objects
+            .lock()
+            .expect("State tracker lock poisoned.")
+            .remove(&(namespace.clone(), name.to_string()));
+    }
+
+    /// Serve the tracked positions as JSON on `GET /debug/states` at
+    /// `address`. Runs until the server fails.
+    #[cfg(feature = "debug-endpoint")]
+    pub async fn serve(self, address: impl Into<std::net::SocketAddr>) {
+        use warp::Filter;
+        let routes = warp::get()
+            .and(warp::path!("debug" / "states"))
+            .map(move || warp::reply::json(&self.snapshot()));
+        warp::serve(routes).run(address).await;
+    }
+}
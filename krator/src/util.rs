@@ -1,5 +1,8 @@
 //! Provides some utility functions for Krator.
 
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
 use kube::{api::DynamicObject, api::ResourceExt, Resource};
 use kube_runtime::watcher::Event;
 use serde::de::DeserializeOwned;
@@ -87,6 +90,15 @@ where
     Ok(serde_json::from_value::<R>(value)?)
 }
 
+/// Type erase an object of a concrete type, the inverse of
+/// [concrete_object].
+pub(crate) fn dynamic_object<R>(object: &R) -> anyhow::Result<DynamicObject>
+where
+    R: serde::Serialize,
+{
+    Ok(serde_json::from_value(serde_json::to_value(object)?)?)
+}
+
 /// Convert [DynamicEvent](crate::util::DynamicEvent) to
 /// concrete event `kube_runtime::watcher::Event<R>` where `R` implements
 /// `DeserializeOwned`.
@@ -121,3 +133,154 @@ where
 
 /// Used to refer to `kube_runtime::watcher::Event<kube::api::DynamicObject>`.
 pub type DynamicEvent = Event<DynamicObject>;
+
+/// Exponential backoff with jitter, used when retrying failed operations.
+///
+/// ```
+/// # use std::time::Duration;
+/// use krator::util::Backoff;
+/// let backoff = Backoff::default();
+/// assert!(backoff.delay(1) <= backoff.initial);
+/// assert!(backoff.delay(100) <= backoff.max);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Backoff {
+    /// Delay after the first failure.
+    pub initial: Duration,
+    /// Upper bound on the delay.
+    pub max: Duration,
+    /// Factor applied to the delay after each consecutive failure.
+    pub multiplier: f64,
+    /// Fraction of the delay, between `0.0` and `1.0`, which is randomly
+    /// subtracted so that retries from many clients are spread out.
+    pub jitter: f64,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay to wait after `attempt` consecutive failures, starting at 1.
+    /// Always between zero and `max`, even for a negative `multiplier`.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let max = self.max.as_secs_f64();
+        let base = (self.initial.as_secs_f64() * self.multiplier.powi(exponent)).min(max);
+        let jitter = self.jitter.clamp(0.0, 1.0) * random_fraction();
+        let seconds = base * (1.0 - jitter);
+        if seconds.is_nan() || seconds <= 0.0 {
+            Duration::ZERO
+        } else if seconds >= max {
+            // Converting `max` back from seconds could overflow.
+            self.max
+        } else {
+            Duration::from_secs_f64(seconds)
+        }
+    }
+}
+
+/// Random number in `[0, 1)`, good enough for jitter without pulling in a
+/// random number generator.
+fn random_fraction() -> f64 {
+    let hash = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backoff(jitter: f64) -> Backoff {
+        Backoff {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+            multiplier: 2.0,
+            jitter,
+        }
+    }
+
+    #[test]
+    fn delay_grows_exponentially() {
+        let backoff = backoff(0.0);
+        let delays: Vec<u64> = (1..=6)
+            .map(|attempt| backoff.delay(attempt).as_secs())
+            .collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 16, 32]);
+        // Attempts start at 1, but 0 is treated like the first failure.
+        assert_eq!(backoff.delay(0), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn delay_is_capped() {
+        let backoff = backoff(0.0);
+        assert_eq!(backoff.delay(7), Duration::from_secs(60));
+        assert_eq!(backoff.delay(100), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn delay_saturates_at_largest_max() {
+        let backoff = Backoff {
+            max: Duration::MAX,
+            ..backoff(0.0)
+        };
+        assert_eq!(backoff.delay(1), Duration::from_secs(1));
+        assert_eq!(backoff.delay(u32::MAX), Duration::MAX);
+    }
+
+    #[test]
+    fn delay_is_never_negative() {
+        let backoff = Backoff {
+            multiplier: -2.0,
+            ..backoff(0.0)
+        };
+        assert_eq!(backoff.delay(1), Duration::from_secs(1));
+        assert_eq!(backoff.delay(2), Duration::ZERO);
+        assert_eq!(backoff.delay(3), Duration::from_secs(4));
+        assert_eq!(backoff.delay(u32::MAX), Duration::ZERO);
+    }
+
+    #[test]
+    fn delay_does_not_overflow() {
+        let backoff = backoff(0.0);
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(60));
+        assert_eq!(backoff.delay(i32::MAX as u32 + 1), Duration::from_secs(60));
+
+        let constant = Backoff {
+            multiplier: 1.0,
+            ..backoff
+        };
+        assert_eq!(constant.delay(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let backoff = backoff(0.5);
+        for _ in 0..1000 {
+            let delay = backoff.delay(4);
+            assert!(delay >= Duration::from_secs(4), "{:?}", delay);
+            assert!(delay <= Duration::from_secs(8), "{:?}", delay);
+
+            let delay = backoff.delay(u32::MAX);
+            assert!(delay >= Duration::from_secs(30), "{:?}", delay);
+            assert!(delay <= Duration::from_secs(60), "{:?}", delay);
+        }
+    }
+
+    #[test]
+    fn jitter_is_clamped() {
+        assert_eq!(backoff(-1.0).delay(3), Duration::from_secs(4));
+        for _ in 0..1000 {
+            assert!(backoff(2.0).delay(3) <= Duration::from_secs(4));
+        }
+    }
+}
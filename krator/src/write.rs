@@ -0,0 +1,70 @@
+//! Writes krator makes to objects on behalf of operators.
+
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Context;
+use kube::api::Resource;
+use kube::Api;
+use serde::de::DeserializeOwned;
+use tracing::warn;
+
+use crate::util::Backoff;
+
+/// Number of times a write is retried after a conflict.
+const CONFLICT_RETRIES: u32 = 5;
+
+/// Perform a write to the object `name`, retrying with bounded backoff while
+/// it conflicts with a concurrent update. Before each retry the object is
+/// read again and passed to `write`, which should base the next attempt on
+/// it, for example by setting its `resourceVersion`. The first attempt gets
+/// `None`.
+pub(crate) async fn retry_conflicts<R, T, F, Fut>(
+    api: &Api<R>,
+    name: &str,
+    mut write: F,
+) -> anyhow::Result<T>
+where
+    R: Resource + Clone + DeserializeOwned,
+    F: FnMut(Option<R>) -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let backoff = Backoff {
+        initial: Duration::from_millis(100),
+        max: Duration::from_secs(2),
+        ..Default::default()
+    };
+    let mut latest = None;
+    let mut attempt = 0;
+    loop {
+        let error = match write(latest.take()).await {
+            Ok(written) => return Ok(written),
+            Err(error) => error,
+        };
+        if attempt >= CONFLICT_RETRIES || !has_status(&error, 409) {
+            return Err(error);
+        }
+        attempt += 1;
+        let delay = backoff.delay(attempt);
+        warn!(
+            %name,
+            attempt,
+            ?delay,
+            "Conflict writing object, retrying with its latest version."
+        );
+        tokio::time::sleep(delay).await;
+        latest = Some(
+            api.get(name)
+                .await
+                .with_context(|| format!("Failed to read {} after conflict", name))?,
+        );
+    }
+}
+
+/// Whether `error` is caused by an API response with status `code`.
+pub(crate) fn has_status(error: &anyhow::Error, code: u16) -> bool {
+    matches!(
+        error.downcast_ref::<kube::Error>(),
+        Some(kube::Error::Api(response)) if response.code == code
+    )
+}
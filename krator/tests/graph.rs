@@ -0,0 +1,147 @@
+//! Snapshots of the exported graph of a derived state machine.
+#![cfg(feature = "derive-graph")]
+
+use std::sync::Arc;
+
+use k8s_openapi::api::core::v1::ConfigMap;
+use krator::graph::{export, Format, Graph};
+use krator::{
+    Manifest, ObjectState, ObjectStatus, Operator, SharedState, StateMachine, Transition,
+};
+use tokio::sync::RwLock;
+
+struct MooseState;
+
+#[async_trait::async_trait]
+impl ObjectState for MooseState {
+    type Manifest = ConfigMap;
+    type Status = MooseStatus;
+    type SharedState = ();
+    async fn async_drop(self, _shared: &mut ()) {}
+}
+
+struct MooseStatus;
+
+impl ObjectStatus for MooseStatus {
+    fn json_patch(&self) -> serde_json::Value {
+        serde_json::json!({})
+    }
+
+    fn failed(_error: &str) -> Self {
+        MooseStatus
+    }
+}
+
+#[derive(StateMachine)]
+#[state_machine(object_state = MooseState)]
+#[allow(dead_code)]
+enum MooseStates {
+    #[transition_to(Roam)]
+    Tagged,
+    #[transition_to(Sleep, Released)]
+    Roam,
+    #[transition_to(Roam)]
+    Sleep,
+    Released,
+}
+
+impl Tagged {
+    async fn run(
+        self: Box<Self>,
+        _shared: SharedState<()>,
+        _state: &mut MooseState,
+        _manifest: Manifest<ConfigMap>,
+    ) -> Transition<MooseState> {
+        Transition::next(self, Roam)
+    }
+}
+
+impl Roam {
+    async fn run(
+        self: Box<Self>,
+        _shared: SharedState<()>,
+        _state: &mut MooseState,
+        _manifest: Manifest<ConfigMap>,
+    ) -> Transition<MooseState> {
+        Transition::next(self, Released)
+    }
+}
+
+impl Sleep {
+    async fn run(
+        self: Box<Self>,
+        _shared: SharedState<()>,
+        _state: &mut MooseState,
+        _manifest: Manifest<ConfigMap>,
+    ) -> Transition<MooseState> {
+        Transition::next(self, Roam)
+    }
+}
+
+// Only used for its state graph.
+#[allow(dead_code)]
+struct MooseTracker;
+
+#[async_trait::async_trait]
+impl Operator for MooseTracker {
+    type Manifest = ConfigMap;
+    type Status = MooseStatus;
+    type ObjectState = MooseState;
+    type InitialState = Tagged;
+    type DeletedState = Released;
+
+    async fn initialize_object_state(&self, _manifest: &ConfigMap) -> anyhow::Result<MooseState> {
+        Ok(MooseState)
+    }
+
+    async fn shared_state(&self) -> SharedState<()> {
+        Arc::new(RwLock::new(()))
+    }
+
+    #[cfg(feature = "admission-webhook")]
+    async fn admission_hook_tls(&self) -> anyhow::Result<krator::admission::AdmissionTls> {
+        anyhow::bail!("Not serving admission webhooks")
+    }
+}
+
+#[test]
+fn exports_dot() {
+    let expected = r#"digraph {
+    "[*]" [shape=point];
+    "[deleted]" [shape=point];
+    "graph::Tagged" [label="Tagged"];
+    "graph::Roam" [label="Roam"];
+    "graph::Released" [label="Released"];
+    "graph::Sleep" [label="Sleep"];
+    "[*]" -> "graph::Tagged";
+    "[deleted]" -> "graph::Released" [style=dashed];
+    "graph::Tagged" -> "graph::Roam";
+    "graph::Roam" -> "graph::Sleep";
+    "graph::Roam" -> "graph::Released";
+    "graph::Sleep" -> "graph::Roam";
+}
+"#;
+    assert_eq!(export::<MooseTracker>(Format::Dot), expected);
+}
+
+#[test]
+fn exports_mermaid() {
+    let expected = r#"stateDiagram-v2
+    state "Tagged" as s0
+    state "Roam" as s1
+    state "Released" as s2
+    state "Sleep" as s3
+    [*] --> s0
+    note left of s2: On deletion
+    s0 --> s1
+    s1 --> s3
+    s1 --> s2
+    s3 --> s1
+"#;
+    assert_eq!(export::<MooseTracker>(Format::Mermaid), expected);
+}
+
+#[test]
+fn derived_graph_is_valid() {
+    Graph::of::<MooseTracker>().validate().unwrap();
+}
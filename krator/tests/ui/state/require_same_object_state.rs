@@ -54,8 +54,8 @@ impl State<PodState> for TestState {
         &self,
         _state: &mut PodState,
         _pod: &Pod,
-    ) -> anyhow::Result<Status> {
-        Ok(Default::default())
+    ) -> anyhow::Result<Option<Status>> {
+        Ok(Some(Default::default()))
     }
 }
 
@@ -74,8 +74,8 @@ impl State<OtherPodState> for OtherState {
         &self,
         _state: &mut OtherPodState,
         _pod: &Pod,
-    ) -> anyhow::Result<Status> {
-        Ok(Default::default())
+    ) -> anyhow::Result<Option<Status>> {
+        Ok(Some(Default::default()))
     }
 }
 
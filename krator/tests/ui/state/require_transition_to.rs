@@ -42,8 +42,8 @@ impl State<PodState> for TestState {
         &self,
         _state: &mut PodState,
         _pod: &Pod,
-    ) -> anyhow::Result<Status> {
-        Ok(Default::default())
+    ) -> anyhow::Result<Option<Status>> {
+        Ok(Some(Default::default()))
     }
 }
 
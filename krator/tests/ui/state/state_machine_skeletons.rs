@@ -0,0 +1,74 @@
+// Test that StateMachine generates State implementations.
+// run-pass
+// edition:2018
+extern crate anyhow;
+extern crate krator;
+extern crate k8s_openapi;
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Status;
+use k8s_openapi::api::core::v1::Pod;
+use krator::{Manifest, ObjectState, SharedState, State, StateMachine, Transition};
+
+struct PodState;
+struct ProviderState;
+
+#[krator::__private::async_trait::async_trait]
+impl ObjectState for PodState {
+    type Manifest = Pod;
+    type Status = Status;
+    type SharedState = ProviderState;
+    async fn async_drop(self, _provider_state: &mut ProviderState) { }
+}
+
+#[derive(StateMachine)]
+#[state_machine(object_state = PodState)]
+#[allow(dead_code)]
+enum PodStates {
+    #[transition_to(Running)]
+    Waiting,
+    #[transition_to(Waiting, Done)]
+    #[state_machine(status)]
+    Running { attempts: u32 },
+    Done,
+}
+
+impl Waiting {
+    async fn run(
+        self: Box<Self>,
+        _shared: SharedState<ProviderState>,
+        _state: &mut PodState,
+        _manifest: Manifest<Pod>,
+    ) -> Transition<PodState> {
+        Transition::next(self, Running { attempts: 1 })
+    }
+}
+
+impl Running {
+    async fn run(
+        self: Box<Self>,
+        _shared: SharedState<ProviderState>,
+        _state: &mut PodState,
+        _manifest: Manifest<Pod>,
+    ) -> Transition<PodState> {
+        Transition::next(self, Done)
+    }
+
+    async fn report_status(
+        &self,
+        _state: &mut PodState,
+        _manifest: &Pod,
+    ) -> anyhow::Result<Option<Status>> {
+        Ok(Some(Status {
+            message: Some(format!("Attempt {}", self.attempts)),
+            ..Default::default()
+        }))
+    }
+}
+
+fn assert_state<S: State<PodState>>() {}
+
+fn main() {
+    assert_state::<Waiting>();
+    assert_state::<Running>();
+    assert_state::<Done>();
+}
@@ -0,0 +1,16 @@
+// Test that unknown state_machine options are rejected.
+// edition:2018
+extern crate krator;
+
+use krator::StateMachine;
+
+#[derive(StateMachine)]
+#[allow(dead_code)]
+enum PodStates {
+    #[transition_to(Done)]
+    #[state_machine(automatic)]
+    Waiting,
+    Done,
+}
+
+fn main() {}